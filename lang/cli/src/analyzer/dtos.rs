@@ -1,9 +1,13 @@
 //! DTO extraction from parsed .rune files
 
-use rune_parser::{ParsedLine, LineKind};
+use rune_parser::{split_optional, ParsedLine, LineKind};
+use rune_parser::validate::normalize_type;
+use rune_parser::casing::to_kebab_case;
+use super::methods::string_to_type_ref;
+use serde::Serialize;
 
 /// Information about a DTO definition
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DtoInfo {
     pub name: String,
     pub kebab_name: String,
@@ -12,7 +16,7 @@ pub struct DtoInfo {
 }
 
 /// Information about a DTO property
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct PropertyInfo {
     pub name: String,
     pub type_ref: TypeRef,
@@ -21,50 +25,30 @@ pub struct PropertyInfo {
 }
 
 /// Type reference for properties
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum TypeRef {
     Primitive(String),      // "string", "number", "boolean", "void", "Uint8Array"
     Dto(String),            // "GetRecordingDto"
     Custom(String),         // Custom type that resolves to primitive
 }
 
-/// Convert PascalCase or camelCase to kebab-case
-pub fn to_kebab_case(s: &str) -> String {
-    let mut result = String::new();
-    for (i, c) in s.chars().enumerate() {
-        if c.is_uppercase() {
-            if i > 0 {
-                result.push('-');
-            }
-            result.push(c.to_lowercase().next().unwrap());
-        } else {
-            result.push(c);
-        }
-    }
-    result
-}
-
 /// Parse property string to extract property info
 fn parse_property(prop: &str) -> PropertyInfo {
     // Check for optional suffix
-    let (prop_str, optional) = if prop.ends_with('?') {
-        (&prop[..prop.len() - 1], true)
-    } else {
-        (prop, false)
-    };
+    let (prop_str, optional) = split_optional(prop);
 
     // Check for array syntax: url(s), address(es), child(ren)
-    if let Some(paren_pos) = prop_str.find('(') {
-        if prop_str.ends_with(')') {
-            let base_name = &prop_str[..paren_pos];
-            // Array property - base_name is both the property name base and type reference
-            return PropertyInfo {
-                name: prop_str.to_string(),
-                type_ref: TypeRef::Custom(base_name.to_string()),
-                is_array: true,
-                optional,
-            };
-        }
+    if let Some(paren_pos) = prop_str.find('(')
+        && prop_str.ends_with(')')
+    {
+        let base_name = &prop_str[..paren_pos];
+        // Array property - base_name is both the property name base and type reference
+        return PropertyInfo {
+            name: prop_str.to_string(),
+            type_ref: TypeRef::Custom(base_name.to_string()),
+            is_array: true,
+            optional,
+        };
     }
 
     // Check if it's a DTO reference (ends with Dto)
@@ -82,6 +66,35 @@ fn parse_property(prop: &str) -> PropertyInfo {
     }
 }
 
+/// Property from an indented `name: type` line; `type[]` or `Array<type>`
+/// marks an array
+fn parse_typed_property(name: &str, type_name: &str) -> PropertyInfo {
+    let (name, optional) = split_optional(name);
+    let (element, is_array) = normalize_type(type_name);
+    PropertyInfo {
+        name: name.to_string(),
+        type_ref: string_to_type_ref(element),
+        is_array,
+        optional,
+    }
+}
+
+/// Text of the top-level `//` lines directly above `lines[at]`, top to bottom;
+/// a blank line ends the block
+fn leading_comments(lines: &[ParsedLine], at: usize) -> Vec<String> {
+    let mut comments: Vec<String> = lines[..at]
+        .iter()
+        .rev()
+        .map_while(|l| match &l.kind {
+            LineKind::Comment { text, indent: 0 } => Some(text.trim().to_string()),
+            _ => None,
+        })
+        .filter(|text| !text.is_empty())
+        .collect();
+    comments.reverse();
+    comments
+}
+
 /// Extract all DTO definitions from parsed lines
 pub fn extract_dtos(lines: &[ParsedLine]) -> Vec<DtoInfo> {
     let mut dtos = Vec::new();
@@ -91,27 +104,44 @@ pub fn extract_dtos(lines: &[ParsedLine]) -> Vec<DtoInfo> {
         if let LineKind::DtoDef { name, properties } = &lines[i].kind {
             let mut description = String::new();
 
-            // Look for description on following lines
+            let mut parsed_properties: Vec<PropertyInfo> = properties
+                .iter()
+                .map(|p| parse_property(p))
+                .collect();
+
+            // Description and indented property lines follow the tag
             let mut j = i + 1;
             let mut desc_lines = Vec::new();
             while j < lines.len() {
-                if let LineKind::DtoDesc { text, .. } = &lines[j].kind {
-                    desc_lines.push(text.clone());
-                    j += 1;
-                } else {
-                    break;
+                match &lines[j].kind {
+                    LineKind::DtoDesc { text, .. } => {
+                        if !text.trim().is_empty() {
+                            desc_lines.push(text.trim().to_string());
+                        }
+                    }
+                    LineKind::DtoProperty { name, type_name } => {
+                        parsed_properties.push(parse_typed_property(name, type_name));
+                    }
+                    LineKind::DtoArrayProperty { property_name, base_type, .. } => {
+                        let (name, optional) = split_optional(property_name);
+                        parsed_properties.push(PropertyInfo {
+                            name: name.to_string(),
+                            type_ref: string_to_type_ref(base_type),
+                            is_array: true,
+                            optional,
+                        });
+                    }
+                    _ => break,
                 }
+                j += 1;
             }
 
+            // A comment block directly above the tag documents the DTO too
+            desc_lines.extend(leading_comments(lines, i));
             if !desc_lines.is_empty() {
                 description = desc_lines.join(" ");
             }
 
-            let parsed_properties: Vec<PropertyInfo> = properties
-                .iter()
-                .map(|p| parse_property(p))
-                .collect();
-
             dtos.push(DtoInfo {
                 name: name.clone(),
                 kebab_name: to_kebab_case(name),
@@ -145,6 +175,28 @@ mod tests {
         assert_eq!(dtos[0].description, "input for retrieving a recording");
     }
 
+    #[test]
+    fn joins_multi_line_description_in_order() {
+        let doc = "[DTO] UserDto: id, name\n    a registered user,\n    as stored in the database\n    email: string\n    kept after the properties";
+        let lines = parse_document(doc);
+        let dtos = extract_dtos(&lines);
+
+        assert_eq!(dtos[0].properties.len(), 3);
+        assert_eq!(
+            dtos[0].description,
+            "a registered user, as stored in the database kept after the properties"
+        );
+    }
+
+    #[test]
+    fn appends_comment_block_above_the_tag() {
+        let doc = "// unrelated\n\n// what the client sends\n// to start a call\n[DTO] CallDto: id\n    a call";
+        let lines = parse_document(doc);
+        let dtos = extract_dtos(&lines);
+
+        assert_eq!(dtos[0].description, "a call what the client sends to start a call");
+    }
+
     #[test]
     fn extracts_dto_with_array_property() {
         let doc = "[DTO] SearchDto: url(s)\n    list of URLs";
@@ -158,6 +210,20 @@ mod tests {
         assert_eq!(dtos[0].properties[0].type_ref, TypeRef::Custom("url".to_string()));
     }
 
+    #[test]
+    fn extracts_optional_properties() {
+        let doc = "[DTO] SearchDto: query, metadata?, url(s)?\n    a search";
+        let lines = parse_document(doc);
+        let props = &extract_dtos(&lines)[0].properties;
+
+        assert!(!props[0].optional);
+        assert_eq!(props[1].name, "metadata");
+        assert!(props[1].optional && !props[1].is_array);
+        assert_eq!(props[2].name, "url(s)");
+        assert!(props[2].optional && props[2].is_array);
+        assert_eq!(props[2].type_ref, TypeRef::Custom("url".to_string()));
+    }
+
     #[test]
     fn extracts_dto_with_nested_dto() {
         let doc = "[DTO] SetMetadataDto: GetRecordingDto, MetadataDto\n    input for setting metadata";
@@ -188,4 +254,24 @@ mod tests {
         assert_eq!(dtos[0].name, "ADto");
         assert_eq!(dtos[1].name, "BDto");
     }
+
+    #[test]
+    fn extracts_nested_property_lines() {
+        let doc = "[DTO] FooDto:\n    a foo\n    name: string\n    tags: tag(s)\n    owner?: UserDto";
+        let lines = parse_document(doc);
+        let dtos = extract_dtos(&lines);
+
+        assert_eq!(dtos.len(), 1);
+        assert_eq!(dtos[0].description, "a foo");
+        let props = &dtos[0].properties;
+        assert_eq!(props.len(), 3);
+        assert_eq!(props[0].name, "name");
+        assert_eq!(props[0].type_ref, TypeRef::Primitive("string".to_string()));
+        assert_eq!(props[1].name, "tags");
+        assert_eq!(props[1].type_ref, TypeRef::Custom("tag".to_string()));
+        assert!(props[1].is_array);
+        assert_eq!(props[2].name, "owner");
+        assert_eq!(props[2].type_ref, TypeRef::Dto("UserDto".to_string()));
+        assert!(props[2].optional);
+    }
 }
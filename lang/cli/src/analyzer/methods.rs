@@ -4,9 +4,10 @@ use std::collections::HashMap;
 use rune_parser::{ParsedLine, LineKind};
 use super::dtos::TypeRef;
 use super::types::TypeInfo;
+use serde::Serialize;
 
 /// Information about a method
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MethodInfo {
     pub name: String,
     pub is_static: bool,
@@ -14,10 +15,11 @@ pub struct MethodInfo {
     pub return_type: TypeRef,
     pub boundary: Option<String>,  // "ex:", "db:", etc.
     pub faults: Vec<String>,
+    pub comment: Option<String>,  // inline `// ...` on the defining step
 }
 
 /// Information about a parameter
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ParamInfo {
     pub name: String,
     pub type_ref: TypeRef,
@@ -64,7 +66,7 @@ pub fn extract_methods_from_steps(lines: &[ParsedLine]) -> Vec<MethodInfo> {
 
     while i < lines.len() {
         match &lines[i].kind {
-            LineKind::Step { noun: _, verb, params, output, is_static, .. } => {
+            LineKind::Step { noun: _, verb, params, output, is_static, comment, .. } => {
                 // Collect faults from following lines
                 let faults = collect_faults(&lines[i+1..]);
 
@@ -83,9 +85,10 @@ pub fn extract_methods_from_steps(lines: &[ParsedLine]) -> Vec<MethodInfo> {
                     return_type: string_to_type_ref(output),
                     boundary: None,
                     faults,
+                    comment: comment.clone(),
                 });
             }
-            LineKind::BoundaryStep { prefix, noun: _, verb, params, output, is_static, .. } => {
+            LineKind::BoundaryStep { prefix, noun: _, verb, params, output, is_static, comment, .. } => {
                 // Collect faults from following lines
                 let faults = collect_faults(&lines[i+1..]);
 
@@ -104,6 +107,7 @@ pub fn extract_methods_from_steps(lines: &[ParsedLine]) -> Vec<MethodInfo> {
                     return_type: string_to_type_ref(output),
                     boundary: Some(prefix.clone()),
                     faults,
+                    comment: comment.clone(),
                 });
             }
             _ => {}
@@ -8,7 +8,8 @@ mod faults;
 mod requirements;
 mod polymorphic;
 
-pub use nouns::{NounInfo, to_pascal_case, extract_nouns, extract_nouns_with_types};
+pub use rune_parser::casing::*;
+pub use nouns::{NounInfo, extract_nouns, extract_nouns_with_types};
 pub use methods::*;
 pub use dtos::*;
 pub use types::*;
@@ -17,9 +18,10 @@ pub use requirements::*;
 pub use polymorphic::*;
 
 use rune_parser::parse_document;
+use serde::Serialize;
 
 /// Complete analyzed specification
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AnalyzedSpec {
     pub dtos: Vec<DtoInfo>,
     pub types: Vec<TypeInfo>,
@@ -28,6 +30,17 @@ pub struct AnalyzedSpec {
     pub polymorphics: Vec<PolyInfo>,
 }
 
+impl AnalyzedSpec {
+    /// `noun: message` for each method the dedup dropped because an earlier
+    /// step declared it with a different signature
+    pub fn conflict_warnings(&self) -> Vec<String> {
+        self.nouns
+            .iter()
+            .flat_map(|noun| noun.conflicts.iter().map(move |(_, message)| format!("{}: {}", noun.name, message)))
+            .collect()
+    }
+}
+
 /// Analyze a rune document and extract semantic information
 pub fn analyze(text: &str) -> AnalyzedSpec {
     let lines = parse_document(text);
@@ -75,5 +88,20 @@ mod tests {
         assert!(!analyzed.types.is_empty());
         assert!(!analyzed.requirements.is_empty());
         assert!(!analyzed.nouns.is_empty());
+        assert!(analyzed.conflict_warnings().is_empty());
+    }
+
+    #[test]
+    fn lists_conflicting_methods_per_noun() {
+        let spec = "[REQ] user.get(IdDto): UserDto
+    db:user.load(IdDto): UserDto
+
+[REQ] user.find(IdDto): UserDto
+    db:user.load(IdDto): ProfileDto
+";
+        assert_eq!(
+            analyze(spec).conflict_warnings(),
+            vec!["user: 'load' is declared as (IdDto): UserDto and as (IdDto): ProfileDto; keeping the first"]
+        );
     }
 }
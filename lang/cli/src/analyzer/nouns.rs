@@ -4,9 +4,12 @@ use std::collections::{HashMap, HashSet};
 use rune_parser::{ParsedLine, LineKind};
 use super::methods::{MethodInfo, ParamInfo, string_to_type_ref_with_resolution, build_type_map};
 use super::types::TypeInfo;
+use rune_parser::casing::to_pascal_case;
+use super::dtos::TypeRef;
+use serde::Serialize;
 
 /// Information about a noun (class)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct NounInfo {
     pub name: String,
     pub pascal_name: String,
@@ -15,25 +18,7 @@ pub struct NounInfo {
     pub constructor_params: Vec<String>,    // repeated params across methods (names only, for backwards compat)
     pub constructor_param_infos: Vec<ParamInfo>,  // typed constructor params
     pub methods: Vec<MethodInfo>,
-}
-
-/// Convert snake_case or lowercase to PascalCase
-pub fn to_pascal_case(s: &str) -> String {
-    let mut result = String::new();
-    let mut capitalize_next = true;
-
-    for c in s.chars() {
-        if c == '_' || c == '-' {
-            capitalize_next = true;
-        } else if capitalize_next {
-            result.push(c.to_uppercase().next().unwrap());
-            capitalize_next = false;
-        } else {
-            result.push(c);
-        }
-    }
-
-    result
+    pub conflicts: Vec<(MethodInfo, String)>,  // same-named methods dropped by dedup
 }
 
 /// Extract all nouns from parsed lines and classify them
@@ -61,7 +46,7 @@ pub fn extract_nouns_with_types(lines: &[ParsedLine], types: &[TypeInfo]) -> Vec
     let mut i = 0;
     while i < lines.len() {
         match &lines[i].kind {
-            LineKind::Step { noun, verb, params, output, is_static, .. } => {
+            LineKind::Step { noun, verb, params, output, is_static, comment, .. } => {
                 let faults = collect_faults(&lines[i+1..]);
                 let method = MethodInfo {
                     name: verb.clone(),
@@ -73,10 +58,11 @@ pub fn extract_nouns_with_types(lines: &[ParsedLine], types: &[TypeInfo]) -> Vec
                     return_type: string_to_type_ref_with_resolution(output, &type_map),
                     boundary: None,
                     faults,
+                    comment: comment.clone(),
                 };
                 noun_methods.entry(noun.clone()).or_default().push(method);
             }
-            LineKind::BoundaryStep { prefix, noun, verb, params, output, is_static, .. } => {
+            LineKind::BoundaryStep { prefix, noun, verb, params, output, is_static, comment, .. } => {
                 let faults = collect_faults(&lines[i+1..]);
                 let method = MethodInfo {
                     name: verb.clone(),
@@ -88,11 +74,12 @@ pub fn extract_nouns_with_types(lines: &[ParsedLine], types: &[TypeInfo]) -> Vec
                     return_type: string_to_type_ref_with_resolution(output, &type_map),
                     boundary: Some(prefix.clone()),
                     faults,
+                    comment: comment.clone(),
                 };
                 noun_methods.entry(noun.clone()).or_default().push(method);
                 noun_boundaries.entry(noun.clone()).or_default().insert(prefix.clone());
             }
-            LineKind::Ply { noun, verb, params, output, is_static, .. } => {
+            LineKind::Ply { noun, verb, params, output, is_static, comment, .. } => {
                 let faults = collect_faults(&lines[i+1..]);
                 let method = MethodInfo {
                     name: verb.clone(),
@@ -104,6 +91,7 @@ pub fn extract_nouns_with_types(lines: &[ParsedLine], types: &[TypeInfo]) -> Vec
                     return_type: string_to_type_ref_with_resolution(output, &type_map),
                     boundary: None,
                     faults,
+                    comment: comment.clone(),
                 };
                 noun_methods.entry(noun.clone()).or_default().push(method);
             }
@@ -124,7 +112,7 @@ pub fn extract_nouns_with_types(lines: &[ParsedLine], types: &[TypeInfo]) -> Vec
         let is_impure = !boundary_types.is_empty();
 
         // Deduplicate methods by (name, is_static, boundary) signature
-        let unique_methods = deduplicate_methods(methods);
+        let (unique_methods, conflicts) = deduplicate_methods(methods);
 
         // Infer constructor params: params that appear in multiple methods
         let (constructor_params, constructor_param_infos) = infer_constructor_params(&unique_methods);
@@ -137,6 +125,7 @@ pub fn extract_nouns_with_types(lines: &[ParsedLine], types: &[TypeInfo]) -> Vec
             constructor_params,
             constructor_param_infos,
             methods: unique_methods,
+            conflicts,
         });
     }
 
@@ -146,59 +135,83 @@ pub fn extract_nouns_with_types(lines: &[ParsedLine], types: &[TypeInfo]) -> Vec
     nouns
 }
 
-/// Deduplicate methods by (name, is_static, boundary) signature
-fn deduplicate_methods(methods: Vec<MethodInfo>) -> Vec<MethodInfo> {
-    let mut seen: HashSet<(String, bool, Option<String>)> = HashSet::new();
-    let mut unique = Vec::new();
+/// Deduplicate methods by (name, is_static, boundary) signature. The first
+/// declaration wins; later ones whose params or return type differ come back
+/// as conflicts, each with a message naming both signatures.
+fn deduplicate_methods(methods: Vec<MethodInfo>) -> (Vec<MethodInfo>, Vec<(MethodInfo, String)>) {
+    let mut seen: HashMap<(String, bool, Option<String>), usize> = HashMap::new();
+    let mut unique: Vec<MethodInfo> = Vec::new();
+    let mut conflicts = Vec::new();
 
     for method in methods {
         let key = (method.name.clone(), method.is_static, method.boundary.clone());
-        if !seen.contains(&key) {
-            seen.insert(key);
-            unique.push(method);
+        match seen.get(&key) {
+            Some(&first) => {
+                let kept = &unique[first];
+                let same_params = kept.params.iter().map(|p| &p.name).eq(method.params.iter().map(|p| &p.name));
+                if !same_params || kept.return_type != method.return_type {
+                    let message = format!(
+                        "'{}' is declared as {} and as {}; keeping the first",
+                        method.name,
+                        signature(kept),
+                        signature(&method)
+                    );
+                    conflicts.push((method, message));
+                }
+            }
+            None => {
+                seen.insert(key, unique.len());
+                unique.push(method);
+            }
         }
     }
 
-    unique
+    (unique, conflicts)
+}
+
+fn signature(method: &MethodInfo) -> String {
+    let params: Vec<&str> = method.params.iter().map(|p| p.name.as_str()).collect();
+    let output = match &method.return_type {
+        TypeRef::Primitive(t) | TypeRef::Dto(t) | TypeRef::Custom(t) => t,
+    };
+    format!("({}): {}", params.join(", "), output)
 }
 
-/// Infer constructor parameters: params that appear in multiple methods
+/// Infer constructor parameters: params that appear in multiple methods, in
+/// the order they are first seen in the spec.
 /// Returns both names (for backwards compat) and typed params
 fn infer_constructor_params(methods: &[MethodInfo]) -> (Vec<String>, Vec<ParamInfo>) {
     if methods.len() < 2 {
         return (Vec::new(), Vec::new());
     }
 
-    // Count occurrences of each param across all methods and track type
-    let mut param_counts: HashMap<String, usize> = HashMap::new();
-    let mut param_types: HashMap<String, ParamInfo> = HashMap::new();
+    // Count the methods each param appears in, keeping the first-seen typed
+    // param for each name
+    let mut param_counts: HashMap<&str, usize> = HashMap::new();
+    let mut first_seen: Vec<&ParamInfo> = Vec::new();
 
     for method in methods {
         // Use a set to avoid counting the same param twice in one method
-        let unique_params: HashSet<_> = method.params.iter().map(|p| &p.name).collect();
+        let mut in_method: HashSet<&str> = HashSet::new();
         for param in &method.params {
-            if unique_params.contains(&param.name) {
-                *param_counts.entry(param.name.clone()).or_default() += 1;
-                // Store the type from the first method that has this param
-                param_types.entry(param.name.clone()).or_insert_with(|| param.clone());
+            if !in_method.insert(&param.name) {
+                continue;
             }
+            let count = param_counts.entry(&param.name).or_default();
+            if *count == 0 {
+                first_seen.push(param);
+            }
+            *count += 1;
         }
     }
 
     // Params that appear in more than one method
-    let mut constructor_params: Vec<String> = param_counts
-        .iter()
-        .filter(|(_, count)| **count > 1)
-        .map(|(name, _)| name.clone())
-        .collect();
-
-    constructor_params.sort();
-
-    // Build typed params in the same order
-    let constructor_param_infos: Vec<ParamInfo> = constructor_params
-        .iter()
-        .filter_map(|name| param_types.get(name).cloned())
+    let constructor_param_infos: Vec<ParamInfo> = first_seen
+        .into_iter()
+        .filter(|p| param_counts[p.name.as_str()] > 1)
+        .cloned()
         .collect();
+    let constructor_params = constructor_param_infos.iter().map(|p| p.name.clone()).collect();
 
     (constructor_params, constructor_param_infos)
 }
@@ -286,6 +299,26 @@ mod tests {
         assert!(provider_noun.constructor_params.contains(&"config".to_string()));
     }
 
+    #[test]
+    fn constructor_params_keep_spec_order() {
+        let doc = r#"
+[REQ] recording.register(GetRecordingDto): IdDto
+    provider.search(config, region, id): SearchDto
+    provider.download(region, config, url): data
+    archive.store(zone, config): void
+    archive.fetch(config, zone, zone): data
+"#;
+        let lines = parse_document(doc);
+        let nouns = extract_nouns(&lines);
+
+        let provider_noun = nouns.iter().find(|n| n.name == "provider").unwrap();
+        assert_eq!(provider_noun.constructor_params, vec!["config", "region"]);
+        let archive_noun = nouns.iter().find(|n| n.name == "archive").unwrap();
+        assert_eq!(archive_noun.constructor_params, vec!["zone", "config"]);
+        let typed: Vec<&str> = archive_noun.constructor_param_infos.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(typed, vec!["zone", "config"]);
+    }
+
     #[test]
     fn converts_to_pascal_case() {
         assert_eq!(to_pascal_case("id"), "Id");
@@ -314,4 +347,39 @@ mod tests {
         let to_dto_method = id_noun.methods.iter().find(|m| m.name == "toDto").unwrap();
         assert!(!to_dto_method.is_static);
     }
+
+    #[test]
+    fn keeps_inline_step_comment() {
+        let doc = r#"
+[REQ] recording.register(GetRecordingDto): IdDto
+    id::create(name): id // creates an id
+    id.toDto(): IdDto
+"#;
+        let lines = parse_document(doc);
+        let nouns = extract_nouns(&lines);
+
+        let id_noun = nouns.iter().find(|n| n.name == "id").unwrap();
+        let create_method = id_noun.methods.iter().find(|m| m.name == "create").unwrap();
+        assert_eq!(create_method.comment.as_deref(), Some("creates an id"));
+        let to_dto_method = id_noun.methods.iter().find(|m| m.name == "toDto").unwrap();
+        assert_eq!(to_dto_method.comment, None);
+    }
+
+    #[test]
+    fn reports_conflicting_duplicate_methods() {
+        let doc = r#"
+[REQ] user.get(IdDto): UserDto
+    user.get(IdDto): UserDto
+    user.get(IdDto): ProfileDto
+    user.get(IdDto): UserDto
+"#;
+        let lines = parse_document(doc);
+        let nouns = extract_nouns(&lines);
+
+        let user = nouns.iter().find(|n| n.name == "user").unwrap();
+        assert_eq!(user.methods.len(), 1);
+        assert_eq!(user.methods[0].return_type, TypeRef::Dto("UserDto".to_string()));
+        assert_eq!(user.conflicts.len(), 1);
+        assert_eq!(user.conflicts[0].1, "'get' is declared as (IdDto): UserDto and as (IdDto): ProfileDto; keeping the first");
+    }
 }
@@ -4,9 +4,11 @@ use std::collections::HashMap;
 use rune_parser::{ParsedLine, LineKind};
 use super::methods::{ParamInfo, string_to_type_ref_with_resolution, build_type_map};
 use super::types::TypeInfo;
+use rune_parser::casing::{to_kebab_case, to_pascal_case};
+use serde::Serialize;
 
 /// Information about a polymorphic block
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct PolyInfo {
     pub noun: String,              // "provider"
     pub pascal_name: String,       // "Provider"
@@ -18,7 +20,7 @@ pub struct PolyInfo {
 }
 
 /// Information about a case within a polymorphic block
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct CaseInfo {
     pub name: String,              // "genie"
     pub pascal_name: String,       // "Genie"
@@ -28,7 +30,7 @@ pub struct CaseInfo {
 }
 
 /// A step within a case implementation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct CaseStep {
     pub noun: String,
     pub verb: String,
@@ -187,39 +189,6 @@ fn collect_faults(lines: &[ParsedLine]) -> Vec<String> {
     faults
 }
 
-/// Convert to PascalCase
-fn to_pascal_case(s: &str) -> String {
-    let mut result = String::new();
-    let mut capitalize_next = true;
-    for c in s.chars() {
-        if c == '_' || c == '-' {
-            capitalize_next = true;
-        } else if capitalize_next {
-            result.push(c.to_uppercase().next().unwrap());
-            capitalize_next = false;
-        } else {
-            result.push(c);
-        }
-    }
-    result
-}
-
-/// Convert camelCase or PascalCase to kebab-case
-fn to_kebab_case(s: &str) -> String {
-    let mut result = String::new();
-    for (i, c) in s.chars().enumerate() {
-        if c.is_uppercase() {
-            if i > 0 {
-                result.push('-');
-            }
-            result.push(c.to_lowercase().next().unwrap());
-        } else {
-            result.push(c);
-        }
-    }
-    result
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,20 +1,21 @@
 //! Requirements (REQ) extraction from parsed .rune files
 
 use rune_parser::{ParsedLine, LineKind};
+use serde::Serialize;
 
 /// Information about a requirement flow
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ReqInfo {
     pub noun: String,
     pub verb: String,
-    pub input_dto: String,
+    pub input_dto: Option<String>,  // None for `[REQ] health.check(): StatusDto`
     pub output_dto: String,
     pub steps: Vec<StepInfo>,
     pub all_faults: Vec<String>,
 }
 
 /// Information about a step in a requirement flow
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct StepInfo {
     pub line_num: usize,
     pub noun: String,
@@ -25,10 +26,11 @@ pub struct StepInfo {
     pub boundary: Option<String>,
     pub faults: Vec<String>,
     pub kind: StepKind,
+    pub comment: Option<String>,   // inline `// ...` on the step line
 }
 
 /// Kind of step
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum StepKind {
     Regular,
     Boundary,
@@ -77,7 +79,7 @@ pub fn extract_requirements(lines: &[ParsedLine]) -> Vec<ReqInfo> {
                             break;
                         }
                     }
-                    LineKind::Step { noun: step_noun, verb: step_verb, params, output: step_output, is_static, .. } => {
+                    LineKind::Step { noun: step_noun, verb: step_verb, params, output: step_output, is_static, comment, .. } => {
                         // Before adding new step, flush any collected faults to previous step
                         if !current_step_faults.is_empty() && !steps.is_empty() {
                             steps.last_mut().unwrap().faults.extend(current_step_faults.clone());
@@ -95,10 +97,11 @@ pub fn extract_requirements(lines: &[ParsedLine]) -> Vec<ReqInfo> {
                             boundary: None,
                             faults: Vec::new(),
                             kind: StepKind::Regular,
+                            comment: comment.clone(),
                         });
                         j += 1;
                     }
-                    LineKind::BoundaryStep { prefix, noun: step_noun, verb: step_verb, params, output: step_output, is_static, .. } => {
+                    LineKind::BoundaryStep { prefix, noun: step_noun, verb: step_verb, params, output: step_output, is_static, comment, .. } => {
                         // Flush previous faults
                         if !current_step_faults.is_empty() && !steps.is_empty() {
                             steps.last_mut().unwrap().faults.extend(current_step_faults.clone());
@@ -116,10 +119,11 @@ pub fn extract_requirements(lines: &[ParsedLine]) -> Vec<ReqInfo> {
                             boundary: Some(prefix.clone()),
                             faults: Vec::new(),
                             kind: StepKind::Boundary,
+                            comment: comment.clone(),
                         });
                         j += 1;
                     }
-                    LineKind::Ply { noun: step_noun, verb: step_verb, params, output: step_output, is_static, .. } => {
+                    LineKind::Ply { noun: step_noun, verb: step_verb, params, output: step_output, is_static, comment, .. } => {
                         // Flush previous faults
                         if !current_step_faults.is_empty() && !steps.is_empty() {
                             steps.last_mut().unwrap().faults.extend(current_step_faults.clone());
@@ -137,6 +141,7 @@ pub fn extract_requirements(lines: &[ParsedLine]) -> Vec<ReqInfo> {
                             boundary: None,
                             faults: Vec::new(),
                             kind: StepKind::Polymorphic,
+                            comment: comment.clone(),
                         });
                         j += 1;
                     }
@@ -158,6 +163,7 @@ pub fn extract_requirements(lines: &[ParsedLine]) -> Vec<ReqInfo> {
                             boundary: None,
                             faults: Vec::new(),
                             kind: StepKind::Case(name.clone()),
+                            comment: None,
                         });
                         j += 1;
                     }
@@ -179,6 +185,7 @@ pub fn extract_requirements(lines: &[ParsedLine]) -> Vec<ReqInfo> {
                             boundary: None,
                             faults: Vec::new(),
                             kind: StepKind::Return,
+                            comment: None,
                         });
                         j += 1;
                     }
@@ -200,6 +207,7 @@ pub fn extract_requirements(lines: &[ParsedLine]) -> Vec<ReqInfo> {
                             boundary: None,
                             faults: Vec::new(),
                             kind: StepKind::Constructor,
+                            comment: None,
                         });
                         j += 1;
                     }
@@ -225,7 +233,7 @@ pub fn extract_requirements(lines: &[ParsedLine]) -> Vec<ReqInfo> {
             requirements.push(ReqInfo {
                 noun: noun.clone(),
                 verb: verb.clone(),
-                input_dto: input.clone(),
+                input_dto: (!input.trim().is_empty()).then(|| input.trim().to_string()),
                 output_dto: output.clone(),
                 steps,
                 all_faults,
@@ -256,7 +264,7 @@ mod tests {
         assert_eq!(reqs.len(), 1);
         assert_eq!(reqs[0].noun, "recording");
         assert_eq!(reqs[0].verb, "register");
-        assert_eq!(reqs[0].input_dto, "GetRecordingDto");
+        assert_eq!(reqs[0].input_dto.as_deref(), Some("GetRecordingDto"));
         assert_eq!(reqs[0].output_dto, "IdDto");
         assert_eq!(reqs[0].steps.len(), 2);
     }
@@ -356,4 +364,17 @@ mod tests {
         assert!(reqs[0].all_faults.contains(&"timed-out".to_string()));
         assert!(reqs[0].all_faults.contains(&"network-error".to_string()));
     }
+
+    #[test]
+    fn extracts_requirement_without_input() {
+        let doc = r#"[REQ] health.check(): StatusDto
+    db:health.ping(): StatusDto"#;
+        let lines = parse_document(doc);
+        let reqs = extract_requirements(&lines);
+
+        assert_eq!(reqs.len(), 1);
+        assert_eq!(reqs[0].input_dto, None);
+        assert_eq!(reqs[0].output_dto, "StatusDto");
+        assert_eq!(reqs[0].steps.len(), 1);
+    }
 }
@@ -1,9 +1,10 @@
 //! Type extraction from parsed .rune files
 
 use rune_parser::{ParsedLine, LineKind};
+use serde::Serialize;
 
 /// Information about a type definition
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TypeInfo {
     pub name: String,
     pub underlying_type: String,
@@ -0,0 +1,66 @@
+//! Analyze command - dumps what the analyzer extracts from a .rune file
+
+use std::path::Path;
+
+use crate::analyzer::{analyze, AnalyzedSpec};
+
+use super::{display_name, read_input, CommandError};
+
+/// The analyzed spec of a .rune file, as pretty JSON or as debug text.
+/// Conflicting method declarations are reported on stderr.
+pub fn dump_analysis(input_path: &Path, json: bool) -> Result<String, CommandError> {
+    let content = read_input(input_path)?;
+    let spec = analyze(&content);
+    warn_conflicts(input_path, &spec);
+    if json {
+        serde_json::to_string_pretty(&spec).map_err(|e| CommandError::serialize("analysis", e))
+    } else {
+        Ok(format!("{:#?}", spec))
+    }
+}
+
+/// Print a warning on stderr for each method whose later declarations were
+/// dropped because their signature differs from the first
+pub fn warn_conflicts(input_path: &Path, spec: &AnalyzedSpec) {
+    for warning in spec.conflict_warnings() {
+        eprintln!("{}: warning: {}", display_name(input_path), warning);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    const SPEC: &str = "[REQ] user.get(IdDto): UserDto
+    db:user.load(IdDto): UserDto
+
+[DTO] IdDto: id
+    an id
+
+[DTO] UserDto: id, name
+    a user
+
+[TYP] id: string
+    the id
+
+[TYP] name: string
+    a name
+";
+
+    #[test]
+    fn serializes_analyzed_spec() {
+        let temp = tempdir().unwrap();
+        let input = temp.path().join("user.rune");
+        fs::write(&input, SPEC).unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&dump_analysis(&input, true).unwrap()).unwrap();
+        assert_eq!(json["nouns"].as_array().map(Vec::len), Some(1));
+        assert_eq!(json["nouns"][0]["name"], "user");
+        assert_eq!(json["dtos"][1]["properties"][1]["type_ref"]["Custom"], "name");
+        assert_eq!(json["requirements"][0]["steps"][0]["boundary"], "db:");
+
+        assert!(dump_analysis(&input, false).unwrap().starts_with("AnalyzedSpec {"));
+    }
+}
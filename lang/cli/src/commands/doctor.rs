@@ -0,0 +1,109 @@
+//! Doctor command - checks what `install` set up and how to fix what's missing
+
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+use super::{bin_dir, data_dir, grammar_status_in, GrammarStatus};
+
+/// Completion scripts `install` writes, relative to the home directory
+const COMPLETION_FILES: [&str; 3] = [
+    ".zsh/completions/_rune",
+    ".local/share/bash-completion/completions/rune",
+    ".config/fish/completions/rune.fish",
+];
+
+/// One line of the doctor checklist
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: String,
+    pub ok: bool,
+    /// What to do about a failed check
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: impl Into<String>) -> Self {
+        Self { name: name.into(), ok: true, hint: None }
+    }
+
+    fn fail(name: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self { name: name.into(), ok: false, hint: Some(hint.into()) }
+    }
+
+    /// `✓ name`, or `✗ name` with the hint indented underneath
+    pub fn describe(&self) -> String {
+        match &self.hint {
+            Some(hint) if !self.ok => format!("✗ {}\n    {}", self.name, hint),
+            _ => format!("✓ {}", self.name),
+        }
+    }
+}
+
+/// Check the install under the configured data and bin directories
+pub fn doctor() -> Vec<Check> {
+    doctor_in(&data_dir(), &bin_dir(), dirs::home_dir().as_deref())
+}
+
+/// `doctor` with explicit directories; `home` is where completions are looked for
+pub fn doctor_in(data: &Path, bin: &Path, home: Option<&Path>) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    let has_cc = Command::new("cc").arg("--version").output().is_ok_and(|o| o.status.success());
+    checks.push(if has_cc {
+        Check::pass("C compiler (cc)")
+    } else {
+        Check::fail("C compiler (cc)", "install a C toolchain (build-essential, Xcode command line tools); install builds the grammar with cc")
+    });
+
+    let lsp = bin.join("rune-lsp");
+    let on_path = env::var_os("PATH").is_some_and(|path| env::split_paths(&path).any(|p| p == bin));
+    checks.push(if !lsp.is_file() {
+        Check::fail(format!("rune-lsp in {}", bin.display()), "run `rune-syntax install` from the rune repo")
+    } else if !on_path {
+        Check::fail(format!("rune-lsp in {}", bin.display()), format!("add {} to PATH so editors can start it", bin.display()))
+    } else {
+        Check::pass(format!("rune-lsp in {}", bin.display()))
+    });
+
+    let parser = data.join("parser/rune.so");
+    checks.push(match grammar_status_in(data) {
+        GrammarStatus::UpToDate => Check::pass(format!("parser {}", parser.display())),
+        GrammarStatus::Stale(_) => Check::fail(format!("parser {}", parser.display()), "built from an older grammar; re-run `rune-syntax install`"),
+        GrammarStatus::NotInstalled => Check::fail(format!("parser {}", parser.display()), "run `rune-syntax install` (needs cc)"),
+    });
+
+    let completions = home.and_then(|home| COMPLETION_FILES.iter().map(|f| home.join(f)).find(|p| p.is_file()));
+    checks.push(match completions {
+        Some(path) => Check::pass(format!("shell completions {}", path.display())),
+        None => Check::fail("shell completions", "run `rune-syntax install` and pick a shell, or `rune-syntax completions <shell>`"),
+    });
+
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::grammar_version;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn finds_installed_parser_and_completions() {
+        let data = tempdir().unwrap();
+        let bin = tempdir().unwrap();
+        let home = tempdir().unwrap();
+        fs::create_dir_all(data.path().join("parser")).unwrap();
+        fs::write(data.path().join("parser/rune.so"), "").unwrap();
+        fs::write(data.path().join("parser/rune.version"), grammar_version()).unwrap();
+        fs::create_dir_all(home.path().join(".config/fish/completions")).unwrap();
+        fs::write(home.path().join(".config/fish/completions/rune.fish"), "").unwrap();
+
+        let checks = doctor_in(data.path(), bin.path(), Some(home.path()));
+        assert!(checks[2].ok, "{:?}", checks[2]);
+        assert!(checks[3].ok, "{:?}", checks[3]);
+        assert!(!checks[1].ok);
+        assert_eq!(checks[1].hint.as_deref(), Some("run `rune-syntax install` from the rune repo"));
+    }
+}
@@ -0,0 +1,68 @@
+//! Errors returned by the commands. `Display` is the message the CLI prints
+//! after `Error: `.
+
+use std::error::Error as StdError;
+use std::io;
+use std::path::PathBuf;
+
+use rune_parser::codes::DiagnosticCode;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CommandError {
+    /// Reading, writing or creating `path` failed
+    #[error("Failed to {action} {}: {source}", path.display())]
+    Io {
+        action: &'static str,
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// `init` was given a name that can't be a directory/spec name
+    #[error("Invalid project name '{0}': use letters, digits, '-' or '_'")]
+    InvalidName(String),
+    /// A command would overwrite something that is already there
+    #[error("{} already exists and is not empty", .0.display())]
+    Collision(PathBuf),
+    /// The nearest rune.toml can't be read or parsed
+    #[error("{0}")]
+    InvalidConfig(String),
+    /// An `--exclude` pattern is not a valid glob
+    #[error("Invalid exclude glob: {0}")]
+    InvalidGlob(globset::Error),
+    /// Output (diagnostics for `--json`, an OpenAPI document, ...) couldn't
+    /// be serialized
+    #[error("Failed to serialize {what}: {source}")]
+    Serialize {
+        what: &'static str,
+        #[source]
+        source: Box<dyn StdError + Send + Sync>,
+    },
+    /// `explain` was given a code no diagnostic uses
+    #[error("Unknown diagnostic code '{0}' (known codes: {known})", known = known_codes())]
+    UnknownCode(String),
+    /// The file watcher couldn't start or watch `path`
+    #[error("Failed to watch {}: {source}", path.display())]
+    Watch {
+        path: PathBuf,
+        #[source]
+        source: notify::Error,
+    },
+    /// An install/uninstall step (toolchain, editor, shell, icons) failed
+    #[error("{0}")]
+    Setup(String),
+}
+
+impl CommandError {
+    pub fn io(action: &'static str, path: impl Into<PathBuf>, source: io::Error) -> Self {
+        Self::Io { action, path: path.into(), source }
+    }
+
+    pub fn serialize(what: &'static str, source: impl StdError + Send + Sync + 'static) -> Self {
+        Self::Serialize { what, source: Box::new(source) }
+    }
+}
+
+fn known_codes() -> String {
+    DiagnosticCode::ALL.iter().map(|c| c.as_str()).collect::<Vec<_>>().join(", ")
+}
@@ -0,0 +1,34 @@
+//! Explain command - describes a diagnostic code and how to fix it
+
+use rune_parser::codes::DiagnosticCode;
+
+use super::CommandError;
+
+/// The explanation for `code`, headed by the code itself
+pub fn explain(code: &str) -> Result<String, CommandError> {
+    let Some(code) = DiagnosticCode::parse(code) else {
+        return Err(CommandError::UnknownCode(code.to_string()));
+    };
+    Ok(format!("{}\n\n{}", code, code.explanation()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explains_known_code() {
+        let text = explain("REQ001").unwrap();
+        assert!(text.starts_with("REQ001\n\n"));
+        assert!(text.contains("takes a DTO in and returns a DTO"));
+    }
+
+    #[test]
+    fn rejects_unknown_code() {
+        let err = explain("NOPE42").unwrap_err();
+        assert!(matches!(&err, CommandError::UnknownCode(code) if code == "NOPE42"));
+        let message = err.to_string();
+        assert!(message.starts_with("Unknown diagnostic code 'NOPE42' (known codes: "));
+        assert!(message.contains("LIN001"));
+    }
+}
@@ -3,56 +3,170 @@
 use std::fs;
 use std::path::Path;
 
-/// Format a .rune file
-pub fn format(input_path: &Path, check_only: bool) -> Result<bool, String> {
-    let content = fs::read_to_string(input_path)
-        .map_err(|e| format!("Failed to read {}: {}", input_path.display(), e))?;
+use std::collections::HashSet;
 
-    let formatted = format_content(&content);
+use rune_parser::validate::noun_in_scope;
+use rune_parser::{parse_document, LineKind};
+
+use super::{is_stdin, read_input, CommandError};
+
+/// Format a .rune file. With `-` the document is read from stdin and, unless
+/// checking, the formatted result goes to stdout. `normalize_static` also
+/// fixes `.` vs `::` step separators (see `normalize_separators`).
+pub fn format(input_path: &Path, check_only: bool, normalize_static: bool) -> Result<bool, CommandError> {
+    let (content, formatted) = formatted(input_path, normalize_static)?;
 
     if check_only {
         // Return true if already formatted, false if needs formatting
         Ok(content == formatted)
+    } else if is_stdin(input_path) {
+        print!("{}", formatted);
+        Ok(true)
     } else {
         // Write formatted content
         fs::write(input_path, &formatted)
-            .map_err(|e| format!("Failed to write {}: {}", input_path.display(), e))?;
+            .map_err(|e| CommandError::io("write", input_path, e))?;
         Ok(true)
     }
 }
 
+/// The current text of a .rune file and its formatted form, without writing
+/// anything back
+pub fn formatted(input_path: &Path, normalize_static: bool) -> Result<(String, String), CommandError> {
+    let content = read_input(input_path)?;
+    let formatted = if normalize_static {
+        format_content(&normalize_separators(&content))
+    } else {
+        format_content(&content)
+    };
+    Ok((content, formatted))
+}
+
+/// Rewrite step separators where scope settles them: `x::foo` becomes
+/// `x.foo` when `x` is constructed or returned earlier in the [REQ], and
+/// `x.foo` becomes `x::foo` when nothing in scope provides `x` and the file
+/// calls it statically elsewhere. Anything less certain is left alone, since
+/// an instance noun may be injected rather than produced by a step.
+pub fn normalize_separators(content: &str) -> String {
+    let parsed = parse_document(content);
+    // Nouns called statically where no instance of them is in scope
+    let static_nouns: HashSet<&str> = parsed
+        .iter()
+        .filter_map(|l| match &l.kind {
+            LineKind::Step { noun, is_static: true, .. } | LineKind::BoundaryStep { noun, is_static: true, .. }
+                if noun_in_scope(&parsed, l.line_num, noun) != Some(true) =>
+            {
+                Some(noun.as_str())
+            }
+            _ => None,
+        })
+        .collect();
+
+    let mut out = String::with_capacity(content.len());
+    for (line_num, line) in content.split_inclusive('\n').enumerate() {
+        let step = parsed.get(line_num).and_then(|l| match &l.kind {
+            LineKind::Step { noun, is_static, .. } => Some((l, "", noun, *is_static)),
+            LineKind::BoundaryStep { prefix, noun, is_static, .. } => Some((l, prefix.as_str(), noun, *is_static)),
+            _ => None,
+        });
+        let Some((parsed_line, prefix, noun, is_static)) = step else {
+            out.push_str(line);
+            continue;
+        };
+        let (from, to) = match (is_static, noun_in_scope(&parsed, line_num, noun)) {
+            (true, Some(true)) => ("::", "."),
+            (false, Some(false)) if static_nouns.contains(noun.as_str()) => (".", "::"),
+            _ => {
+                out.push_str(line);
+                continue;
+            }
+        };
+        // The separator sits right after the indent, boundary prefix and noun
+        let start = parsed_line.raw_indent + prefix.len() + noun.len();
+        match line.get(start..).and_then(|rest| rest.strip_prefix(from)) {
+            Some(rest) => {
+                out.push_str(&line[..start]);
+                out.push_str(to);
+                out.push_str(rest);
+            }
+            None => out.push_str(line),
+        }
+    }
+    out
+}
+
+/// Which kind of top-level block the formatter is currently inside. Decides how
+/// many blank lines go in front of the next block.
+#[derive(Clone, Copy, PartialEq)]
+enum Section {
+    None,
+    Req,
+    Def,
+    Other,
+}
+
 /// Format rune content
 fn format_content(content: &str) -> String {
     let mut lines: Vec<String> = Vec::new();
     let mut in_block = false;
     let mut consecutive_empty = 0;
+    let mut section = Section::None;
     let mut after_step = false;
     // Are we inside a [PLY] block? Its case steps/faults nest one level deeper
     // (8/10). The block closes at a blank line, the next top-level declaration, a
-    // [NEW]/[RET], or a step that returns to REQ level. We can't infer that from
+    // [CTR]/[NEW]/[RET], or a step that returns to REQ level. We can't infer that from
     // the already-normalized output, so track it as state and use the AUTHOR'S
     // original indent to tell a case step (deep) from a step that closes the
     // block (shallow) — otherwise the REQ's terminal step gets folded into the
     // last [CSE], silently changing meaning.
     let mut in_poly = false;
+    // Inside a [DOC] block every indented line is prose and kept as written
+    let mut in_doc = false;
 
     for line in content.lines() {
         let trimmed = line.trim();
         let orig_indent = line.len() - line.trim_start().len();
 
+        if in_doc && !trimmed.is_empty() {
+            if orig_indent > 0 {
+                lines.extend(std::iter::repeat_n(String::new(), consecutive_empty.min(2)));
+                consecutive_empty = 0;
+                lines.push(line.trim_end().to_string());
+                continue;
+            }
+            in_doc = false;
+        }
+
         if trimmed.is_empty() {
+            // Blank lines are emitted once we know what follows them.
             consecutive_empty += 1;
-            // Keep max 2 consecutive empty lines between REQs
-            if consecutive_empty <= 2 {
-                lines.push(String::new());
-            }
             in_block = false;
             after_step = false;
             in_poly = false;
             continue;
         }
 
+        let is_req = trimmed.starts_with("[REQ]");
+        let is_def = ["[DTO]", "[TYP]", "[NON]", "[DOC]"].iter().any(|tag| trimmed.starts_with(tag));
+        let blanks = if is_req && section == Section::Req {
+            // Exactly two blank lines between consecutive requirements
+            2
+        } else if is_def && section != Section::None {
+            // One blank line before each definition group; definitions written
+            // back to back (e.g. a run of [TYP]s) stay grouped.
+            if consecutive_empty == 0 && section == Section::Def { 0 } else { 1 }
+        } else {
+            consecutive_empty.min(2)
+        };
+        lines.extend(std::iter::repeat_n(String::new(), blanks));
         consecutive_empty = 0;
+        if is_req {
+            section = Section::Req;
+        } else if is_def {
+            section = Section::Def;
+        } else if section == Section::None || (blanks > 0 && !in_block) {
+            section = Section::Other;
+        }
 
         // Normalize line based on content
         if trimmed.starts_with("[REQ]") {
@@ -61,18 +175,19 @@ fn format_content(content: &str) -> String {
             in_block = true;
             after_step = false;
             in_poly = false;
-        } else if trimmed.starts_with("[DTO]") || trimmed.starts_with("[TYP]") || trimmed.starts_with("[NON]") {
+        } else if is_def {
             // Definitions at column 0
             lines.push(trimmed.to_string());
             in_block = true;
             after_step = false;
             in_poly = false;
+            in_doc = trimmed.starts_with("[DOC]");
         } else if trimmed.starts_with("[PLY]") {
             // Opens a polymorphic block; the tag itself sits at REQ-step level (4).
             lines.push(format!("    {}", trimmed));
             after_step = false;
             in_poly = true;
-        } else if trimmed.starts_with("[NEW]") || trimmed.starts_with("[RET]") {
+        } else if ["[CTR]", "[NEW]", "[RET]"].iter().any(|tag| trimmed.starts_with(tag)) {
             // REQ-level tags at 4 spaces; they close any open poly block.
             lines.push(format!("    {}", trimmed));
             after_step = false;
@@ -104,7 +219,7 @@ fn format_content(content: &str) -> String {
             after_step = false;
         } else {
             // Preserve original indentation for unknown lines
-            lines.push(line.to_string());
+            lines.push(line.trim_end().to_string());
             after_step = false;
         }
     }
@@ -174,7 +289,7 @@ mod tests {
 
         fs::write(&input_path, "   [REQ] test.run(In): Out").unwrap();
 
-        let result = format(&input_path, true);
+        let result = format(&input_path, true, false);
         assert!(result.is_ok());
         assert!(!result.unwrap()); // Should need formatting
     }
@@ -186,7 +301,7 @@ mod tests {
 
         fs::write(&input_path, "[REQ] test.run(In): Out\n").unwrap();
 
-        let result = format(&input_path, true);
+        let result = format(&input_path, true, false);
         assert!(result.is_ok());
         assert!(result.unwrap()); // Should be formatted
     }
@@ -210,6 +325,16 @@ mod tests {
         assert!(out.contains("\n        ex:ch.mail(InDto): OutDto"), "case step must stay at indent 8");
     }
 
+    #[test]
+    fn constructor_after_ply_closes_the_block() {
+        // Written inside the last case, a [CTR] still belongs to the REQ and
+        // ends the [PLY] block, so the step after it goes back to indent 4.
+        let input = "[REQ] n.send(InDto): OutDto\n    [PLY] ch.deliver(InDto): OutDto\n        [CSE] email\n        ex:ch.mail(InDto): OutDto\n        [CTR] store  // note: cached\n        store.put(OutDto): OutDto\n";
+        let out = format_content(input);
+        assert!(out.contains("\n    [CTR] store  // note: cached\n"), "got:\n{out}");
+        assert!(out.contains("\n    store.put(OutDto): OutDto"), "got:\n{out}");
+    }
+
     #[test]
     fn description_with_punctuation_is_untouched() {
         // (sanity) descriptions are free text; the formatter shouldn't choke on them
@@ -217,4 +342,58 @@ mod tests {
         let out = format_content(input);
         assert!(out.contains("rafac@monsterrg.com e.g. WGS"));
     }
+
+    #[test]
+    fn normalizes_separators_by_scope() {
+        let input = "[REQ] file.save(FileDto): IdDto
+    [CTR] storage
+    storage::put(FileDto): IdDto
+    db:ledger::record(IdDto): IdDto
+    id::create(FileDto): IdDto
+
+
+[REQ] file.copy(FileDto): IdDto
+    storage.put(FileDto): IdDto
+    id.create(FileDto): IdDto
+    mailer.send(IdDto): IdDto
+";
+        let expected = input
+            .replace("    storage::put", "    storage.put")
+            .replace("    id.create", "    id::create");
+        assert_eq!(normalize_separators(input), expected);
+        assert_eq!(normalize_separators(&expected), expected);
+    }
+
+    #[test]
+    fn keeps_doc_blocks_as_written() {
+        let input = "[REQ] a.run(InDto): OutDto\n    a.make(InDto): OutDto\n[DOC]   Notes\n  a.b(c): d is prose\n\n      timeout\n[DTO] InDto: name\n";
+        let expected = "[REQ] a.run(InDto): OutDto\n    a.make(InDto): OutDto\n\n[DOC]   Notes\n  a.b(c): d is prose\n\n      timeout\n[DTO] InDto: name\n";
+        assert_eq!(format_content(input), expected);
+        assert_eq!(format_content(expected), expected);
+    }
+
+    const UNFORMATTED: &str = "[REQ] a.run(InDto): OutDto   \n    db:a.save(InDto): OutDto\n      timeout\n[REQ] b.run(InDto): OutDto\n    b.make(InDto): OutDto\n\n\n\n\n[REQ] c.run(InDto): OutDto\n    c.make(InDto): OutDto\n[DTO] InDto: name\n    the input  \n\n\n[DTO] OutDto: name\n    the output\n[TYP] name: string\n    a name\n[TYP] age: number\n    an age\n";
+
+    #[test]
+    fn normalizes_spacing_between_blocks() {
+        let expected = "[REQ] a.run(InDto): OutDto\n    db:a.save(InDto): OutDto\n      timeout\n\n\n[REQ] b.run(InDto): OutDto\n    b.make(InDto): OutDto\n\n\n[REQ] c.run(InDto): OutDto\n    c.make(InDto): OutDto\n\n[DTO] InDto: name\n    the input\n\n[DTO] OutDto: name\n    the output\n[TYP] name: string\n    a name\n[TYP] age: number\n    an age\n";
+        assert_eq!(format_content(UNFORMATTED), expected);
+    }
+
+    #[test]
+    fn formatting_is_idempotent() {
+        let once = format_content(UNFORMATTED);
+        assert_eq!(format_content(&once), once);
+    }
+
+    #[test]
+    fn check_only_flags_spacing_without_writing() {
+        let temp = tempdir().unwrap();
+        let input_path = temp.path().join("example.rune");
+        let content = "[REQ] a.run(InDto): OutDto\n\n[REQ] b.run(InDto): OutDto\n";
+        fs::write(&input_path, content).unwrap();
+
+        assert!(!format(&input_path, true, false).unwrap());
+        assert_eq!(fs::read_to_string(&input_path).unwrap(), content);
+    }
 }
@@ -0,0 +1,363 @@
+//! Graph command - renders the requirement/noun call graph of a .rune file
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use clap::ValueEnum;
+use rune_parser::parse_document;
+
+use crate::analyzer::{analyze, AnalyzedSpec, StepKind};
+
+use super::{warn_conflicts, CommandError};
+
+/// Graph output syntax
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum GraphFormat {
+    Mermaid,
+    Dot,
+}
+
+/// What a graph node stands for
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeKind {
+    Req,
+    Noun,
+    Case,
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphNode {
+    pub id: String,
+    pub label: String,
+    pub kind: NodeKind,
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub label: String,
+    pub boundary: Option<String>, // "db:", "ex:", ...
+}
+
+/// A `[CSE]` branch, drawn as a subgraph around its case node
+#[derive(Debug, Clone)]
+pub struct GraphCase {
+    pub id: String,
+    pub label: String,
+    pub node: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    pub cases: Vec<GraphCase>,
+    /// ID handed out for each (kind, name), so two names that sanitize the
+    /// same way still get different IDs
+    ids: HashMap<(String, String), String>,
+}
+
+impl Graph {
+    /// The ID for `name`: letters and digits kept, everything else `_`, and
+    /// a `_2`, `_3`, ... suffix when another name already sanitized to it.
+    fn node_id(&mut self, kind: &str, name: &str) -> String {
+        if let Some(id) = self.ids.get(&(kind.to_string(), name.to_string())) {
+            return id.clone();
+        }
+        let safe: String = name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let base = format!("{}_{}", kind, safe);
+        let mut id = base.clone();
+        let mut n = 1;
+        while self.ids.values().any(|taken| *taken == id) {
+            n += 1;
+            id = format!("{}_{}", base, n);
+        }
+        self.ids.insert((kind.to_string(), name.to_string()), id.clone());
+        id
+    }
+
+    fn add_node(&mut self, id: String, label: String, kind: NodeKind) {
+        if !self.nodes.iter().any(|n| n.id == id) {
+            self.nodes.push(GraphNode { id, label, kind });
+        }
+    }
+}
+
+/// Render the graph of a .rune file to stdout, or to `output` when given
+pub fn graph(input_path: &Path, format: GraphFormat, output: Option<&Path>) -> Result<(), CommandError> {
+    let content = fs::read_to_string(input_path).map_err(|e| CommandError::io("read", input_path, e))?;
+
+    let spec = analyze(&content);
+    warn_conflicts(input_path, &spec);
+    let graph = graph_of(&spec, &content);
+    let rendered = match format {
+        GraphFormat::Mermaid => to_mermaid(&graph),
+        GraphFormat::Dot => to_dot(&graph),
+    };
+
+    match output {
+        Some(path) => fs::write(path, rendered).map_err(|e| CommandError::io("write", path, e)),
+        None => {
+            print!("{}", rendered);
+            Ok(())
+        }
+    }
+}
+
+/// Build the call graph: one node per REQ, one per called noun, and one per
+/// `[CSE]` branch. Steps inside a case hang off the case node.
+pub fn build_graph(text: &str) -> Graph {
+    graph_of(&analyze(text), text)
+}
+
+/// `build_graph` for an already analyzed `text`
+fn graph_of(spec: &AnalyzedSpec, text: &str) -> Graph {
+    // StepInfo has no indent; look it up to tell case steps from the steps
+    // that follow a [PLY] block at REQ level.
+    let indents: HashMap<usize, usize> = parse_document(text)
+        .iter()
+        .map(|l| (l.line_num, l.indent))
+        .collect();
+
+    let mut graph = Graph::default();
+    for req in &spec.requirements {
+        let req_id = graph.node_id("req", &format!("{}.{}", req.noun, req.verb));
+        graph.add_node(req_id.clone(), format!("{}.{}", req.noun, req.verb), NodeKind::Req);
+
+        let mut ply: Option<(String, usize, usize)> = None; // (noun node, indent, line)
+        let mut case: Option<String> = None;
+
+        for step in &req.steps {
+            let indent = indents.get(&step.line_num).copied().unwrap_or(0);
+            if let Some((_, ply_indent, _)) = &ply
+                && indent <= *ply_indent
+                && !matches!(step.kind, StepKind::Case(_))
+            {
+                ply = None;
+                case = None;
+            }
+
+            let (label, target) = match &step.kind {
+                StepKind::Return => continue,
+                StepKind::Case(name) => {
+                    let Some((ply_node, _, ply_line)) = ply.clone() else { continue };
+                    // The same case name can come back under another [PLY]
+                    let case_name = format!("{}.{}.{}.{}", req.noun, req.verb, ply_line, name);
+                    let case_id = graph.node_id("case", &case_name);
+                    let cse_id = graph.node_id("cse", &case_name);
+                    graph.add_node(case_id.clone(), format!("[CSE] {}", name), NodeKind::Case);
+                    graph.cases.push(GraphCase {
+                        id: cse_id,
+                        label: name.clone(),
+                        node: case_id.clone(),
+                    });
+                    graph.edges.push(GraphEdge {
+                        from: ply_node,
+                        to: case_id.clone(),
+                        label: String::new(),
+                        boundary: None,
+                    });
+                    case = Some(case_id);
+                    continue;
+                }
+                StepKind::Constructor => ("new".to_string(), step.noun.clone()),
+                _ => (step.verb.clone(), step.noun.clone()),
+            };
+            if target.is_empty() {
+                continue;
+            }
+
+            let noun_id = graph.node_id("noun", &target);
+            graph.add_node(noun_id.clone(), target, NodeKind::Noun);
+            graph.edges.push(GraphEdge {
+                from: case.clone().unwrap_or_else(|| req_id.clone()),
+                to: noun_id.clone(),
+                label,
+                boundary: step.boundary.clone(),
+            });
+
+            if step.kind == StepKind::Polymorphic {
+                ply = Some((noun_id, indent, step.line_num + 1));
+                case = None;
+            }
+        }
+    }
+    graph
+}
+
+/// Render as a Mermaid flowchart
+pub fn to_mermaid(graph: &Graph) -> String {
+    let mut out = String::from("flowchart LR\n");
+    for node in &graph.nodes {
+        if graph.cases.iter().any(|c| c.node == node.id) {
+            continue;
+        }
+        let shape = match node.kind {
+            NodeKind::Req => format!("[\"{}\"]", node.label),
+            _ => format!("([\"{}\"])", node.label),
+        };
+        out.push_str(&format!("    {}{}\n", node.id, shape));
+    }
+    for case in &graph.cases {
+        out.push_str(&format!("    subgraph {} [\"[CSE] {}\"]\n", case.id, case.label));
+        out.push_str(&format!("        {}{{\"{}\"}}\n", case.node, case.label));
+        out.push_str("    end\n");
+    }
+    let mut link_styles = Vec::new();
+    for (i, edge) in graph.edges.iter().enumerate() {
+        let arrow = match (&edge.boundary, edge.label.is_empty()) {
+            (Some(prefix), _) => {
+                link_styles.push(format!("    linkStyle {} stroke:{}\n", i, boundary_color(prefix)));
+                format!("-.->|\"{}{}\"|", prefix, edge.label)
+            }
+            (None, true) => "-->".to_string(),
+            (None, false) => format!("-->|\"{}\"|", edge.label),
+        };
+        out.push_str(&format!("    {} {} {}\n", edge.from, arrow, edge.to));
+    }
+    for style in link_styles {
+        out.push_str(&style);
+    }
+    out
+}
+
+/// Render as a Graphviz digraph
+pub fn to_dot(graph: &Graph) -> String {
+    let mut out = String::from("digraph rune {\n    rankdir=LR;\n");
+    for node in &graph.nodes {
+        if graph.cases.iter().any(|c| c.node == node.id) {
+            continue;
+        }
+        let shape = match node.kind {
+            NodeKind::Req => "box",
+            _ => "ellipse",
+        };
+        out.push_str(&format!("    {} [label=\"{}\", shape={}];\n", node.id, node.label, shape));
+    }
+    for case in &graph.cases {
+        out.push_str(&format!("    subgraph cluster_{} {{\n", case.id));
+        out.push_str(&format!("        label=\"[CSE] {}\";\n", case.label));
+        out.push_str(&format!("        {} [label=\"{}\", shape=diamond];\n", case.node, case.label));
+        out.push_str("    }\n");
+    }
+    for edge in &graph.edges {
+        let attrs = match &edge.boundary {
+            Some(prefix) => format!(
+                " [label=\"{}{}\", style=dashed, color=\"{}\"]",
+                prefix,
+                edge.label,
+                boundary_color(prefix)
+            ),
+            None if edge.label.is_empty() => String::new(),
+            None => format!(" [label=\"{}\"]", edge.label),
+        };
+        out.push_str(&format!("    {} -> {}{};\n", edge.from, edge.to, attrs));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn boundary_color(prefix: &str) -> &'static str {
+    match prefix {
+        "db:" => "#1f77b4",
+        "fs:" => "#2ca02c",
+        "mq:" => "#9467bd",
+        "ex:" => "#d62728",
+        "os:" => "#8c564b",
+        _ => "#7f7f7f",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const SPEC: &str = "[REQ] notify.send(NotifyDto): ReceiptDto
+    [NEW] channel
+    [PLY] channel.deliver(NotifyDto): ReceiptDto
+        [CSE] email
+        ex:smtp.send(NotifyDto): ReceiptDto
+          timeout
+        [CSE] sms
+        ex:carrier.send(NotifyDto): ReceiptDto
+    db:receipt.save(ReceiptDto): ReceiptDto
+    [RET] ReceiptDto
+";
+
+    #[test]
+    fn counts_nodes_and_edges() {
+        let graph = build_graph(SPEC);
+        // notify.send, channel, email, smtp, sms, carrier, receipt
+        assert_eq!(graph.nodes.len(), 7);
+        // new channel, deliver, 2x ply->case, smtp, carrier, receipt
+        assert_eq!(graph.edges.len(), 7);
+        assert_eq!(graph.cases.len(), 2);
+    }
+
+    #[test]
+    fn step_after_ply_stays_on_the_req() {
+        let graph = build_graph(SPEC);
+        let save = graph.edges.iter().find(|e| e.to == "noun_receipt").unwrap();
+        assert_eq!(save.from, "req_notify_send");
+        assert_eq!(save.boundary.as_deref(), Some("db:"));
+        let smtp = graph.edges.iter().find(|e| e.to == "noun_smtp").unwrap();
+        assert_eq!(smtp.from, "case_notify_send_3_email");
+    }
+
+    #[test]
+    fn renders_both_formats() {
+        let graph = build_graph(SPEC);
+        let mermaid = to_mermaid(&graph);
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains("subgraph cse_notify_send_3_email"));
+        assert!(mermaid.contains("req_notify_send -.->|\"db:save\"| noun_receipt"));
+
+        let dot = to_dot(&graph);
+        assert!(dot.starts_with("digraph rune {"));
+        assert!(dot.contains("subgraph cluster_cse_notify_send_3_sms"));
+        assert!(dot.contains("style=dashed"));
+    }
+
+    #[test]
+    fn same_case_name_under_two_plys() {
+        let spec = "[REQ] notify.send(NotifyDto): ReceiptDto
+    [PLY] channel.deliver(NotifyDto): ReceiptDto
+        [CSE] email
+        ex:smtp.send(NotifyDto): ReceiptDto
+    [PLY] channel.retry(NotifyDto): ReceiptDto
+        [CSE] email
+        ex:queue.send(NotifyDto): ReceiptDto
+";
+        let graph = build_graph(spec);
+        let cases: Vec<_> = graph.cases.iter().map(|c| c.node.as_str()).collect();
+        assert_eq!(cases, ["case_notify_send_2_email", "case_notify_send_5_email"]);
+        let queue = graph.edges.iter().find(|e| e.to == "noun_queue").unwrap();
+        assert_eq!(queue.from, "case_notify_send_5_email");
+    }
+
+    #[test]
+    fn node_ids_do_not_collide() {
+        let mut graph = Graph::default();
+        assert_eq!(graph.node_id("req", "a_b.c"), "req_a_b_c");
+        assert_eq!(graph.node_id("req", "a.b_c"), "req_a_b_c_2");
+        assert_eq!(graph.node_id("req", "a_b.c"), "req_a_b_c");
+        assert_eq!(graph.node_id("req", "a_b_c_2"), "req_a_b_c_2_2");
+    }
+
+    #[test]
+    fn writes_to_output_file() {
+        let temp = tempdir().unwrap();
+        let input = temp.path().join("spec.rune");
+        let output = temp.path().join("graph.dot");
+        fs::write(&input, SPEC).unwrap();
+
+        graph(&input, GraphFormat::Dot, Some(&output)).unwrap();
+        assert!(fs::read_to_string(&output).unwrap().starts_with("digraph rune {"));
+    }
+}
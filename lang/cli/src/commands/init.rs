@@ -0,0 +1,157 @@
+//! Init command - scaffolds a new project with a starter .rune spec
+
+use std::fs;
+use std::path::Path;
+
+use super::{CommandError, Verbosity};
+
+/// Scaffold `<dir>/<name>/<name>.rune`, `<dir>/<name>/rune.toml` and the
+/// `deno.json` the generated TypeScript needs.
+/// Refuses to touch a directory that already has files in it.
+pub fn init(dir: &Path, name: &str, verbosity: Verbosity) -> Result<(), CommandError> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(CommandError::InvalidName(name.to_string()));
+    }
+
+    let root = dir.join(name);
+    if root.exists() {
+        let mut entries = fs::read_dir(&root)
+            .map_err(|e| CommandError::io("read", &root, e))?;
+        if entries.next().is_some() {
+            return Err(CommandError::Collision(root));
+        }
+    }
+    fs::create_dir_all(&root)
+        .map_err(|e| CommandError::io("create", &root, e))?;
+
+    let spec_path = root.join(format!("{}.rune", name));
+    fs::write(&spec_path, STARTER_SPEC)
+        .map_err(|e| CommandError::io("write", &spec_path, e))?;
+    verbosity.detail(format!("  wrote {}", spec_path.display()));
+
+    let config_path = root.join("rune.toml");
+    fs::write(&config_path, format!("[project]\nname = \"{}\"\nspec = \"{}.rune\"\n", name, name))
+        .map_err(|e| CommandError::io("write", &config_path, e))?;
+    verbosity.detail(format!("  wrote {}", config_path.display()));
+
+    let deno_path = root.join("deno.json");
+    fs::write(&deno_path, DENO_JSON)
+        .map_err(|e| CommandError::io("write", &deno_path, e))?;
+    verbosity.detail(format!("  wrote {}", deno_path.display()));
+
+    verbosity.say(format!("Created {}", root.display()));
+    verbosity.say("");
+    verbosity.say("Next steps:");
+    verbosity.say(format!("  cd {}", root.display()));
+    verbosity.say(format!("  rune-syntax validate {}.rune", name));
+    verbosity.say(format!("  rune-syntax watch {}.rune", name));
+
+    Ok(())
+}
+
+/// Same imports and compiler options as `REQUIRED_IMPORTS` /
+/// `REQUIRED_COMPILER_OPTIONS` in src/rune/entrypoints/sync/mod.ts, so the
+/// first sync has nothing to add.
+const DENO_JSON: &str = r##"{
+  "tasks": {
+    "check": "deno check .",
+    "test": "deno test --allow-all"
+  },
+  "compilerOptions": {
+    "strict": true,
+    "experimentalDecorators": true,
+    "emitDecoratorMetadata": true
+  },
+  "imports": {
+    "@/": "./",
+    "class-validator": "npm:class-validator@^0.14",
+    "class-transformer": "npm:class-transformer@^0.5",
+    "reflect-metadata": "npm:reflect-metadata@^0.2",
+    "#std/assert": "jsr:@std/assert",
+    "#std/path": "jsr:@std/path",
+    "@mrg-keystone/keep": "jsr:@mrg-keystone/keep@^1",
+    "#assert": "jsr:@mrg-keystone/keep@^1/assert",
+    "#api-doc": "jsr:@danet/swagger@^2.1.1/decorators"
+  }
+}
+"##;
+
+/// A complete, valid example: a pure step, a boundary step with a fault, DTOs
+/// with descriptions and the [TYP]s they use.
+const STARTER_SPEC: &str = "[REQ] greeting.create(GreetingRequestDto): GreetingDto
+    greeting::compose(name): message
+    db:greeting.save(message): GreetingDto
+      timeout
+    [RET] GreetingDto
+
+[DTO] GreetingRequestDto: name
+    who to greet
+
+[DTO] GreetingDto: message
+    a stored greeting
+
+[TYP] name: string
+    the name of the person being greeted
+[TYP] message: string
+    the composed greeting text
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::validate;
+    use tempfile::tempdir;
+
+    #[test]
+    fn scaffolds_a_valid_spec() {
+        let temp = tempdir().unwrap();
+        init(temp.path(), "hello", Verbosity::Quiet).unwrap();
+
+        let spec = temp.path().join("hello/hello.rune");
+        let errors = validate(&spec).unwrap();
+        assert!(errors.is_empty(), "starter spec has errors: {:?}", errors);
+        let config = fs::read_to_string(temp.path().join("hello/rune.toml")).unwrap();
+        assert!(config.contains("name = \"hello\""));
+    }
+
+    #[test]
+    fn writes_deno_json() {
+        let temp = tempdir().unwrap();
+        init(temp.path(), "hello", Verbosity::Quiet).unwrap();
+
+        let deno: serde_json::Value = serde_json::from_str(&fs::read_to_string(temp.path().join("hello/deno.json")).unwrap()).unwrap();
+        for key in ["@/", "class-validator", "class-transformer", "reflect-metadata", "#std/assert"] {
+            assert!(deno["imports"].get(key).is_some(), "missing import {}", key);
+        }
+        assert!(deno["tasks"]["test"].is_string());
+        assert!(deno["tasks"]["check"].is_string());
+        assert_eq!(deno["compilerOptions"]["experimentalDecorators"], true);
+    }
+
+    #[test]
+    fn refuses_non_empty_directory() {
+        let temp = tempdir().unwrap();
+        fs::create_dir(temp.path().join("hello")).unwrap();
+        fs::write(temp.path().join("hello/notes.txt"), "keep me").unwrap();
+
+        let err = init(temp.path(), "hello", Verbosity::Quiet).unwrap_err();
+        assert!(matches!(&err, CommandError::Collision(path) if *path == temp.path().join("hello")));
+        assert!(err.to_string().contains("not empty"));
+        assert_eq!(fs::read_to_string(temp.path().join("hello/notes.txt")).unwrap(), "keep me");
+    }
+
+    #[test]
+    fn rejects_invalid_names() {
+        let temp = tempdir().unwrap();
+        let err = init(temp.path(), "my app", Verbosity::Quiet).unwrap_err();
+        assert!(matches!(err, CommandError::InvalidName(ref name) if name == "my app"));
+        assert_eq!(err.to_string(), "Invalid project name 'my app': use letters, digits, '-' or '_'");
+    }
+
+    #[test]
+    fn accepts_empty_directory() {
+        let temp = tempdir().unwrap();
+        fs::create_dir(temp.path().join("hello")).unwrap();
+        assert!(init(temp.path(), "hello", Verbosity::Quiet).is_ok());
+    }
+}
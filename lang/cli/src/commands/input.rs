@@ -0,0 +1,34 @@
+//! Input helpers - `-` as an input path means "read the document from stdin"
+
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+use super::CommandError;
+
+/// Whether `path` is the conventional `-` placeholder for stdin
+pub fn is_stdin(path: &Path) -> bool {
+    path.as_os_str() == "-"
+}
+
+/// Name to report in diagnostics: the path, or `<stdin>`
+pub fn display_name(path: &Path) -> String {
+    if is_stdin(path) {
+        "<stdin>".to_string()
+    } else {
+        path.display().to_string()
+    }
+}
+
+/// Read a .rune document from a file, or from stdin when the path is `-`
+pub fn read_input(path: &Path) -> Result<String, CommandError> {
+    if is_stdin(path) {
+        let mut content = String::new();
+        io::stdin()
+            .read_to_string(&mut content)
+            .map_err(|e| CommandError::io("read", "<stdin>", e))?;
+        Ok(content)
+    } else {
+        fs::read_to_string(path).map_err(|e| CommandError::io("read", path, e))
+    }
+}
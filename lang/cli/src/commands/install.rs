@@ -4,11 +4,13 @@
 
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use inquire::{MultiSelect, Select};
 
+use super::{CommandError, Verbosity};
+
 // Embed grammar source files at compile time
 const PARSER_C: &str = include_str!("../../../grammar/src/parser.c");
 const SCANNER_C: &str = include_str!("../../../grammar/src/scanner.c");
@@ -17,6 +19,58 @@ const ALLOC_H: &str = include_str!("../../../grammar/src/tree_sitter/alloc.h");
 const ARRAY_H: &str = include_str!("../../../grammar/src/tree_sitter/array.h");
 const HIGHLIGHTS_SCM: &str = include_str!("../../../queries/highlights.scm");
 
+/// Identifies the embedded grammar: a hash of `parser.c` and `scanner.c`.
+/// `install` records it next to the built parser so `version --check` can
+/// tell when the installed parser predates this binary.
+pub const GRAMMAR_VERSION: u64 = fnv1a(fnv1a(FNV_OFFSET, PARSER_C.as_bytes()), SCANNER_C.as_bytes());
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// 64-bit FNV-1a, continuing from `hash`. Unlike `DefaultHasher` its output
+/// is fixed across Rust releases, so recorded versions stay comparable.
+const fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+        i += 1;
+    }
+    hash
+}
+
+/// `GRAMMAR_VERSION` as written to and read from the version file
+pub fn grammar_version() -> String {
+    format!("{:016x}", GRAMMAR_VERSION)
+}
+
+/// How the installed parser compares to the grammar embedded in this binary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GrammarStatus {
+    UpToDate,
+    /// Built from other sources; holds the recorded version, if any
+    Stale(Option<String>),
+    NotInstalled,
+}
+
+/// Compare the parser installed under the rune data directory with the
+/// embedded grammar
+pub fn grammar_status() -> GrammarStatus {
+    grammar_status_in(&data_dir())
+}
+
+/// `grammar_status` for an explicit data directory
+pub fn grammar_status_in(data: &Path) -> GrammarStatus {
+    if !data.join("parser/rune.so").exists() {
+        return GrammarStatus::NotInstalled;
+    }
+    // Installs from before the version file existed count as stale
+    match fs::read_to_string(data.join("parser/rune.version")) {
+        Ok(recorded) if recorded.trim() == grammar_version() => GrammarStatus::UpToDate,
+        Ok(recorded) => GrammarStatus::Stale(Some(recorded.trim().to_string())),
+        Err(_) => GrammarStatus::Stale(None),
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum Editor {
     Neovim,
@@ -36,6 +90,7 @@ pub struct IconTargets {
 }
 
 impl Editor {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "neovim" | "nvim" | "1" => Some(Editor::Neovim),
@@ -49,8 +104,8 @@ impl Editor {
     }
 }
 
-/// Get the rune data directory
-fn data_dir() -> PathBuf {
+/// Get the rune data directory (`RUNE_DATA` overrides)
+pub fn data_dir() -> PathBuf {
     env::var("RUNE_DATA")
         .map(PathBuf::from)
         .unwrap_or_else(|_| {
@@ -60,8 +115,8 @@ fn data_dir() -> PathBuf {
         })
 }
 
-/// Get the rune binary directory
-fn bin_dir() -> PathBuf {
+/// Get the rune binary directory (`RUNE_BIN` overrides)
+pub fn bin_dir() -> PathBuf {
     env::var("RUNE_BIN")
         .map(PathBuf::from)
         .unwrap_or_else(|_| {
@@ -92,14 +147,18 @@ fn find_source_dir() -> Option<PathBuf> {
 }
 
 /// Install Rune components with interactive prompts
-pub fn install(yes: bool) -> Result<(), String> {
+pub fn install(yes: bool, verbosity: Verbosity) -> Result<(), CommandError> {
+    run_install(yes, verbosity).map_err(CommandError::Setup)
+}
+
+fn run_install(yes: bool, verbosity: Verbosity) -> Result<(), String> {
     let data = data_dir();
     let bin = bin_dir();
 
-    println!("Installing Rune...");
-    println!("  Data: {}", data.display());
-    println!("  Bin:  {}", bin.display());
-    println!();
+    verbosity.say("Installing Rune...");
+    verbosity.say(format!("  Data: {}", data.display()));
+    verbosity.say(format!("  Bin:  {}", bin.display()));
+    verbosity.say("");
 
     // Create directories
     fs::create_dir_all(&data).map_err(|e| format!("Failed to create data dir: {}", e))?;
@@ -110,15 +169,15 @@ pub fn install(yes: bool) -> Result<(), String> {
     // Write embedded queries
     fs::write(data.join("queries/highlights.scm"), HIGHLIGHTS_SCM)
         .map_err(|e| format!("Failed to write queries: {}", e))?;
-    println!("  ✓ Queries installed");
+    verbosity.say("  ✓ Queries installed");
 
     // Build tree-sitter parser from embedded sources
-    build_parser(&data)?;
+    build_parser(&data, verbosity)?;
 
     // Build and install LSP
-    build_lsp(&bin)?;
+    build_lsp(&bin, verbosity)?;
 
-    println!();
+    verbosity.say("");
 
     // Editor, shell, and icon setup
     let (editors, shell, icons) = if yes {
@@ -128,50 +187,54 @@ pub fn install(yes: bool) -> Result<(), String> {
     };
 
     for editor in editors {
-        setup_editor(editor, &data)?;
+        setup_editor(editor, &data, verbosity)?;
     }
 
-    println!();
+    verbosity.say("");
 
     if let Some(shell) = shell {
-        setup_shell_completions(&shell)?;
+        setup_shell_completions(&shell, verbosity)?;
     }
 
-    println!();
+    verbosity.say("");
 
-    if icons.yazi { setup_yazi_icons()?; }
-    if icons.lf { setup_lf_icons()?; }
-    if icons.eza { setup_eza_icons()?; }
-    if icons.lsd { setup_lsd_icons()?; }
+    if icons.yazi { setup_yazi_icons(verbosity)?; }
+    if icons.lf { setup_lf_icons(verbosity)?; }
+    if icons.eza { setup_eza_icons(verbosity)?; }
+    if icons.lsd { setup_lsd_icons(verbosity)?; }
 
-    println!();
-    println!("Done!");
+    verbosity.say("");
+    verbosity.say("Done!");
 
     Ok(())
 }
 
 /// Uninstall Rune components
-pub fn uninstall(editor: Option<Editor>) -> Result<(), String> {
+pub fn uninstall(editor: Option<Editor>, verbosity: Verbosity) -> Result<(), CommandError> {
+    run_uninstall(editor, verbosity).map_err(CommandError::Setup)
+}
+
+fn run_uninstall(editor: Option<Editor>, verbosity: Verbosity) -> Result<(), String> {
     let data = data_dir();
     let bin = bin_dir();
 
-    println!("Uninstalling Rune...");
-    println!();
+    verbosity.say("Uninstalling Rune...");
+    verbosity.say("");
 
     // Remove data directory
     if data.exists() {
         fs::remove_dir_all(&data).map_err(|e| format!("Failed to remove data dir: {}", e))?;
-        println!("  ✓ Data directory removed");
+        verbosity.say("  ✓ Data directory removed");
     }
 
     // Remove LSP binary
     let lsp_path = bin.join("rune-lsp");
     if lsp_path.exists() {
         fs::remove_file(&lsp_path).map_err(|e| format!("Failed to remove LSP: {}", e))?;
-        println!("  ✓ LSP removed");
+        verbosity.say("  ✓ LSP removed");
     }
 
-    println!();
+    verbosity.say("");
 
     // Editor cleanup
     let editor = match editor {
@@ -179,39 +242,33 @@ pub fn uninstall(editor: Option<Editor>) -> Result<(), String> {
         None => prompt_editor()?,
     };
 
-    cleanup_editor(editor)?;
+    cleanup_editor(editor, verbosity)?;
 
-    println!();
-    println!("Done!");
+    verbosity.say("");
+    verbosity.say("Done!");
 
     Ok(())
 }
 
-fn cleanup_editor(editor: Editor) -> Result<(), String> {
+fn cleanup_editor(editor: Editor, verbosity: Verbosity) -> Result<(), String> {
     match editor {
-        Editor::Neovim => cleanup_neovim(),
-        Editor::Helix => cleanup_helix(),
+        Editor::Neovim => cleanup_neovim(verbosity),
+        Editor::Helix => cleanup_helix(verbosity),
         Editor::VSCode => {
             println!("VS Code: Remove the extension manually");
             Ok(())
         }
-        Editor::Zed => {
-            println!("Zed: Remove rune from your languages config manually");
-            Ok(())
-        }
+        Editor::Zed => cleanup_zed(verbosity),
         Editor::Sublime => {
             println!("Sublime: Remove syntax files from Packages/User/ manually");
             Ok(())
         }
-        Editor::Emacs => {
-            println!("Emacs: Remove rune-mode from your config manually");
-            Ok(())
-        }
+        Editor::Emacs => cleanup_emacs(verbosity),
     }
 }
 
-fn cleanup_neovim() -> Result<(), String> {
-    println!("Cleaning up Neovim...");
+fn cleanup_neovim(verbosity: Verbosity) -> Result<(), String> {
+    verbosity.say("Cleaning up Neovim...");
 
     let home = dirs::home_dir().ok_or("Could not find home directory")?;
     let nvim_site = home.join(".local/share/nvim/site");
@@ -221,35 +278,35 @@ fn cleanup_neovim() -> Result<(), String> {
     let parser = nvim_site.join("parser/rune.so");
     if parser.exists() {
         fs::remove_file(&parser).map_err(|e| format!("Failed to remove parser: {}", e))?;
-        println!("  ✓ Parser removed");
+        verbosity.say("  ✓ Parser removed");
     }
 
     // Remove queries
     let queries = nvim_site.join("queries/rune");
     if queries.exists() {
         fs::remove_dir_all(&queries).map_err(|e| format!("Failed to remove queries: {}", e))?;
-        println!("  ✓ Queries removed");
+        verbosity.say("  ✓ Queries removed");
     }
 
     // Remove ftdetect
     let ftdetect = nvim_config.join("after/ftdetect/rune.lua");
     if ftdetect.exists() {
         fs::remove_file(&ftdetect).map_err(|e| format!("Failed to remove ftdetect: {}", e))?;
-        println!("  ✓ Filetype detection removed");
+        verbosity.say("  ✓ Filetype detection removed");
     }
 
     // Remove ftplugin
     let ftplugin = nvim_config.join("after/ftplugin/rune.lua");
     if ftplugin.exists() {
         fs::remove_file(&ftplugin).map_err(|e| format!("Failed to remove ftplugin: {}", e))?;
-        println!("  ✓ LSP and highlights config removed");
+        verbosity.say("  ✓ LSP and highlights config removed");
     }
 
     Ok(())
 }
 
-fn cleanup_helix() -> Result<(), String> {
-    println!("Cleaning up Helix...");
+fn cleanup_helix(verbosity: Verbosity) -> Result<(), String> {
+    verbosity.say("Cleaning up Helix...");
 
     let config_dir = dirs::config_dir()
         .ok_or("Could not find config directory")?
@@ -259,21 +316,21 @@ fn cleanup_helix() -> Result<(), String> {
     let queries = config_dir.join("runtime/queries/rune");
     if queries.exists() {
         fs::remove_dir_all(&queries).map_err(|e| format!("Failed to remove queries: {}", e))?;
-        println!("  ✓ Queries removed");
+        verbosity.say("  ✓ Queries removed");
     }
 
     // Remove grammar source
     let grammar = config_dir.join("runtime/grammars/sources/rune");
     if grammar.exists() {
         fs::remove_dir_all(&grammar).map_err(|e| format!("Failed to remove grammar: {}", e))?;
-        println!("  ✓ Grammar source removed");
+        verbosity.say("  ✓ Grammar source removed");
     }
 
     // Remove theme
     let theme = config_dir.join("themes/rune.toml");
     if theme.exists() {
         fs::remove_file(&theme).map_err(|e| format!("Failed to remove theme: {}", e))?;
-        println!("  ✓ Theme removed");
+        verbosity.say("  ✓ Theme removed");
     }
 
     println!("  ! Remove rune config from languages.toml manually");
@@ -281,9 +338,69 @@ fn cleanup_helix() -> Result<(), String> {
     Ok(())
 }
 
+fn cleanup_zed(verbosity: Verbosity) -> Result<(), String> {
+    verbosity.say("Cleaning up Zed...");
+    let home = dirs::home_dir().ok_or("Could not find home directory")?;
+    cleanup_zed_in(&home, verbosity)
+}
+
+fn cleanup_zed_in(home: &Path, verbosity: Verbosity) -> Result<(), String> {
+    let settings_path = zed_settings_path(home);
+    if !settings_path.exists() {
+        return Ok(());
+    }
+    let Some(mut settings) = read_zed_settings(&settings_path)? else {
+        println!("  ! settings.json is not plain JSON - remove the Rune entries manually");
+        return Ok(());
+    };
+
+    for (section, key) in [("languages", "Rune"), ("file_types", "Rune"), ("lsp", "rune-lsp")] {
+        if let Some(table) = settings.get_mut(section).and_then(|v| v.as_object_mut()) {
+            table.remove(key);
+            if table.is_empty() {
+                settings.remove(section);
+            }
+        }
+    }
+
+    if settings.is_empty() {
+        fs::remove_file(&settings_path).map_err(|e| format!("Failed to remove settings.json: {}", e))?;
+    } else {
+        write_zed_settings(&settings_path, &settings)?;
+    }
+    verbosity.say("  ✓ Rune removed from settings.json");
+    Ok(())
+}
+
+fn cleanup_emacs(verbosity: Verbosity) -> Result<(), String> {
+    verbosity.say("Cleaning up Emacs...");
+    let home = dirs::home_dir().ok_or("Could not find home directory")?;
+    cleanup_emacs_in(&home, verbosity)
+}
+
+fn cleanup_emacs_in(home: &Path, verbosity: Verbosity) -> Result<(), String> {
+    let init_path = emacs_init_path(home);
+    if !init_path.exists() {
+        return Ok(());
+    }
+    let content = fs::read_to_string(&init_path)
+        .map_err(|e| format!("Failed to read {}: {}", init_path.display(), e))?;
+    let Some(stripped) = strip_marked_block(&content, &format!(";; {}", RUNE_BEGIN), &format!(";; {}", RUNE_END)) else {
+        return Ok(());
+    };
+
+    if stripped.trim().is_empty() {
+        fs::remove_file(&init_path).map_err(|e| format!("Failed to remove {}: {}", init_path.display(), e))?;
+    } else {
+        fs::write(&init_path, stripped).map_err(|e| format!("Failed to update {}: {}", init_path.display(), e))?;
+    }
+    verbosity.say(format!("  ✓ rune-mode removed from {}", init_path.display()));
+    Ok(())
+}
+
 /// Build the tree-sitter parser from embedded sources
-fn build_parser(data: &PathBuf) -> Result<(), String> {
-    println!("Building parser...");
+fn build_parser(data: &Path, verbosity: Verbosity) -> Result<(), String> {
+    verbosity.say("Building parser...");
 
     // Create temp directory for compilation
     let temp_dir = env::temp_dir().join("rune-build");
@@ -313,8 +430,8 @@ fn build_parser(data: &PathBuf) -> Result<(), String> {
     let output_path = data.join("parser").join(output_name);
 
     // Build with cc
-    let output = Command::new("cc")
-        .arg(shared_flag)
+    let mut cc = Command::new("cc");
+    cc.arg(shared_flag)
         .arg("-o")
         .arg(&output_path)
         .arg("-fPIC")
@@ -322,9 +439,9 @@ fn build_parser(data: &PathBuf) -> Result<(), String> {
         .arg(&parser_c)
         .arg(&scanner_c)
         .arg("-I")
-        .arg(&temp_dir)
-        .output()
-        .map_err(|e| format!("Failed to run cc: {}", e))?;
+        .arg(&temp_dir);
+    verbosity.echo(&cc);
+    let output = cc.output().map_err(|e| format!("Failed to run cc: {}", e))?;
 
     // Clean up temp files
     let _ = fs::remove_dir_all(&temp_dir);
@@ -334,27 +451,30 @@ fn build_parser(data: &PathBuf) -> Result<(), String> {
         return Err(format!("Failed to build parser: {}", stderr));
     }
 
-    println!("  ✓ Parser built");
+    fs::write(data.join("parser/rune.version"), grammar_version())
+        .map_err(|e| format!("Failed to write parser version: {}", e))?;
+
+    verbosity.say("  ✓ Parser built");
     Ok(())
 }
 
 /// Build and install the LSP from source
-fn build_lsp(bin_dir: &PathBuf) -> Result<(), String> {
+fn build_lsp(bin_dir: &Path, verbosity: Verbosity) -> Result<(), String> {
     let source_dir = find_source_dir()
         .ok_or("Could not find rune source directory. Run from within the rune repo.")?;
 
-    println!("Building LSP...");
+    verbosity.say("Building LSP...");
 
     // Build with cargo
-    let output = Command::new("cargo")
-        .arg("build")
+    let mut cargo = Command::new("cargo");
+    cargo.arg("build")
         .arg("-p")
         .arg("rune-lsp")
         .arg("--release")
         .arg("--quiet")
-        .current_dir(&source_dir)
-        .output()
-        .map_err(|e| format!("Failed to run cargo: {}", e))?;
+        .current_dir(&source_dir);
+    verbosity.echo(&cargo);
+    let output = cargo.output().map_err(|e| format!("Failed to run cargo: {}", e))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -370,27 +490,28 @@ fn build_lsp(bin_dir: &PathBuf) -> Result<(), String> {
     // Ad-hoc sign on macOS to avoid security kill
     #[cfg(target_os = "macos")]
     {
-        let _ = Command::new("codesign")
-            .arg("-s")
+        let mut codesign = Command::new("codesign");
+        codesign.arg("-s")
             .arg("-")
-            .arg(&dest)
-            .output();
+            .arg(&dest);
+        verbosity.echo(&codesign);
+        let _ = codesign.output();
     }
 
-    println!("  ✓ LSP installed");
+    verbosity.say("  ✓ LSP installed");
     Ok(())
 }
 
 /// Set up shell completions by writing completion file and updating shell config
-fn setup_shell_completions(shell: &str) -> Result<(), String> {
+fn setup_shell_completions(shell: &str, verbosity: Verbosity) -> Result<(), String> {
     let home = dirs::home_dir().ok_or("Could not find home directory")?;
 
     // Generate completion script
-    let completion_script = Command::new("rune")
-        .arg("completions")
-        .arg(shell)
-        .output()
-        .map_err(|e| format!("Failed to generate completions: {}", e))?;
+    let mut completions = Command::new("rune");
+    completions.arg("completions")
+        .arg(shell);
+    verbosity.echo(&completions);
+    let completion_script = completions.output().map_err(|e| format!("Failed to generate completions: {}", e))?;
 
     if !completion_script.status.success() {
         return Err("Failed to generate completion script".to_string());
@@ -407,9 +528,9 @@ fn setup_shell_completions(shell: &str) -> Result<(), String> {
             fs::write(comp_dir.join("_rune"), script.as_ref())
                 .map_err(|e| format!("Failed to write completion file: {}", e))?;
 
-            println!("  ✓ Completions installed to ~/.zsh/completions/_rune");
-            println!("    Add to .zshrc: fpath=(~/.zsh/completions $fpath)");
-            println!("    Then run: rm -f ~/.zcompdump* && exec zsh");
+            verbosity.say("  ✓ Completions installed to ~/.zsh/completions/_rune");
+            verbosity.say("    Add to .zshrc: fpath=(~/.zsh/completions $fpath)");
+            verbosity.say("    Then run: rm -f ~/.zcompdump* && exec zsh");
         }
         "bash" => {
             // Write completion file
@@ -419,7 +540,7 @@ fn setup_shell_completions(shell: &str) -> Result<(), String> {
             fs::write(comp_dir.join("rune"), script.as_ref())
                 .map_err(|e| format!("Failed to write completion file: {}", e))?;
 
-            println!("  ✓ Completions installed to ~/.local/share/bash-completion/completions/rune");
+            verbosity.say("  ✓ Completions installed to ~/.local/share/bash-completion/completions/rune");
         }
         "fish" => {
             // Write completion file
@@ -429,7 +550,7 @@ fn setup_shell_completions(shell: &str) -> Result<(), String> {
             fs::write(comp_dir.join("rune.fish"), script.as_ref())
                 .map_err(|e| format!("Failed to write completion file: {}", e))?;
 
-            println!("  ✓ Completions installed to ~/.config/fish/completions/rune.fish");
+            verbosity.say("  ✓ Completions installed to ~/.config/fish/completions/rune.fish");
         }
         _ => return Err(format!("Unsupported shell: {}", shell)),
     }
@@ -454,7 +575,7 @@ fn prompt_editors() -> Result<Vec<Editor>, String> {
         .prompt()
         .map_err(|e| e.to_string())?;
 
-    Ok(selections.into_iter().filter_map(|s| Editor::from_str(s)).collect())
+    Ok(selections.into_iter().filter_map(Editor::from_str).collect())
 }
 
 fn prompt_shell() -> Result<Option<String>, String> {
@@ -490,31 +611,25 @@ fn prompt_icons() -> Result<IconTargets, String> {
     })
 }
 
-fn setup_editor(editor: Editor, data_dir: &PathBuf) -> Result<(), String> {
+fn setup_editor(editor: Editor, data_dir: &Path, verbosity: Verbosity) -> Result<(), String> {
     match editor {
-        Editor::Neovim => setup_neovim(data_dir),
-        Editor::Helix => setup_helix(data_dir),
+        Editor::Neovim => setup_neovim(data_dir, verbosity),
+        Editor::Helix => setup_helix(data_dir, verbosity),
         Editor::VSCode => {
             println!("VS Code: Install the extension from editors/vscode/");
             Ok(())
         }
-        Editor::Zed => {
-            println!("Zed: Add rune to your languages config");
-            Ok(())
-        }
+        Editor::Zed => setup_zed(verbosity),
         Editor::Sublime => {
             println!("Sublime: Copy syntax files to Packages/User/");
             Ok(())
         }
-        Editor::Emacs => {
-            println!("Emacs: Add rune-mode to your config");
-            Ok(())
-        }
+        Editor::Emacs => setup_emacs(verbosity),
     }
 }
 
-fn setup_neovim(data_dir: &PathBuf) -> Result<(), String> {
-    println!("Setting up Neovim...");
+fn setup_neovim(data_dir: &Path, verbosity: Verbosity) -> Result<(), String> {
+    verbosity.say("Setting up Neovim...");
 
     let home = dirs::home_dir().ok_or("Could not find home directory")?;
     let nvim_site = home.join(".local/share/nvim/site");
@@ -527,7 +642,7 @@ fn setup_neovim(data_dir: &PathBuf) -> Result<(), String> {
     if parser_src.exists() {
         fs::copy(&parser_src, parser_dest.join("rune.so"))
             .map_err(|e| format!("Failed to copy parser: {}", e))?;
-        println!("  ✓ Parser installed");
+        verbosity.say("  ✓ Parser installed");
     }
 
     // Install queries
@@ -537,7 +652,7 @@ fn setup_neovim(data_dir: &PathBuf) -> Result<(), String> {
     if queries_src.exists() {
         fs::copy(&queries_src, queries_dest.join("highlights.scm"))
             .map_err(|e| format!("Failed to copy queries: {}", e))?;
-        println!("  ✓ Queries installed");
+        verbosity.say("  ✓ Queries installed");
     }
 
     // Create ftdetect
@@ -547,7 +662,7 @@ fn setup_neovim(data_dir: &PathBuf) -> Result<(), String> {
   extension = { rune = "rune" },
 })
 "#).map_err(|e| format!("Failed to write ftdetect: {}", e))?;
-    println!("  ✓ Filetype detection configured");
+    verbosity.say("  ✓ Filetype detection configured");
 
     // Create ftplugin with highlights and LSP (wrapped in pcall for safety)
     let ftplugin_dir = nvim_config.join("after/ftplugin");
@@ -583,7 +698,7 @@ if not lsp_ok then
   vim.notify("Rune LSP error: " .. tostring(lsp_err), vim.log.levels.WARN)
 end
 "##).map_err(|e| format!("Failed to write ftplugin: {}", e))?;
-    println!("  ✓ LSP and highlights configured");
+    verbosity.say("  ✓ LSP and highlights configured");
 
     // Create icon config for nvim-web-devicons (auto-loads from after/plugin/)
     let plugin_dir = nvim_config.join("after/plugin");
@@ -600,13 +715,13 @@ if ok then
   })
 end
 "##).map_err(|e| format!("Failed to write icon config: {}", e))?;
-    println!("  ✓ File icon configured (nvim-web-devicons)");
+    verbosity.say("  ✓ File icon configured (nvim-web-devicons)");
 
     Ok(())
 }
 
-fn setup_helix(data_dir: &PathBuf) -> Result<(), String> {
-    println!("Setting up Helix...");
+fn setup_helix(data_dir: &Path, verbosity: Verbosity) -> Result<(), String> {
+    verbosity.say("Setting up Helix...");
 
     let config_dir = dirs::config_dir()
         .ok_or("Could not find config directory")?
@@ -619,7 +734,7 @@ fn setup_helix(data_dir: &PathBuf) -> Result<(), String> {
     if queries_src.exists() {
         fs::copy(&queries_src, queries_dest.join("highlights.scm"))
             .map_err(|e| format!("Failed to copy queries: {}", e))?;
-        println!("  ✓ Queries installed");
+        verbosity.say("  ✓ Queries installed");
     }
 
     // Create languages.toml entry
@@ -644,18 +759,149 @@ command = "rune-lsp"
     } else {
         fs::write(&languages_path, languages_content)
             .map_err(|e| format!("Failed to write languages.toml: {}", e))?;
-        println!("  ✓ Language config created");
+        verbosity.say("  ✓ Language config created");
+    }
+
+    Ok(())
+}
+
+fn setup_zed(verbosity: Verbosity) -> Result<(), String> {
+    verbosity.say("Setting up Zed...");
+    let home = dirs::home_dir().ok_or("Could not find home directory")?;
+    setup_zed_in(&home, &bin_dir().join("rune-lsp"), verbosity)
+}
+
+/// Merge a `Rune` language entry, the `.rune` file type and the LSP binary into
+/// Zed's settings.json, keeping everything else in it.
+fn setup_zed_in(home: &Path, lsp_path: &Path, verbosity: Verbosity) -> Result<(), String> {
+    let settings_path = zed_settings_path(home);
+    let mut settings = if settings_path.exists() {
+        match read_zed_settings(&settings_path)? {
+            Some(settings) => settings,
+            None => {
+                println!("  ! settings.json is not plain JSON - add the Rune entries manually");
+                return Ok(());
+            }
+        }
+    } else {
+        serde_json::Map::new()
+    };
+
+    let entries = [
+        ("languages", "Rune", serde_json::json!({ "language_servers": ["rune-lsp"] })),
+        ("file_types", "Rune", serde_json::json!(["rune"])),
+        ("lsp", "rune-lsp", serde_json::json!({ "binary": { "path": lsp_path.display().to_string() } })),
+    ];
+    for (section, key, value) in entries {
+        let table = settings
+            .entry(section)
+            .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()))
+            .as_object_mut()
+            .ok_or_else(|| format!("settings.json: \"{}\" is not an object", section))?;
+        table.insert(key.to_string(), value);
     }
 
+    if let Some(parent) = settings_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create Zed config dir: {}", e))?;
+    }
+    write_zed_settings(&settings_path, &settings)?;
+    verbosity.say("  ✓ Language and LSP configured in settings.json");
     Ok(())
 }
 
+fn zed_settings_path(home: &Path) -> PathBuf {
+    home.join(".config/zed/settings.json")
+}
+
+/// `None` when the file isn't plain JSON (Zed also accepts comments)
+fn read_zed_settings(path: &Path) -> Result<Option<serde_json::Map<String, serde_json::Value>>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read settings.json: {}", e))?;
+    if content.trim().is_empty() {
+        return Ok(Some(serde_json::Map::new()));
+    }
+    Ok(serde_json::from_str(&content).ok())
+}
+
+fn write_zed_settings(path: &Path, settings: &serde_json::Map<String, serde_json::Value>) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings.json: {}", e))?;
+    fs::write(path, content + "\n").map_err(|e| format!("Failed to write settings.json: {}", e))
+}
+
+fn setup_emacs(verbosity: Verbosity) -> Result<(), String> {
+    verbosity.say("Setting up Emacs...");
+    let home = dirs::home_dir().ok_or("Could not find home directory")?;
+    setup_emacs_in(&home, &bin_dir().join("rune-lsp"), verbosity)
+}
+
+/// Append a minimal `rune-mode` and its eglot registration to the init file
+fn setup_emacs_in(home: &Path, lsp_path: &Path, verbosity: Verbosity) -> Result<(), String> {
+    let init_path = emacs_init_path(home);
+    let begin = format!(";; {}", RUNE_BEGIN);
+    let end = format!(";; {}", RUNE_END);
+    let block = format!(
+        r#"{begin}
+(define-derived-mode rune-mode prog-mode "Rune"
+  "Major mode for .rune specs."
+  (setq-local comment-start "// "))
+(add-to-list 'auto-mode-alist '("\\.rune\\'" . rune-mode))
+(with-eval-after-load 'eglot
+  (add-to-list 'eglot-server-programs '(rune-mode . ("{lsp}"))))
+(add-hook 'rune-mode-hook #'eglot-ensure)
+{end}
+"#,
+        lsp = lsp_path.display()
+    );
+
+    let content = if init_path.exists() {
+        fs::read_to_string(&init_path).map_err(|e| format!("Failed to read {}: {}", init_path.display(), e))?
+    } else {
+        String::new()
+    };
+    if content.contains(&begin) {
+        verbosity.say("  ✓ rune-mode already configured");
+        return Ok(());
+    }
+
+    if let Some(parent) = init_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create Emacs config dir: {}", e))?;
+    }
+    let new_content = if content.trim().is_empty() { block } else { format!("{}\n\n{}", content.trim_end(), block) };
+    fs::write(&init_path, new_content).map_err(|e| format!("Failed to write {}: {}", init_path.display(), e))?;
+    verbosity.say(format!("  ✓ rune-mode and eglot configured in {}", init_path.display()));
+    Ok(())
+}
+
+/// The init file Emacs would load: an existing one of ~/.emacs.d/init.el,
+/// ~/.config/emacs/init.el or ~/.emacs, else a new ~/.emacs.d/init.el
+fn emacs_init_path(home: &Path) -> PathBuf {
+    let candidates = [home.join(".emacs.d/init.el"), home.join(".config/emacs/init.el"), home.join(".emacs")];
+    candidates
+        .iter()
+        .find(|p| p.exists())
+        .cloned()
+        .unwrap_or_else(|| home.join(".emacs.d/init.el"))
+}
+
+/// `content` without the lines from `begin` to `end`; `None` if not present
+fn strip_marked_block(content: &str, begin: &str, end: &str) -> Option<String> {
+    let start = content.find(begin)?;
+    let stop = content[start..].find(end)? + start + end.len();
+    let before = content[..start].trim_end();
+    let after = content[stop..].trim_start_matches('\n');
+    Some(match (before.is_empty(), after.is_empty()) {
+        (true, _) => after.to_string(),
+        (false, true) => format!("{}\n", before),
+        (false, false) => format!("{}\n\n{}", before, after),
+    })
+}
+
 // Marker comments for config injection
 const RUNE_BEGIN: &str = "# BEGIN RUNE CONFIG";
 const RUNE_END: &str = "# END RUNE CONFIG";
 
-fn setup_yazi_icons() -> Result<(), String> {
-    println!("Setting up yazi icons...");
+fn setup_yazi_icons(verbosity: Verbosity) -> Result<(), String> {
+    verbosity.say("Setting up yazi icons...");
 
     // yazi uses XDG config (~/.config/yazi) on all platforms
     let config_dir = dirs::home_dir()
@@ -671,7 +917,7 @@ fn setup_yazi_icons() -> Result<(), String> {
             .map_err(|e| format!("Failed to read theme.toml: {}", e))?;
 
         if content.contains(r#"name = "rune""#) {
-            println!("  ✓ Rune icon already configured");
+            verbosity.say("  ✓ Rune icon already configured");
         } else if content.contains("prepend_exts") {
             // Add to existing prepend_exts array
             let new_content = content.replace(
@@ -680,7 +926,7 @@ fn setup_yazi_icons() -> Result<(), String> {
             );
             fs::write(&theme_path, new_content)
                 .map_err(|e| format!("Failed to update theme.toml: {}", e))?;
-            println!("  ✓ Added rune icon to existing prepend_exts");
+            verbosity.say("  ✓ Added rune icon to existing prepend_exts");
         } else if content.contains("[icon]") {
             // Add prepend_exts to existing [icon] section
             let new_content = content.replace(
@@ -689,25 +935,25 @@ fn setup_yazi_icons() -> Result<(), String> {
             );
             fs::write(&theme_path, new_content)
                 .map_err(|e| format!("Failed to update theme.toml: {}", e))?;
-            println!("  ✓ Added rune icon to theme.toml");
+            verbosity.say("  ✓ Added rune icon to theme.toml");
         } else {
             // Append new [icon] section
             let new_content = format!("{}\n\n[icon]\nprepend_exts = [\n  {}\n]", content.trim_end(), rune_icon);
             fs::write(&theme_path, new_content)
                 .map_err(|e| format!("Failed to update theme.toml: {}", e))?;
-            println!("  ✓ Added rune icon to theme.toml");
+            verbosity.say("  ✓ Added rune icon to theme.toml");
         }
     } else {
         fs::write(&theme_path, format!("[icon]\nprepend_exts = [\n  {}\n]\n", rune_icon))
             .map_err(|e| format!("Failed to create theme.toml: {}", e))?;
-        println!("  ✓ Created theme.toml with rune icon");
+        verbosity.say("  ✓ Created theme.toml with rune icon");
     }
 
     Ok(())
 }
 
-fn setup_lf_icons() -> Result<(), String> {
-    println!("Setting up lf icons...");
+fn setup_lf_icons(verbosity: Verbosity) -> Result<(), String> {
+    verbosity.say("Setting up lf icons...");
 
     let config_dir = dirs::config_dir()
         .ok_or("Could not find config directory")?
@@ -724,21 +970,21 @@ fn setup_lf_icons() -> Result<(), String> {
             let new_content = format!("{}\n{}\n{}\n{}\n", content.trim_end(), RUNE_BEGIN, rune_line, RUNE_END);
             fs::write(&icons_path, new_content)
                 .map_err(|e| format!("Failed to update icons: {}", e))?;
-            println!("  ✓ Added rune icon");
+            verbosity.say("  ✓ Added rune icon");
         } else {
-            println!("  ✓ Rune icon already configured");
+            verbosity.say("  ✓ Rune icon already configured");
         }
     } else {
         fs::write(&icons_path, format!("{}\n{}\n{}\n", RUNE_BEGIN, rune_line, RUNE_END))
             .map_err(|e| format!("Failed to create icons: {}", e))?;
-        println!("  ✓ Created icons file with rune icon");
+        verbosity.say("  ✓ Created icons file with rune icon");
     }
 
     Ok(())
 }
 
-fn setup_eza_icons() -> Result<(), String> {
-    println!("Setting up eza icons...");
+fn setup_eza_icons(verbosity: Verbosity) -> Result<(), String> {
+    verbosity.say("Setting up eza icons...");
 
     let config_dir = dirs::config_dir()
         .ok_or("Could not find config directory")?
@@ -756,21 +1002,21 @@ fn setup_eza_icons() -> Result<(), String> {
             let new_content = format!("{}{}", content.trim_end(), rune_config);
             fs::write(&theme_path, new_content)
                 .map_err(|e| format!("Failed to update theme.yml: {}", e))?;
-            println!("  ✓ Added rune icon");
+            verbosity.say("  ✓ Added rune icon");
         } else {
-            println!("  ✓ Rune icon already configured");
+            verbosity.say("  ✓ Rune icon already configured");
         }
     } else {
         fs::write(&theme_path, format!("{}\nicons:\n  filenames:\n    \"*.rune\": \"ᚱ\"\n{}\n", RUNE_BEGIN, RUNE_END))
             .map_err(|e| format!("Failed to create theme.yml: {}", e))?;
-        println!("  ✓ Created theme.yml with rune icon");
+        verbosity.say("  ✓ Created theme.yml with rune icon");
     }
 
     Ok(())
 }
 
-fn setup_lsd_icons() -> Result<(), String> {
-    println!("Setting up lsd icons...");
+fn setup_lsd_icons(verbosity: Verbosity) -> Result<(), String> {
+    verbosity.say("Setting up lsd icons...");
 
     let config_dir = dirs::config_dir()
         .ok_or("Could not find config directory")?
@@ -787,15 +1033,102 @@ fn setup_lsd_icons() -> Result<(), String> {
             let new_content = format!("{}{}", content.trim_end(), rune_config);
             fs::write(&icons_path, new_content)
                 .map_err(|e| format!("Failed to update icons.yaml: {}", e))?;
-            println!("  ✓ Added rune icon");
+            verbosity.say("  ✓ Added rune icon");
         } else {
-            println!("  ✓ Rune icon already configured");
+            verbosity.say("  ✓ Rune icon already configured");
         }
     } else {
         fs::write(&icons_path, format!("{}\nextension:\n  rune: ᚱ\n{}\n", RUNE_BEGIN, RUNE_END))
             .map_err(|e| format!("Failed to create icons.yaml: {}", e))?;
-        println!("  ✓ Created icons.yaml with rune icon");
+        verbosity.say("  ✓ Created icons.yaml with rune icon");
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn grammar_version_hash_is_stable() {
+        // Published FNV-1a 64 test vectors
+        assert_eq!(fnv1a(FNV_OFFSET, b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(FNV_OFFSET, b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(FNV_OFFSET, b"foobar"), 0x8594_4171_f739_67e8);
+        // Hashing in pieces matches hashing the whole input
+        assert_eq!(fnv1a(fnv1a(FNV_OFFSET, b"foo"), b"bar"), fnv1a(FNV_OFFSET, b"foobar"));
+
+        assert_eq!(grammar_version().len(), 16);
+        assert_eq!(grammar_version(), format!("{:016x}", fnv1a(fnv1a(FNV_OFFSET, PARSER_C.as_bytes()), SCANNER_C.as_bytes())));
+    }
+
+    #[test]
+    fn grammar_status_compares_the_recorded_version() {
+        let data = tempdir().unwrap();
+        assert_eq!(grammar_status_in(data.path()), GrammarStatus::NotInstalled);
+
+        fs::create_dir_all(data.path().join("parser")).unwrap();
+        fs::write(data.path().join("parser/rune.so"), "").unwrap();
+        assert_eq!(grammar_status_in(data.path()), GrammarStatus::Stale(None));
+
+        fs::write(data.path().join("parser/rune.version"), "0123456789abcdef\n").unwrap();
+        assert_eq!(grammar_status_in(data.path()), GrammarStatus::Stale(Some("0123456789abcdef".to_string())));
+
+        fs::write(data.path().join("parser/rune.version"), grammar_version()).unwrap();
+        assert_eq!(grammar_status_in(data.path()), GrammarStatus::UpToDate);
+    }
+
+    #[test]
+    fn zed_config_is_merged_and_removed() {
+        let home = tempdir().unwrap();
+        let settings_path = home.path().join(".config/zed/settings.json");
+        fs::create_dir_all(settings_path.parent().unwrap()).unwrap();
+        fs::write(&settings_path, r#"{ "theme": "One Dark" }"#).unwrap();
+
+        setup_zed_in(home.path(), Path::new("/bin/rune-lsp"), Verbosity::Quiet).unwrap();
+        let settings: serde_json::Value = serde_json::from_str(&fs::read_to_string(&settings_path).unwrap()).unwrap();
+        assert_eq!(settings["theme"], "One Dark");
+        assert_eq!(settings["languages"]["Rune"]["language_servers"][0], "rune-lsp");
+        assert_eq!(settings["file_types"]["Rune"][0], "rune");
+        assert_eq!(settings["lsp"]["rune-lsp"]["binary"]["path"], "/bin/rune-lsp");
+
+        cleanup_zed_in(home.path(), Verbosity::Quiet).unwrap();
+        let settings: serde_json::Value = serde_json::from_str(&fs::read_to_string(&settings_path).unwrap()).unwrap();
+        assert_eq!(settings, serde_json::json!({ "theme": "One Dark" }));
+
+        // A settings file the installer created is removed entirely
+        fs::remove_file(&settings_path).unwrap();
+        setup_zed_in(home.path(), Path::new("/bin/rune-lsp"), Verbosity::Quiet).unwrap();
+        assert!(settings_path.exists());
+        cleanup_zed_in(home.path(), Verbosity::Quiet).unwrap();
+        assert!(!settings_path.exists());
+    }
+
+    #[test]
+    fn emacs_block_is_appended_once_and_removed() {
+        let home = tempdir().unwrap();
+        let init_path = home.path().join(".emacs");
+        fs::write(&init_path, "(setq inhibit-startup-screen t)\n").unwrap();
+
+        setup_emacs_in(home.path(), Path::new("/bin/rune-lsp"), Verbosity::Quiet).unwrap();
+        setup_emacs_in(home.path(), Path::new("/bin/rune-lsp"), Verbosity::Quiet).unwrap();
+        let content = fs::read_to_string(&init_path).unwrap();
+        assert!(content.starts_with("(setq inhibit-startup-screen t)\n\n;; # BEGIN RUNE CONFIG\n"));
+        assert_eq!(content.matches("(define-derived-mode rune-mode").count(), 1);
+        assert!(content.contains("'(rune-mode . (\"/bin/rune-lsp\"))"));
+        assert!(content.contains("(add-to-list 'auto-mode-alist '(\"\\\\.rune\\\\'\" . rune-mode))"));
+
+        cleanup_emacs_in(home.path(), Verbosity::Quiet).unwrap();
+        assert_eq!(fs::read_to_string(&init_path).unwrap(), "(setq inhibit-startup-screen t)\n");
+
+        // Without an init file one is created, and removed again on cleanup
+        fs::remove_file(&init_path).unwrap();
+        setup_emacs_in(home.path(), Path::new("/bin/rune-lsp"), Verbosity::Quiet).unwrap();
+        let created = home.path().join(".emacs.d/init.el");
+        assert!(created.exists());
+        cleanup_emacs_in(home.path(), Verbosity::Quiet).unwrap();
+        assert!(!created.exists());
+    }
+}
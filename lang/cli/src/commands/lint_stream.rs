@@ -0,0 +1,49 @@
+//! Lint-stream command - validates documents piped through one long-lived
+//! process, for editor plugins that can run a filter but not an LSP client
+
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use rune_parser::config::Config;
+
+use super::{json_diagnostic, validate_content, CommandError, JsonDiagnostic};
+
+/// Separates documents on stdin (form feed)
+pub const DOCUMENT_SEPARATOR: u8 = 0x0c;
+
+/// Validate each form-feed terminated document read from `input` and write
+/// its diagnostics to `output` as a one-line JSON array, flushing after each
+/// so the editor can read the answer before sending the next document. A
+/// final document without a separator is linted at end of input. Returns how
+/// many documents were linted. rune.toml is looked up from the working
+/// directory once, at startup.
+pub fn lint_stream(mut input: impl BufRead, mut output: impl Write) -> Result<usize, CommandError> {
+    let config = Config::discover(Path::new(".")).map_err(CommandError::InvalidConfig)?;
+    let mut linted = 0;
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        let read = input
+            .read_until(DOCUMENT_SEPARATOR, &mut buf)
+            .map_err(|e| CommandError::io("read", "<stdin>", e))?;
+        let terminated = buf.last() == Some(&DOCUMENT_SEPARATOR);
+        if read == 0 || (!terminated && buf.iter().all(u8::is_ascii_whitespace)) {
+            return Ok(linted);
+        }
+        if terminated {
+            buf.pop();
+        }
+
+        let document = std::str::from_utf8(&buf)
+            .map_err(|e| CommandError::io("read", "<stdin>", io::Error::new(io::ErrorKind::InvalidData, e)))?;
+        let report: Vec<JsonDiagnostic> = validate_content(document, &config)
+            .iter()
+            .map(|e| json_diagnostic("<stdin>", e))
+            .collect();
+        let json = serde_json::to_string(&report).map_err(|e| CommandError::serialize("diagnostics", e))?;
+        writeln!(output, "{}", json)
+            .and_then(|()| output.flush())
+            .map_err(|e| CommandError::io("write", "<stdout>", e))?;
+        linted += 1;
+    }
+}
@@ -0,0 +1,93 @@
+//! Migrate command - rewrites legacy syntax in a .rune file to the current form
+
+use std::fs;
+use std::path::Path;
+
+use rune_parser::{parse_document, LineKind};
+
+use super::{is_stdin, read_input, CommandError};
+
+/// Migrate a .rune file in place and return how many lines changed (or, when
+/// checking, would change). With `-` the migrated document goes to stdout.
+pub fn migrate(input_path: &Path, check_only: bool) -> Result<usize, CommandError> {
+    let content = read_input(input_path)?;
+    let (migrated, changed) = migrate_content(&content);
+
+    if check_only {
+        return Ok(changed);
+    }
+    if is_stdin(input_path) {
+        print!("{}", migrated);
+    } else if changed > 0 {
+        fs::write(input_path, &migrated).map_err(|e| CommandError::io("write", input_path, e))?;
+    }
+    Ok(changed)
+}
+
+/// Rewrite `[NEW] class` to `[CTR] class`. Only lines the parser reads as a
+/// constructor are touched, so `[NEW]` in a comment or description stays, and
+/// indentation and inline comments are kept as written.
+pub fn migrate_content(content: &str) -> (String, usize) {
+    let constructors: Vec<usize> = parse_document(content)
+        .iter()
+        .filter(|l| matches!(l.kind, LineKind::New { .. }))
+        .map(|l| l.line_num)
+        .collect();
+
+    let mut out = String::with_capacity(content.len());
+    let mut changed = 0;
+    for (line_num, line) in content.split_inclusive('\n').enumerate() {
+        let indent = line.len() - line.trim_start().len();
+        if constructors.contains(&line_num) && line[indent..].starts_with("[NEW]") {
+            out.push_str(&line[..indent]);
+            out.push_str("[CTR]");
+            out.push_str(&line[indent + "[NEW]".len()..]);
+            changed += 1;
+        } else {
+            out.push_str(line);
+        }
+    }
+    (out, changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const SPEC: &str = "[REQ] file.save(FileDto): IdDto
+    [NEW] storage  // the bucket client
+    storage.put(FileDto): IdDto
+    // [NEW] in a comment is left alone
+";
+
+    #[test]
+    fn rewrites_new_to_ctr() {
+        let (migrated, changed) = migrate_content(SPEC);
+        assert_eq!(changed, 1);
+        assert!(migrated.contains("\n    [CTR] storage  // the bucket client\n"));
+        assert!(migrated.contains("// [NEW] in a comment is left alone\n"));
+        assert_eq!(migrate_content(&migrated).1, 0);
+    }
+
+    #[test]
+    fn check_reports_without_writing() {
+        let temp = tempdir().unwrap();
+        let input = temp.path().join("spec.rune");
+        fs::write(&input, SPEC).unwrap();
+
+        assert_eq!(migrate(&input, true).unwrap(), 1);
+        assert_eq!(fs::read_to_string(&input).unwrap(), SPEC);
+
+        assert_eq!(migrate(&input, false).unwrap(), 1);
+        assert!(fs::read_to_string(&input).unwrap().contains("    [CTR] storage"));
+        assert_eq!(migrate(&input, true).unwrap(), 0);
+    }
+
+    #[test]
+    fn missing_file_is_an_io_error() {
+        let temp = tempdir().unwrap();
+        let err = migrate(&temp.path().join("missing.rune"), true).unwrap_err();
+        assert!(matches!(err, CommandError::Io { action: "read", .. }));
+    }
+}
@@ -1,9 +1,33 @@
 //! CLI commands
 
+mod error;
+mod input;
 mod validate;
 mod format;
 mod install;
+mod doctor;
+mod watch;
+mod graph;
+mod init;
+mod explain;
+mod openapi;
+mod analyze;
+mod migrate;
+mod lint_stream;
+mod verbosity;
 
+pub use error::*;
+pub use input::*;
 pub use validate::*;
 pub use format::*;
 pub use install::*;
+pub use doctor::*;
+pub use watch::*;
+pub use graph::*;
+pub use init::*;
+pub use explain::*;
+pub use openapi::*;
+pub use analyze::*;
+pub use migrate::*;
+pub use lint_stream::*;
+pub use verbosity::*;
@@ -0,0 +1,271 @@
+//! OpenAPI command - emits OpenAPI 3.1 schemas and path stubs for a .rune file
+
+use std::fs;
+use std::path::Path;
+
+use clap::ValueEnum;
+use rune_parser::parse_array_property;
+use rune_parser::validate::normalize_type;
+use serde_json::{json, Map, Value};
+
+use crate::analyzer::{analyze, to_pascal_case, AnalyzedSpec, DtoInfo, PropertyInfo, TypeRef};
+
+use super::{warn_conflicts, CommandError};
+
+/// OpenAPI output syntax
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum OpenApiFormat {
+    Json,
+    Yaml,
+}
+
+/// Write the OpenAPI document for a .rune file to stdout, or to `output`
+pub fn openapi(input_path: &Path, format: OpenApiFormat, output: Option<&Path>) -> Result<(), CommandError> {
+    let content = fs::read_to_string(input_path).map_err(|e| CommandError::io("read", input_path, e))?;
+
+    let title = input_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "rune".to_string());
+    let spec = analyze(&content);
+    warn_conflicts(input_path, &spec);
+    let document = openapi_of(&spec, &title);
+    let rendered = match format {
+        OpenApiFormat::Json => serde_json::to_string_pretty(&document)
+            .map(|s| s + "\n")
+            .map_err(|e| CommandError::serialize("OpenAPI document", e))?,
+        OpenApiFormat::Yaml => serde_norway::to_string(&document)
+            .map_err(|e| CommandError::serialize("OpenAPI document", e))?,
+    };
+
+    match output {
+        Some(path) => fs::write(path, rendered).map_err(|e| CommandError::io("write", path, e)),
+        None => {
+            print!("{}", rendered);
+            Ok(())
+        }
+    }
+}
+
+/// One schema per `[DTO]` under `components.schemas`, and a POST stub per
+/// `[REQ]` at `/<noun>/<verb>` whose bodies reference its input/output DTOs.
+pub fn build_openapi(text: &str, title: &str) -> Value {
+    openapi_of(&analyze(text), title)
+}
+
+/// `build_openapi` for an already analyzed spec
+fn openapi_of(spec: &AnalyzedSpec, title: &str) -> Value {
+
+    let mut schemas = Map::new();
+    for dto in &spec.dtos {
+        schemas.insert(dto.name.clone(), dto_schema(dto, spec));
+    }
+
+    let mut paths = Map::new();
+    for req in &spec.requirements {
+        let mut operation = json!({
+            "operationId": format!("{}{}", req.noun, to_pascal_case(&req.verb)),
+            "responses": {
+                "200": {
+                    "description": "OK",
+                    "content": { "application/json": { "schema": type_schema(&req.output_dto, spec) } }
+                }
+            }
+        });
+        if let Some(input) = &req.input_dto {
+            operation["requestBody"] = json!({
+                "required": true,
+                "content": { "application/json": { "schema": type_schema(input, spec) } }
+            });
+        }
+        paths.insert(format!("/{}/{}", req.noun, req.verb), json!({ "post": operation }));
+    }
+
+    json!({
+        "openapi": "3.1.0",
+        "info": { "title": title, "version": "0.0.0" },
+        "paths": paths,
+        "components": { "schemas": schemas }
+    })
+}
+
+fn dto_schema(dto: &DtoInfo, spec: &AnalyzedSpec) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for prop in &dto.properties {
+        let name = property_name(prop);
+        let schema = property_schema(prop, spec);
+        if !prop.optional {
+            required.push(Value::String(name.clone()));
+        }
+        properties.insert(name, schema);
+    }
+
+    let mut schema = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        schema["required"] = Value::Array(required);
+    }
+    if !dto.description.is_empty() {
+        schema["description"] = Value::String(dto.description.clone());
+    }
+    schema
+}
+
+/// `url(s)` is serialized as `urls`
+fn property_name(prop: &PropertyInfo) -> String {
+    match parse_array_property(&prop.name) {
+        Some((base, suffix)) => format!("{}{}", base, suffix),
+        None => prop.name.clone(),
+    }
+}
+
+fn property_schema(prop: &PropertyInfo, spec: &AnalyzedSpec) -> Value {
+    let element = match &prop.type_ref {
+        TypeRef::Primitive(name) | TypeRef::Dto(name) | TypeRef::Custom(name) => type_schema(name, spec),
+    };
+    if prop.is_array {
+        json!({ "type": "array", "items": element })
+    } else {
+        element
+    }
+}
+
+/// Schema for a type name: a DTO becomes a `$ref`, a `[TYP]` its underlying
+/// primitive (string unions as `enum`), and a property named after a DTO by
+/// convention (`address` -> `AddressDto`) a `$ref` to it.
+fn type_schema(name: &str, spec: &AnalyzedSpec) -> Value {
+    let (element, is_array) = normalize_type(name);
+    if is_array {
+        return json!({ "type": "array", "items": type_schema(element, spec) });
+    }
+    let name = element;
+    if name.ends_with("Dto") {
+        return json!({ "$ref": format!("#/components/schemas/{}", name) });
+    }
+    if let Some(schema) = primitive_schema(name) {
+        return schema;
+    }
+    if let Some(typ) = spec.types.iter().find(|t| t.name == name) {
+        let mut schema = primitive_schema(&typ.underlying_type)
+            .or_else(|| union_schema(&typ.underlying_type))
+            .unwrap_or_else(|| json!({}));
+        if let Some(description) = &typ.description {
+            schema["description"] = Value::String(description.clone());
+        }
+        return schema;
+    }
+    let convention = format!("{}Dto", to_pascal_case(name));
+    if spec.dtos.iter().any(|d| d.name == convention) {
+        return json!({ "$ref": format!("#/components/schemas/{}", convention) });
+    }
+    json!({})
+}
+
+fn primitive_schema(name: &str) -> Option<Value> {
+    match name {
+        "string" => Some(json!({ "type": "string" })),
+        "number" => Some(json!({ "type": "number" })),
+        "boolean" => Some(json!({ "type": "boolean" })),
+        "Uint8Array" => Some(json!({ "type": "string", "format": "binary" })),
+        "void" => Some(json!({ "type": "null" })),
+        _ => None,
+    }
+}
+
+/// `"genie" | "fiveNine"` -> a string enum
+fn union_schema(underlying: &str) -> Option<Value> {
+    let members: Option<Vec<Value>> = underlying
+        .split('|')
+        .map(|m| {
+            let m = m.trim();
+            m.strip_prefix('"')
+                .and_then(|m| m.strip_suffix('"'))
+                .map(|m| Value::String(m.to_string()))
+        })
+        .collect();
+    members.map(|members| json!({ "type": "string", "enum": members }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const SPEC: &str = r#"[REQ] user.create(CreateUserDto): UserDto
+    db:user.save(CreateUserDto): UserDto
+
+[DTO] CreateUserDto: name, provider, tag(s), address?
+    what a client sends
+
+[DTO] UserDto: id, name, address
+    a stored user
+
+// billing and shipping use the same shape
+[DTO] AddressDto: name
+    a postal address
+
+[TYP] id: string
+    the id
+
+[TYP] name: string
+    a name
+
+[TYP] tag: string
+    a label
+
+[TYP] provider: "genie" | "fiveNine"
+    who sent it
+"#;
+
+    #[test]
+    fn maps_dtos_to_schemas() {
+        let doc = build_openapi(SPEC, "users");
+        let create = &doc["components"]["schemas"]["CreateUserDto"];
+        assert_eq!(create["type"], "object");
+        assert_eq!(create["properties"]["name"]["type"], "string");
+        assert_eq!(create["properties"]["tags"]["type"], "array");
+        assert_eq!(create["properties"]["tags"]["items"]["type"], "string");
+        assert_eq!(create["properties"]["provider"]["enum"], json!(["genie", "fiveNine"]));
+        assert_eq!(create["properties"]["address"]["$ref"], "#/components/schemas/AddressDto");
+        // Optional properties are left out of `required`
+        assert_eq!(create["required"], json!(["name", "provider", "tags"]));
+        assert_eq!(
+            doc["components"]["schemas"]["AddressDto"]["description"],
+            "a postal address billing and shipping use the same shape"
+        );
+    }
+
+    #[test]
+    fn maps_reqs_to_path_stubs() {
+        let doc = build_openapi(SPEC, "users");
+        assert_eq!(doc["openapi"], "3.1.0");
+        let op = &doc["paths"]["/user/create"]["post"];
+        assert_eq!(op["operationId"], "userCreate");
+        assert_eq!(
+            op["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/CreateUserDto"
+        );
+        assert_eq!(
+            op["responses"]["200"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/UserDto"
+        );
+    }
+
+    #[test]
+    fn writes_parseable_json_and_yaml() {
+        let temp = tempdir().unwrap();
+        let input = temp.path().join("users.rune");
+        fs::write(&input, SPEC).unwrap();
+
+        let json_out = temp.path().join("openapi.json");
+        openapi(&input, OpenApiFormat::Json, Some(&json_out)).unwrap();
+        let parsed: Value = serde_json::from_str(&fs::read_to_string(&json_out).unwrap()).unwrap();
+        assert_eq!(parsed["info"]["title"], "users");
+        assert!(parsed["components"]["schemas"].get("UserDto").is_some());
+
+        let yaml_out = temp.path().join("openapi.yaml");
+        openapi(&input, OpenApiFormat::Yaml, Some(&yaml_out)).unwrap();
+        let parsed: Value = serde_norway::from_str(&fs::read_to_string(&yaml_out).unwrap()).unwrap();
+        assert!(parsed["components"]["schemas"].get("AddressDto").is_some());
+    }
+}
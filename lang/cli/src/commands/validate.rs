@@ -1,45 +1,252 @@
-//! Validate command - validates a .rune file
+//! Validate command - validates a .rune file or a directory of them
 
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use rune_parser::{parse_document, LineKind};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use rune_parser::codes::DiagnosticCode;
+use rune_parser::config::Config;
+use rune_parser::symbols::SymbolTable;
+use rune_parser::validate::{self, normalize_type, validate_document, ValidateOptions};
+use rune_parser::{source_lines, LineKind, ParsedLine};
+use serde::{Deserialize, Serialize};
+
+use super::{is_stdin, read_input, CommandError};
+
+/// Diagnostic severity, named as in the LSP
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl From<validate::Severity> for Severity {
+    fn from(severity: validate::Severity) -> Self {
+        match severity {
+            validate::Severity::Error => Self::Error,
+            validate::Severity::Warning => Self::Warning,
+        }
+    }
+}
 
 /// Validation error
 #[derive(Debug)]
 pub struct ValidationError {
     pub line: usize,
+    pub column: usize,
+    pub severity: Severity,
+    pub code: DiagnosticCode,
     pub message: String,
 }
 
-/// Validate a .rune file
-pub fn validate(input_path: &Path) -> Result<Vec<ValidationError>, String> {
-    let content = fs::read_to_string(input_path)
-        .map_err(|e| format!("Failed to read {}: {}", input_path.display(), e))?;
+impl ValidationError {
+    /// `line: message` as the text report prints it after the file name;
+    /// warnings are marked so they read apart from errors
+    pub fn describe(&self) -> String {
+        match self.severity {
+            Severity::Error => format!("{}: {}", self.line, self.message),
+            Severity::Warning => format!("{}: warning: {}", self.line, self.message),
+        }
+    }
+}
 
-    let lines = parse_document(&content);
-    let mut errors = Vec::new();
+/// Whether a run with these diagnostics fails: any error does, and with
+/// `--deny-warnings` so does any warning
+pub fn fails_validation(errors: &[ValidationError], deny_warnings: bool) -> bool {
+    errors.iter().any(|e| deny_warnings || e.severity == Severity::Error)
+}
+
+/// One entry of the `--format json` report
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonDiagnostic {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub severity: Severity,
+    pub message: String,
+    pub code: String,
+}
+
+/// Validation errors for one file of a workspace
+#[derive(Debug)]
+pub struct FileReport {
+    pub path: PathBuf,
+    pub errors: Vec<ValidationError>,
+}
+
+/// The `--format json` entry for one error in `file`
+pub fn json_diagnostic(file: &str, e: &ValidationError) -> JsonDiagnostic {
+    JsonDiagnostic {
+        file: file.to_string(),
+        line: e.line,
+        column: e.column,
+        severity: e.severity,
+        message: e.message.clone(),
+        code: e.code.to_string(),
+    }
+}
+
+/// Serialize validation errors as a JSON array of diagnostics
+pub fn json_report(file: &str, errors: &[ValidationError]) -> Result<String, CommandError> {
+    let report: Vec<JsonDiagnostic> = errors.iter().map(|e| json_diagnostic(file, e)).collect();
+    serde_json::to_string_pretty(&report).map_err(|e| CommandError::serialize("diagnostics", e))
+}
 
-    for parsed_line in &lines {
-        // Check for Unknown lines (parse errors)
-        if let LineKind::Unknown(text) = &parsed_line.kind {
-            errors.push(ValidationError {
-                line: parsed_line.line_num + 1,
-                message: format!("Parse error: {}", text),
-            });
+/// Serialize a workspace run as one flat JSON array of diagnostics
+pub fn workspace_json_report(reports: &[FileReport]) -> Result<String, CommandError> {
+    let report: Vec<JsonDiagnostic> = reports
+        .iter()
+        .flat_map(|r| {
+            let file = r.path.display().to_string();
+            r.errors.iter().map(move |e| json_diagnostic(&file, e))
+        })
+        .collect();
+    serde_json::to_string_pretty(&report).map_err(|e| CommandError::serialize("diagnostics", e))
+}
+
+/// Directories a workspace walk always skips
+pub const DEFAULT_EXCLUDES: [&str; 2] = ["node_modules", "target"];
+
+/// Validate every .rune file under `dir` against one shared symbol table, so
+/// a DTO or TYP defined in one file resolves when another file uses it.
+pub fn validate_workspace(dir: &Path) -> Result<Vec<FileReport>, CommandError> {
+    validate_workspace_with(dir, &[])
+}
+
+/// `validate_workspace`, also skipping paths that match any of the `exclude`
+/// globs (see `exclude_set`)
+pub fn validate_workspace_with(dir: &Path, exclude: &[String]) -> Result<Vec<FileReport>, CommandError> {
+    let patterns: Vec<&str> = DEFAULT_EXCLUDES.into_iter().chain(exclude.iter().map(String::as_str)).collect();
+    let exclude = exclude_set(&patterns)?;
+    let mut paths = Vec::new();
+    collect_rune_files(dir, dir, &exclude, &mut paths)?;
+    paths.sort();
+
+    let mut files = Vec::new();
+    let mut symbols = SymbolTable::default();
+    for path in paths {
+        let content = fs::read_to_string(&path)
+            .map_err(|e| CommandError::io("read", &path, e))?;
+        let config = Config::discover(path.parent().unwrap_or(dir)).map_err(CommandError::InvalidConfig)?;
+        let lines = config.parse(&content);
+        symbols.add_lines(&lines);
+        files.push((path, content, lines, config));
+    }
+
+    Ok(files
+        .into_iter()
+        .map(|(path, content, lines, config)| {
+            let mut errors = validate_content_with(&content, &config, &symbols);
+            errors.extend(reference_errors(&lines, &symbols));
+            errors.sort_by_key(|e| e.line);
+            FileReport { path, errors }
+        })
+        .collect())
+}
+
+/// Walk `dir`, skipping excluded entries; an excluded directory is not entered
+fn collect_rune_files(root: &Path, dir: &Path, exclude: &GlobSet, out: &mut Vec<PathBuf>) -> Result<(), CommandError> {
+    let entries = fs::read_dir(dir).map_err(|e| CommandError::io("read", dir, e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| CommandError::io("read", dir, e))?.path();
+        let relative = path.strip_prefix(root).unwrap_or(&path);
+        if exclude.is_match(relative) {
+            continue;
+        }
+        if path.is_dir() {
+            collect_rune_files(root, &path, exclude, out)?;
+        } else if path.extension().is_some_and(|e| e == "rune") {
+            out.push(path);
         }
+    }
+    Ok(())
+}
+
+/// Compile exclude globs, matched against paths relative to the workspace
+/// root. A pattern without a `/` matches any single path component
+/// (`vendor`, `*.gen.rune`); one with a `/` matches from the root
+/// (`specs/old/*`). `*` stays within a component, `**` crosses them.
+pub fn exclude_set(patterns: &[&str]) -> Result<GlobSet, CommandError> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern = pattern.trim_end_matches('/');
+        let anchored = if pattern.contains('/') { pattern.to_string() } else { format!("**/{}", pattern) };
+        let glob = GlobBuilder::new(&anchored)
+            .literal_separator(true)
+            .build()
+            .map_err(CommandError::InvalidGlob)?;
+        builder.add(glob);
+    }
+    builder.build().map_err(CommandError::InvalidGlob)
+}
 
-        // Check 80 column limit
-        let line_text = content.lines().nth(parsed_line.line_num).unwrap_or("");
-        if line_text.len() > 80 {
-            errors.push(ValidationError {
-                line: parsed_line.line_num + 1,
-                message: format!("Line exceeds 80 columns ({} chars)", line_text.len()),
-            });
+/// Signatures naming a DTO that no file in the workspace defines. DTO
+/// properties are already checked by the shared rules against `symbols`.
+fn reference_errors(lines: &[ParsedLine], symbols: &SymbolTable) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    for parsed_line in lines {
+        let referenced: Vec<&String> = match &parsed_line.kind {
+            LineKind::Req { input, output, .. } | LineKind::Ent { input, output, .. } => vec![input, output],
+            LineKind::Step { params, output, .. }
+            | LineKind::BoundaryStep { params, output, .. }
+            | LineKind::Ply { params, output, .. } => params.iter().chain(std::iter::once(output)).collect(),
+            _ => continue,
+        };
+        for name in referenced {
+            let (base, _) = normalize_type(name);
+            if base.ends_with("Dto") && !symbols.dtos.contains(base) {
+                errors.push(ValidationError {
+                    line: parsed_line.line_num + 1,
+                    column: 1,
+                    severity: Severity::Error,
+                    code: DiagnosticCode::UndefinedReference,
+                    message: format!("DTO '{}' is not defined", base),
+                });
+            }
         }
     }
+    errors
+}
 
-    Ok(errors)
+/// Validate a .rune file (`-` reads stdin), honouring the nearest rune.toml
+pub fn validate(input_path: &Path) -> Result<Vec<ValidationError>, CommandError> {
+    let content = read_input(input_path)?;
+    let dir = match input_path.parent() {
+        Some(p) if !is_stdin(input_path) && !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    let config = Config::discover(dir).map_err(CommandError::InvalidConfig)?;
+    Ok(validate_content(&content, &config))
+}
+
+/// Validate .rune source text with the rules the LSP reports
+pub fn validate_content(content: &str, config: &Config) -> Vec<ValidationError> {
+    validate_content_with(content, config, &SymbolTable::default())
+}
+
+/// `validate_content`, resolving references against DTOs/TYPs defined in
+/// other files too
+pub fn validate_content_with(content: &str, config: &Config, siblings: &SymbolTable) -> Vec<ValidationError> {
+    let source: Vec<&str> = source_lines(content).collect();
+    let mut errors: Vec<ValidationError> = validate_document(content, ValidateOptions { config, siblings })
+        .into_iter()
+        .map(|d| {
+            let column = match d.span {
+                Some(span) => span.start,
+                None => source.get(d.line).map_or(0, |l| l.len() - l.trim_start().len()),
+            };
+            ValidationError {
+                line: d.line + 1,
+                column: column + 1,
+                severity: d.severity.into(),
+                code: d.code,
+                message: d.message,
+            }
+        })
+        .collect();
+    errors.sort_by_key(|e| e.line);
+    errors
 }
 
 #[cfg(test)]
@@ -53,10 +260,14 @@ mod tests {
         let input_path = temp.path().join("example.rune");
 
         fs::write(&input_path, r#"[REQ] test.run(InputDto): OutputDto
-    id::create(name): id
+    id::create(name): OutputDto
 
 [DTO] InputDto: name
     input
+[DTO] OutputDto: name
+    output
+[TYP] name: string
+    a name
 "#).unwrap();
 
         let result = validate(&input_path);
@@ -64,6 +275,61 @@ mod tests {
         assert!(result.unwrap().is_empty());
     }
 
+    #[test]
+    fn malformed_rune_toml_is_an_error() {
+        let temp = tempdir().unwrap();
+        let spec = temp.path().join("spec.rune");
+        fs::write(&spec, "[TYP] id: string\n    an id\n").unwrap();
+        fs::write(temp.path().join("rune.toml"), "warn_noun_typos = ture\n").unwrap();
+
+        let err = validate(&spec).unwrap_err();
+        assert!(matches!(err, CommandError::InvalidConfig(_)));
+        assert!(err.to_string().starts_with("Invalid rune config "), "{}", err);
+        assert!(matches!(validate_workspace(temp.path()), Err(CommandError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn missing_file_is_an_io_error() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("missing.rune");
+        let err = validate(&path).unwrap_err();
+        assert!(matches!(&err, CommandError::Io { action: "read", path: p, .. } if *p == path));
+        assert!(err.to_string().starts_with(&format!("Failed to read {}: ", path.display())));
+    }
+
+    #[test]
+    fn reports_the_same_rules_as_the_lsp() {
+        let errors = validate_content(
+            "[REQ] user.get(IdDto): UserDto\n    db:user.load(IdDto): IdDto\n\n[DTO] IdDto: id\n[TYP] id: string\n    an id\n",
+            &Config::default(),
+        );
+        let found: Vec<(usize, usize, DiagnosticCode)> = errors.iter().map(|e| (e.line, e.column, e.code)).collect();
+        assert_eq!(
+            found,
+            vec![
+                (1, 24, DiagnosticCode::ReturnMismatch),
+                (2, 26, DiagnosticCode::ReturnMismatch),
+                (4, 1, DiagnosticCode::DtoMissingDescription),
+            ]
+        );
+    }
+
+    #[test]
+    fn honours_max_line_length_from_rune_toml() {
+        let temp = tempdir().unwrap();
+        let input_path = temp.path().join("example.rune");
+        fs::write(temp.path().join("rune.toml"), "max_line_length = 100\n").unwrap();
+        fs::write(&input_path, format!("// {}\n", "x".repeat(87))).unwrap();
+
+        assert!(validate(&input_path).unwrap().is_empty());
+
+        fs::write(temp.path().join("rune.toml"), "max_line_length = 60\n").unwrap();
+        let errors = validate(&input_path).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].column, 61);
+        assert!(errors[0].message.contains("60 columns"));
+    }
+
     #[test]
     fn detects_long_lines() {
         let temp = tempdir().unwrap();
@@ -93,4 +359,107 @@ mod tests {
         assert!(!errors.is_empty());
         assert!(errors[0].message.contains("Parse error"));
     }
+
+    #[test]
+    fn json_report_round_trips() {
+        let temp = tempdir().unwrap();
+        let input_path = temp.path().join("example.rune");
+
+        fs::write(&input_path, "invalid line without tag").unwrap();
+
+        let errors = validate(&input_path).unwrap();
+        let json = json_report("example.rune", &errors).unwrap();
+        let parsed: Vec<JsonDiagnostic> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].file, "example.rune");
+        assert_eq!(parsed[0].line, 1);
+        assert_eq!(parsed[0].column, 1);
+        assert_eq!(parsed[0].severity, Severity::Error);
+        assert_eq!(parsed[0].code, "PAR001");
+        assert!(json.contains("\"severity\": \"error\""));
+    }
+
+    #[test]
+    fn workspace_resolves_definitions_across_files() {
+        let temp = tempdir().unwrap();
+        fs::write(temp.path().join("flows.rune"), "[REQ] user.get(IdDto): UserDto\n    db:user.load(IdDto): UserDto\n").unwrap();
+        fs::create_dir(temp.path().join("shared")).unwrap();
+        fs::write(
+            temp.path().join("shared/dtos.rune"),
+            "[DTO] IdDto: id\n    an id\n\n[DTO] UserDto: id\n    a user\n\n[TYP] id: string\n    the id\n",
+        )
+        .unwrap();
+
+        let reports = validate_workspace(temp.path()).unwrap();
+        assert_eq!(reports.len(), 2);
+        assert!(reports.iter().all(|r| r.errors.is_empty()), "{:?}", reports);
+
+        // Without the shared definitions the flow's DTOs are unknown
+        fs::remove_file(temp.path().join("shared/dtos.rune")).unwrap();
+        let reports = validate_workspace(temp.path()).unwrap();
+        let messages: Vec<&str> = reports[0].errors.iter().map(|e| e.message.as_str()).collect();
+        assert!(messages.contains(&"DTO 'UserDto' is not defined"), "{:?}", messages);
+        assert_eq!(reports[0].errors[0].line, 1);
+    }
+
+    #[test]
+    fn workspace_skips_excluded_paths() {
+        let temp = tempdir().unwrap();
+        let spec = "[REQ] user.get(IdDto): IdDto\n    db:user.load(IdDto): IdDto\n\n[DTO] IdDto: id\n    an id\n\n[TYP] id: string\n    the id\n";
+        fs::write(temp.path().join("flows.rune"), spec).unwrap();
+        for dir in ["vendor/lib", "node_modules/pkg", "specs/old"] {
+            fs::create_dir_all(temp.path().join(dir)).unwrap();
+            fs::write(temp.path().join(dir).join("broken.rune"), "[REQ] oops\n").unwrap();
+        }
+
+        let names = |reports: Vec<FileReport>| -> Vec<String> {
+            reports.iter().map(|r| r.path.strip_prefix(temp.path()).unwrap().display().to_string()).collect()
+        };
+        let all = names(validate_workspace(temp.path()).unwrap());
+        assert_eq!(all, ["flows.rune", "specs/old/broken.rune", "vendor/lib/broken.rune"]);
+
+        let exclude = ["vendor".to_string(), "specs/**/*.rune".to_string()];
+        let reports = validate_workspace_with(temp.path(), &exclude).unwrap();
+        assert!(reports.iter().all(|r| r.errors.is_empty()), "{:?}", reports);
+        assert_eq!(names(reports), ["flows.rune"]);
+    }
+
+    #[test]
+    fn exclude_globs() {
+        // Like the walk: a path is skipped when it or a directory above it matches
+        let excluded = |path: &str, pattern: &str| {
+            let set = exclude_set(&[pattern]).unwrap();
+            Path::new(path).ancestors().any(|p| set.is_match(p))
+        };
+        assert!(excluded("a/vendor/x.rune", "vendor"));
+        assert!(excluded("a/x.gen.rune", "*.gen.rune"));
+        assert!(!excluded("a/x.rune", "*.gen.rune"));
+        assert!(excluded("specs/old/x.rune", "specs/*"));
+        assert!(excluded("specs/old/x.rune", "specs/**"));
+        assert!(!excluded("specs/old/x.rune", "specs/*.rune"));
+        assert!(excluded("specs/old/x.rune", "**/x.rune"));
+        assert!(excluded("x.rune", "?.rune"));
+        assert!(!excluded("a/bx.rune", "**/x.rune"));
+        assert!(!excluded("docs/bold", "docs/**/old"));
+        assert!(excluded("docs/a/old", "docs/**/old"));
+        assert!(excluded("docs/old", "docs/**/old"));
+        assert!(exclude_set(&["specs/[old"]).is_err());
+    }
+
+    #[test]
+    fn both_array_spellings_report_undefined_dtos() {
+        for spelling in ["UserDto[]", "Array<UserDto>"] {
+            let temp = tempdir().unwrap();
+            fs::write(
+                temp.path().join("flows.rune"),
+                format!("[REQ] user.sync(IdDto): void\n    db:user.list(IdDto): {}\n\n[DTO] IdDto: id\n    an id\n\n[TYP] id: string\n    the id\n", spelling),
+            )
+            .unwrap();
+
+            let reports = validate_workspace(temp.path()).unwrap();
+            let messages: Vec<&str> = reports[0].errors.iter().map(|e| e.message.as_str()).collect();
+            assert_eq!(messages, ["DTO 'UserDto' is not defined"], "{}", spelling);
+        }
+    }
 }
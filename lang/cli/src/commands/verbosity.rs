@@ -0,0 +1,67 @@
+//! How chatty commands are, from the global `--quiet` / `--verbose` flags
+
+use std::fmt::Display;
+use std::process::Command;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Errors only
+    Quiet,
+    #[default]
+    Normal,
+    /// Also list every file written and command run
+    Verbose,
+}
+
+impl Verbosity {
+    pub fn from_flags(quiet: bool, verbose: bool) -> Self {
+        match (quiet, verbose) {
+            (true, _) => Self::Quiet,
+            (false, true) => Self::Verbose,
+            (false, false) => Self::Normal,
+        }
+    }
+
+    pub fn is_quiet(self) -> bool {
+        self == Self::Quiet
+    }
+
+    pub fn is_verbose(self) -> bool {
+        self == Self::Verbose
+    }
+
+    /// Progress or success output, dropped when quiet
+    pub fn say(self, message: impl Display) {
+        if !self.is_quiet() {
+            println!("{}", message);
+        }
+    }
+
+    /// Detail only shown when verbose
+    pub fn detail(self, message: impl Display) {
+        if self.is_verbose() {
+            println!("{}", message);
+        }
+    }
+
+    /// Echo a command about to run, when verbose
+    pub fn echo(self, command: &Command) {
+        if self.is_verbose() {
+            let args: Vec<String> = command.get_args().map(|a| a.to_string_lossy().into_owned()).collect();
+            println!("  $ {} {}", command.get_program().to_string_lossy(), args.join(" "));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quiet_wins_over_verbose() {
+        assert_eq!(Verbosity::from_flags(true, true), Verbosity::Quiet);
+        assert_eq!(Verbosity::from_flags(false, true), Verbosity::Verbose);
+        assert_eq!(Verbosity::from_flags(false, false), Verbosity::Normal);
+        assert!(Verbosity::Quiet < Verbosity::Normal);
+    }
+}
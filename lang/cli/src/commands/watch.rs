@@ -0,0 +1,121 @@
+//! Watch command - re-validates a .rune file whenever it changes
+
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+
+use super::{validate, CommandError};
+
+/// Events arriving within this window of each other trigger a single run
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Watch a .rune file and re-validate it on every change until interrupted
+pub fn watch(input_path: &Path) -> Result<(), CommandError> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |res| {
+        let _ = tx.send(res);
+    })
+    .map_err(|source| CommandError::Watch { path: input_path.to_path_buf(), source })?;
+
+    // Watch the directory rather than the file: editors that save atomically
+    // replace the file via rename, which would orphan a file-level watch.
+    let dir = match input_path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .map_err(|source| CommandError::Watch { path: dir.to_path_buf(), source })?;
+
+    run(input_path);
+
+    loop {
+        match rx.recv() {
+            Ok(Ok(event)) if touches_input(&event, input_path) => {
+                drain_burst(&rx);
+                run(input_path);
+            }
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => eprintln!("Watch error: {}", e),
+            Err(_) => return Ok(()),
+        }
+    }
+}
+
+/// Clear the screen and print a fresh set of diagnostics
+fn run(input_path: &Path) {
+    print!("\x1B[2J\x1B[H");
+    println!("[{}] {}", clock(SystemTime::now()), input_path.display());
+
+    match validate(input_path) {
+        Ok(errors) if errors.is_empty() => println!("No errors found"),
+        Ok(errors) => {
+            for error in &errors {
+                println!("{}:{}", input_path.display(), error.describe());
+            }
+        }
+        Err(e) => eprintln!("Error: {}", e),
+    }
+}
+
+/// Swallow the rest of a burst of events (editors often emit several per save)
+fn drain_burst<T>(rx: &Receiver<T>) {
+    while rx.recv_timeout(DEBOUNCE).is_ok() {}
+}
+
+/// Whether an event changes the watched file (by name, since the watch is on its directory)
+fn touches_input(event: &Event, input_path: &Path) -> bool {
+    if matches!(event.kind, EventKind::Access(_)) {
+        return false;
+    }
+    let Some(name) = input_path.file_name() else {
+        return false;
+    };
+    event.paths.iter().any(|p| p.file_name() == Some(name))
+}
+
+/// Format a time as HH:MM:SS (UTC)
+fn clock(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    format!("{:02}:{:02}:{:02}", (secs / 3600) % 24, (secs / 60) % 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, ModifyKind};
+    use std::path::PathBuf;
+
+    #[test]
+    fn matches_events_for_the_watched_file() {
+        let event = Event::new(EventKind::Modify(ModifyKind::Any))
+            .add_path(PathBuf::from("/work/spec.rune"));
+        assert!(touches_input(&event, Path::new("spec.rune")));
+        assert!(!touches_input(&event, Path::new("other.rune")));
+    }
+
+    #[test]
+    fn ignores_access_events() {
+        let event = Event::new(EventKind::Access(AccessKind::Any))
+            .add_path(PathBuf::from("/work/spec.rune"));
+        assert!(!touches_input(&event, Path::new("spec.rune")));
+    }
+
+    #[test]
+    fn debounces_a_burst() {
+        let (tx, rx) = mpsc::channel();
+        for i in 0..5 {
+            tx.send(i).unwrap();
+        }
+        drain_burst(&rx);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn formats_clock() {
+        let t = UNIX_EPOCH + Duration::from_secs(3600 * 25 + 61);
+        assert_eq!(clock(t), "01:01:01");
+    }
+}
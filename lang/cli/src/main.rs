@@ -1,11 +1,12 @@
 //! Rune CLI - Generate scaffolded code from .rune specs
 
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
-use clap::{CommandFactory, Parser, Subcommand, ValueHint};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum, ValueHint};
 use clap_complete::{generate, Shell};
+use similar::TextDiff;
 
 use rune_cli::commands;
 
@@ -14,30 +15,151 @@ use rune_cli::commands;
 #[command(about = "Rune syntax tooling (parse/validate/format/render) — internal helper invoked by the `rune` CLI; codegen lives in the rune engine")]
 #[command(version)]
 struct Cli {
+    /// Only print errors and requested output
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Also print every file written and command run
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
 #[derive(Subcommand)]
 enum Commands {
+    /// Create a new project with a starter .rune spec
+    Init {
+        /// Project name (also the directory and spec file name)
+        name: String,
+    },
+
     /// Validate a .rune file
     Validate {
+        /// Input .rune file, a directory to validate as one workspace, or `-` for stdin
+        #[arg(value_hint = ValueHint::AnyPath)]
+        input: PathBuf,
+
+        /// Output format for diagnostics
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Exit non-zero on warnings too, not just errors
+        #[arg(long)]
+        deny_warnings: bool,
+
+        /// When validating a directory, skip paths matching this glob
+        /// (repeatable; node_modules and target are always skipped)
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+    },
+
+    /// Re-validate a .rune file every time it changes (Ctrl-C to stop)
+    Watch {
+        /// Input .rune file
+        #[arg(value_hint = ValueHint::FilePath)]
+        input: PathBuf,
+    },
+
+    /// Lint form-feed separated documents from stdin, one JSON diagnostics
+    /// line per document (for editors without LSP support)
+    LintStream,
+
+    /// Print the requirement/noun call graph (Mermaid or Graphviz DOT)
+    Graph {
+        /// Input .rune file
+        #[arg(value_hint = ValueHint::FilePath)]
+        input: PathBuf,
+
+        /// Graph syntax
+        #[arg(long, value_enum, default_value_t = commands::GraphFormat::Mermaid)]
+        format: commands::GraphFormat,
+
+        /// Write the graph to a file instead of stdout
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
+        output: Option<PathBuf>,
+    },
+
+    /// Emit OpenAPI 3.1 schemas for the DTOs and a path stub per requirement
+    Openapi {
         /// Input .rune file
         #[arg(value_hint = ValueHint::FilePath)]
         input: PathBuf,
+
+        /// Document syntax
+        #[arg(long, value_enum, default_value_t = commands::OpenApiFormat::Json)]
+        format: commands::OpenApiFormat,
+
+        /// Write the document to a file instead of stdout
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
+        output: Option<PathBuf>,
+    },
+
+    /// Dump what the analyzer extracts from a .rune file (for generator authors)
+    #[command(hide = true)]
+    Analyze {
+        /// Input .rune file (`-` reads stdin)
+        #[arg(value_hint = ValueHint::FilePath)]
+        input: PathBuf,
+
+        /// Print JSON instead of debug text
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Explain a diagnostic code (e.g. REQ001)
+    Explain {
+        /// Diagnostic code, as shown by `validate --format json` or the editor
+        code: String,
     },
 
     /// Format a .rune file
     Format {
-        /// Input .rune file
+        /// Input .rune file (`-` reads stdin and writes the result to stdout)
         #[arg(value_hint = ValueHint::FilePath)]
         input: PathBuf,
 
         /// Check if file is formatted without modifying it
         #[arg(long)]
         check: bool,
+
+        /// Print a unified diff of the changes instead of writing them
+        #[arg(long, conflicts_with = "check")]
+        diff: bool,
+
+        /// Also fix `.` vs `::` separators where scope makes the intent clear
+        #[arg(long)]
+        normalize_static: bool,
+    },
+
+    /// Rewrite legacy syntax (`[NEW]` -> `[CTR]`) in a .rune file
+    Migrate {
+        /// Input .rune file (`-` reads stdin and writes the result to stdout)
+        #[arg(value_hint = ValueHint::FilePath)]
+        input: PathBuf,
+
+        /// Report whether the file needs migrating without modifying it
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Print the version and the embedded grammar version
+    Version {
+        /// Also check whether the installed parser matches the embedded grammar
+        #[arg(long)]
+        check: bool,
     },
 
+    /// Check the install: cc, the LSP, the built parser and shell completions
+    Doctor,
+
     /// Install Rune (LSP, parser, editor integration)
     Install {
         /// Skip interactive prompts (defaults to neovim, zsh, yazi)
@@ -62,19 +184,82 @@ enum Commands {
 
 fn main() -> ExitCode {
     let cli = Cli::parse();
+    let verbosity = commands::Verbosity::from_flags(cli.quiet, cli.verbose);
 
     match cli.command {
-        Commands::Validate { input } => {
+        Commands::Init { name } => {
+            match commands::init(Path::new("."), &name, verbosity) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+
+        Commands::Validate { input, format, deny_warnings, exclude } if input.is_dir() => {
+            match commands::validate_workspace_with(&input, &exclude) {
+                Ok(reports) => {
+                    let failed = reports.iter().any(|r| commands::fails_validation(&r.errors, deny_warnings));
+                    if matches!(format, OutputFormat::Json) {
+                        match commands::workspace_json_report(&reports) {
+                            Ok(json) => println!("{}", json),
+                            Err(e) => {
+                                eprintln!("Error: {}", e);
+                                return ExitCode::FAILURE;
+                            }
+                        }
+                    } else if reports.iter().all(|r| r.errors.is_empty()) {
+                        verbosity.say("No errors found");
+                    } else {
+                        for report in &reports {
+                            for error in &report.errors {
+                                println!("{}:{}", report.path.display(), error.describe());
+                            }
+                        }
+                    }
+                    if failed {
+                        ExitCode::FAILURE
+                    } else {
+                        ExitCode::SUCCESS
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+
+        Commands::Validate { input, format, deny_warnings, .. } => {
             match commands::validate(&input) {
+                Ok(errors) if matches!(format, OutputFormat::Json) => {
+                    match commands::json_report(&commands::display_name(&input), &errors) {
+                        Ok(json) => {
+                            println!("{}", json);
+                            if commands::fails_validation(&errors, deny_warnings) {
+                                ExitCode::FAILURE
+                            } else {
+                                ExitCode::SUCCESS
+                            }
+                        }
+                        Err(e) => {
+                            eprintln!("Error: {}", e);
+                            ExitCode::FAILURE
+                        }
+                    }
+                }
                 Ok(errors) => {
                     if errors.is_empty() {
-                        println!("No errors found");
-                        ExitCode::SUCCESS
-                    } else {
-                        for error in &errors {
-                            println!("{}:{}: {}", input.display(), error.line, error.message);
-                        }
+                        verbosity.say("No errors found");
+                    }
+                    for error in &errors {
+                        println!("{}:{}", commands::display_name(&input), error.describe());
+                    }
+                    if commands::fails_validation(&errors, deny_warnings) {
                         ExitCode::FAILURE
+                    } else {
+                        ExitCode::SUCCESS
                     }
                 }
                 Err(e) => {
@@ -84,19 +269,129 @@ fn main() -> ExitCode {
             }
         }
 
-        Commands::Format { input, check } => {
-            match commands::format(&input, check) {
+        Commands::Watch { input } => {
+            match commands::watch(&input) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+
+        Commands::LintStream => {
+            match commands::lint_stream(io::stdin().lock(), io::stdout().lock()) {
+                Ok(_) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+
+        Commands::Graph { input, format, output } => {
+            match commands::graph(&input, format, output.as_deref()) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+
+        Commands::Openapi { input, format, output } => {
+            match commands::openapi(&input, format, output.as_deref()) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+
+        Commands::Analyze { input, json } => {
+            match commands::dump_analysis(&input, json) {
+                Ok(text) => {
+                    println!("{}", text);
+                    ExitCode::SUCCESS
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+
+        Commands::Explain { code } => {
+            match commands::explain(&code) {
+                Ok(text) => {
+                    println!("{}", text);
+                    ExitCode::SUCCESS
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+
+        Commands::Format { input, diff: true, normalize_static, .. } => {
+            match commands::formatted(&input, normalize_static) {
+                Ok((original, formatted)) => {
+                    let diff = unified_diff(&input, &original, &formatted);
+                    print!("{}", diff);
+                    if diff.is_empty() { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+
+        Commands::Format { input, check, normalize_static, .. } => {
+            match commands::format(&input, check, normalize_static) {
                 Ok(is_formatted) => {
                     if check {
                         if is_formatted {
-                            println!("File is properly formatted");
+                            verbosity.say("File is properly formatted");
                             ExitCode::SUCCESS
                         } else {
                             println!("File needs formatting");
                             ExitCode::FAILURE
                         }
                     } else {
-                        println!("Formatted {}", input.display());
+                        if !commands::is_stdin(&input) {
+                            verbosity.say(format!("Formatted {}", input.display()));
+                        }
+                        ExitCode::SUCCESS
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+
+        Commands::Migrate { input, check } => {
+            match commands::migrate(&input, check) {
+                Ok(0) => {
+                    if check {
+                        verbosity.say("File is up to date");
+                    } else if !commands::is_stdin(&input) {
+                        verbosity.say(format!("Nothing to migrate in {}", input.display()));
+                    }
+                    ExitCode::SUCCESS
+                }
+                Ok(changed) => {
+                    if check {
+                        println!("File needs migration ({} line(s))", changed);
+                        ExitCode::FAILURE
+                    } else {
+                        if !commands::is_stdin(&input) {
+                            verbosity.say(format!("Migrated {} line(s) in {}", changed, input.display()));
+                        }
                         ExitCode::SUCCESS
                     }
                 }
@@ -108,7 +403,7 @@ fn main() -> ExitCode {
         }
 
         Commands::Install { yes } => {
-            match commands::install(yes) {
+            match commands::install(yes, verbosity) {
                 Ok(()) => ExitCode::SUCCESS,
                 Err(e) => {
                     eprintln!("Error: {}", e);
@@ -119,7 +414,7 @@ fn main() -> ExitCode {
 
         Commands::Uninstall { editor } => {
             let editor = editor.and_then(|e| commands::Editor::from_str(&e));
-            match commands::uninstall(editor) {
+            match commands::uninstall(editor, verbosity) {
                 Ok(()) => ExitCode::SUCCESS,
                 Err(e) => {
                     eprintln!("Error: {}", e);
@@ -128,9 +423,78 @@ fn main() -> ExitCode {
             }
         }
 
+        Commands::Version { check } => {
+            println!("rune-syntax {} (grammar {})", env!("CARGO_PKG_VERSION"), commands::grammar_version());
+            if !check {
+                return ExitCode::SUCCESS;
+            }
+            match commands::grammar_status() {
+                commands::GrammarStatus::UpToDate => {
+                    println!("Installed parser is up to date");
+                    ExitCode::SUCCESS
+                }
+                commands::GrammarStatus::Stale(recorded) => {
+                    let recorded = recorded.unwrap_or_else(|| "unknown".to_string());
+                    println!("Installed parser is grammar {}, reinstall recommended (rune-syntax install)", recorded);
+                    ExitCode::FAILURE
+                }
+                commands::GrammarStatus::NotInstalled => {
+                    println!("No parser installed (rune-syntax install)");
+                    ExitCode::FAILURE
+                }
+            }
+        }
+
+        Commands::Doctor => {
+            let checks = commands::doctor();
+            for check in &checks {
+                println!("{}", check.describe());
+            }
+            if checks.iter().all(|c| c.ok) {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+
         Commands::Completions { shell } => {
             generate(shell, &mut Cli::command(), "rune-syntax", &mut io::stdout());
             ExitCode::SUCCESS
         }
     }
 }
+
+/// Unified diff from `original` to `formatted`; empty when they match
+fn unified_diff(path: &Path, original: &str, formatted: &str) -> String {
+    if original == formatted {
+        return String::new();
+    }
+    let name = path.display().to_string();
+    TextDiff::from_lines(original, formatted)
+        .unified_diff()
+        .header(&format!("a/{}", name), &format!("b/{}", name))
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_shows_formatting_hunks() {
+        let unformatted = "[REQ] user.get(IdDto): UserDto\n  db:user.load(IdDto): UserDto\n";
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("user.rune");
+        std::fs::write(&input, unformatted).unwrap();
+        let (original, formatted) = commands::formatted(&input, false).unwrap();
+        assert_eq!(original, unformatted);
+
+        let path = Path::new("user.rune");
+        let diff = unified_diff(path, unformatted, &formatted);
+        assert!(diff.starts_with("--- a/user.rune\n+++ b/user.rune\n@@"));
+        assert!(diff.contains("\n-  db:user.load(IdDto): UserDto\n"));
+        assert!(diff.contains("\n+    db:user.load(IdDto): UserDto\n"));
+
+        assert!(unified_diff(path, &formatted, &formatted).is_empty());
+    }
+}
@@ -0,0 +1,20 @@
+//! Methods dropped by dedup are reported on stderr, not silently lost
+
+use std::process::Command;
+
+#[test]
+fn graph_warns_about_conflicting_methods() {
+    let temp = tempfile::tempdir().unwrap();
+    let spec = temp.path().join("spec.rune");
+    std::fs::write(&spec, "[REQ] user.get(IdDto): UserDto\n    db:user.load(IdDto): UserDto\n\n[REQ] user.find(IdDto): UserDto\n    db:user.load(IdDto): ProfileDto\n").unwrap();
+
+    let out = Command::new(env!("CARGO_BIN_EXE_rune-syntax")).args(["graph", spec.to_str().unwrap()]).output().unwrap();
+    assert!(out.status.success());
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(
+        stderr.ends_with(": warning: user: 'load' is declared as (IdDto): UserDto and as (IdDto): ProfileDto; keeping the first\n"),
+        "got: {}",
+        stderr
+    );
+    assert!(String::from_utf8(out.stdout).unwrap().starts_with("flowchart LR\n"));
+}
@@ -0,0 +1,28 @@
+//! Warnings print but only fail `validate` under `--deny-warnings`
+
+use std::process::{Command, Output};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rune-syntax")).args(args).output().unwrap()
+}
+
+#[test]
+fn warnings_fail_only_when_denied() {
+    let temp = tempfile::tempdir().unwrap();
+    let spec = temp.path().join("spec.rune");
+    std::fs::write(&spec, "[REQ] test.run(InDto): OutDto\n    id::create(name): OutDto\n\n[DTO] InDto: name,\n    input\n[DTO] OutDto: name\n    output\n[TYP] name: string\n    a name\n").unwrap();
+    let spec = spec.to_str().unwrap();
+
+    let out = run(&["validate", spec]);
+    assert!(out.status.success());
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(stdout.ends_with(":4: warning: Trailing comma in the property list of 'InDto'\n"), "got: {}", stdout);
+
+    let out = run(&["validate", spec, "--deny-warnings"]);
+    assert!(!out.status.success());
+
+    let out = run(&["validate", temp.path().to_str().unwrap(), "--deny-warnings"]);
+    assert!(!out.status.success());
+    let out = run(&["validate", temp.path().to_str().unwrap()]);
+    assert!(out.status.success());
+}
@@ -0,0 +1,27 @@
+//! `doctor` against empty RUNE_DATA/RUNE_BIN/HOME reports every component missing
+
+use std::process::Command;
+
+#[test]
+fn reports_missing_components() {
+    let data = tempfile::tempdir().unwrap();
+    let bin = tempfile::tempdir().unwrap();
+    let home = tempfile::tempdir().unwrap();
+
+    let out = Command::new(env!("CARGO_BIN_EXE_rune-syntax"))
+        .arg("doctor")
+        .env("RUNE_DATA", data.path())
+        .env("RUNE_BIN", bin.path())
+        .env("HOME", home.path())
+        .env("PATH", "")
+        .output()
+        .unwrap();
+    assert!(!out.status.success());
+
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(stdout.contains("✗ C compiler (cc)\n"), "got: {}", stdout);
+    assert!(stdout.contains(&format!("✗ rune-lsp in {}\n    run `rune-syntax install`", bin.path().display())), "got: {}", stdout);
+    assert!(stdout.contains(&format!("✗ parser {}", data.path().join("parser/rune.so").display())), "got: {}", stdout);
+    assert!(stdout.contains("✗ shell completions\n"), "got: {}", stdout);
+    assert!(!stdout.contains('✓'), "got: {}", stdout);
+}
@@ -0,0 +1,38 @@
+//! `lint-stream` answers each form-feed separated document with a JSON line
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+
+#[test]
+fn answers_each_document_as_it_arrives() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rune-syntax"))
+        .arg("lint-stream")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut read_report = || {
+        let mut line = String::new();
+        stdout.read_line(&mut line).unwrap();
+        serde_json::from_str::<Vec<serde_json::Value>>(&line).unwrap()
+    };
+
+    // The first answer arrives while stdin is still open
+    stdin.write_all(b"invalid line without tag\n\x0c").unwrap();
+    stdin.flush().unwrap();
+    let first = read_report();
+    assert_eq!(first.len(), 1);
+    assert_eq!(first[0]["code"], "PAR001");
+    assert_eq!(first[0]["file"], "<stdin>");
+
+    stdin.write_all(b"[TYP] id: string\n    an id\n").unwrap();
+    drop(stdin);
+    assert!(read_report().is_empty());
+
+    let mut rest = String::new();
+    std::io::Read::read_to_string(&mut stdout, &mut rest).unwrap();
+    assert!(rest.is_empty(), "got: {rest}");
+    assert!(child.wait().unwrap().success());
+}
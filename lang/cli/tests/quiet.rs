@@ -0,0 +1,33 @@
+//! `--quiet` drops success chatter but keeps errors and requested output
+
+use std::process::{Command, Output};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rune-syntax")).args(args).output().unwrap()
+}
+
+#[test]
+fn quiet_success_prints_nothing() {
+    let temp = tempfile::tempdir().unwrap();
+    let spec = temp.path().join("spec.rune");
+    std::fs::write(&spec, "   [REQ] test.run(InDto): OutDto\nid::create(name): OutDto\n\n[DTO] InDto: name\n    input\n[DTO] OutDto: name\n    output\n[TYP] name: string\n    a name\n").unwrap();
+    let spec = spec.to_str().unwrap();
+
+    let out = run(&["--quiet", "format", spec]);
+    assert!(out.status.success());
+    assert!(out.stdout.is_empty(), "got: {}", String::from_utf8_lossy(&out.stdout));
+
+    let out = run(&["validate", spec, "-q"]);
+    assert!(out.status.success());
+    assert!(out.stdout.is_empty());
+
+    let out = run(&["validate", spec]);
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "No errors found\n");
+}
+
+#[test]
+fn quiet_still_reports_errors() {
+    let out = run(&["--quiet", "validate", "does-not-exist.rune"]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8(out.stderr).unwrap().starts_with("Error: Failed to read"));
+}
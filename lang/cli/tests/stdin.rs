@@ -0,0 +1,42 @@
+//! `-` as the input path pipes the document through stdin
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn run(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rune-syntax"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn validate_reads_stdin() {
+    let spec = "[REQ] test.run(InputDto): OutputDto\n    id::create(name): OutputDto\n\n\
+[DTO] InputDto: name\n    input\n[DTO] OutputDto: name\n    output\n[TYP] name: string\n    a name\n";
+    let ok = run(&["validate", "-"], spec);
+    assert!(ok.status.success());
+
+    let bad = run(&["validate", "-"], "invalid line without tag\n");
+    assert!(!bad.status.success());
+    let stdout = String::from_utf8(bad.stdout).unwrap();
+    assert!(stdout.starts_with("<stdin>:1: Parse error"), "got: {stdout}");
+}
+
+#[test]
+fn format_writes_stdout() {
+    let out = run(&["format", "-"], "   [REQ] test.run(In): Out\nid::create(name): id\n");
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "[REQ] test.run(In): Out\n    id::create(name): id\n");
+}
+
+#[test]
+fn format_check_reads_stdin() {
+    assert!(run(&["format", "--check", "-"], "[REQ] test.run(In): Out\n").status.success());
+    assert!(!run(&["format", "--check", "-"], "   [REQ] test.run(In): Out\n").status.success());
+}
@@ -1,17 +1,44 @@
 use ropey::Rope;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer, LspService, Server};
 
-use rune_parser::{parse_document, LineKind};
+use rune_parser::casing::to_pascal_case;
+use rune_parser::config::Config;
+use rune_parser::symbols::SymbolTable;
+use rune_parser::validate::{self, method_signatures, noun_in_scope, scope_at, union_members, validate_parsed, Severity, ValidateOptions};
+use rune_parser::{comment_start, parse_array_property, parse_document, split_optional, LineKind, ParsedLine, Span};
 
 #[derive(Debug)]
 struct Backend {
     client: Client,
-    documents: Arc<RwLock<std::collections::HashMap<Url, Rope>>>,
+    documents: Arc<RwLock<std::collections::HashMap<Url, OpenDocument>>>,
+    /// Documents parsed so far, so tests can check handlers share the cache
+    #[cfg(test)]
+    parses: std::sync::atomic::AtomicUsize,
+}
+
+/// An open document with its text, config and parse, rebuilt once per change
+/// and shared by every request until the next one
+#[derive(Debug)]
+struct OpenDocument {
+    rope: Rope,
+    text: String,
+    config: Config,
+    /// Why rune.toml couldn't be used; `config` is then the defaults
+    config_error: Option<String>,
+    parsed: Vec<ParsedLine>,
+}
+
+impl OpenDocument {
+    fn new(rope: Rope, config: Config) -> Self {
+        let text = rope.to_string();
+        let parsed = config.parse(&text);
+        Self { rope, text, config, config_error: None, parsed }
+    }
 }
 
 impl Backend {
@@ -19,9 +46,28 @@ impl Backend {
         Self {
             client,
             documents: Arc::new(RwLock::new(std::collections::HashMap::new())),
+            #[cfg(test)]
+            parses: Default::default(),
+        }
+    }
+
+    /// Parse `rope` for every handler until the next change. rune.toml is
+    /// re-read each time so edits to it apply without a restart.
+    fn load(&self, uri: &Url, rope: Rope) -> OpenDocument {
+        #[cfg(test)]
+        self.parses.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        match config_for(uri) {
+            Ok(config) => OpenDocument::new(rope, config),
+            Err(e) => OpenDocument { config_error: Some(e), ..OpenDocument::new(rope, Config::default()) },
         }
     }
 
+    /// Run `f` on an open document and its cached parse; `None` when the
+    /// document isn't open
+    async fn with_parsed<T>(&self, uri: &Url, f: impl FnOnce(&OpenDocument) -> T) -> Option<T> {
+        self.documents.read().await.get(uri).map(f)
+    }
+
     // Diagnostics mirror what `rune sync`/`manifest` (the TS parser) actually
     // enforces: structure + the documented shape rules. They deliberately do NOT
     // invent scope/usage rules — the generator performs none, and the valid
@@ -29,460 +75,171 @@ impl Backend {
     // nouns that are never "produced"). Keeping the LSP in lock-step with the
     // generator is what makes it trustworthy.
     async fn validate(&self, uri: &Url) {
-        let docs = self.documents.read().await;
-        let Some(rope) = docs.get(uri) else { return };
-        let text = rope.to_string();
-        drop(docs);
-
-        let diagnostics = Self::compute_diagnostics(&text);
+        // Sibling specs are re-read on every run so edits to them apply
+        // without a restart.
+        let path = uri.to_file_path().ok();
+        let dir = path.as_deref().and_then(|p| p.parent());
+        let siblings = match (path.as_deref(), dir) {
+            (Some(path), Some(dir)) => self.sibling_symbols(path, dir).await,
+            _ => SymbolTable::default(),
+        };
+        let Some(diagnostics) = self.with_parsed(uri, |doc| Self::document_diagnostics(doc, &siblings)).await else {
+            return;
+        };
 
         self.client
-            .publish_diagnostics(uri.clone(), diagnostics, None)
+            .publish_diagnostics(uri.clone(), dedup_diagnostics(diagnostics), None)
             .await;
     }
 
-    /// Pure diagnostic computation, split out of the publish-to-client path so
-    /// the corpus-parity tests can drive validation directly. Mirrors what
-    /// `rune sync`/`manifest` enforces.
-    fn compute_diagnostics(text: &str) -> Vec<Diagnostic> {
-        let lines = parse_document(text);
-        let mut diagnostics = Vec::new();
-
-        // 80 column limit.
-        for (line_num, line) in text.lines().enumerate() {
-            if line.len() > 80 {
-                diagnostics.push(Diagnostic {
-                    range: Range {
-                        start: Position { line: line_num as u32, character: 80 },
-                        end: Position { line: line_num as u32, character: line.len() as u32 },
-                    },
-                    severity: Some(DiagnosticSeverity::ERROR),
-                    message: format!("Line exceeds 80 columns ({} chars)", line.len()),
-                    ..Default::default()
-                });
+    /// Definitions from the other .rune files next to `path`. Open documents
+    /// use their cached parse so unsaved edits count.
+    async fn sibling_symbols(&self, path: &std::path::Path, dir: &std::path::Path) -> SymbolTable {
+        let mut table = SymbolTable::default();
+        let Ok(mut entries) = tokio::fs::read_dir(dir).await else { return table };
+        let mut siblings = Vec::new();
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let sibling = entry.path();
+            if sibling != path && sibling.extension().is_some_and(|e| e == "rune") {
+                siblings.push(sibling);
             }
         }
 
-        // Definitions collected in the first pass (shape checks only — no usage).
-        let mut seen_reqs: HashSet<String> = HashSet::new();
-        let mut defined_dtos: HashSet<String> = HashSet::new();
-        let mut defined_dtos_lines: HashMap<String, usize> = HashMap::new();
-        let mut defined_types: HashMap<String, String> = HashMap::new();
-        let mut defined_types_lines: HashMap<String, usize> = HashMap::new();
-        let mut defined_nouns_lines: HashMap<String, usize> = HashMap::new();
-        let mut dto_has_desc: HashSet<String> = HashSet::new();
-        let mut dto_properties: HashMap<String, Vec<(usize, String)>> = HashMap::new();
-        let mut last_dto_name: Option<String> = None;
-        let mut first_pass_dto: Option<String> = None;
-
-        // First pass: collect DTO/TYP/NON definitions, DTO properties, descriptions.
-        for parsed_line in &lines {
-            let line_num = parsed_line.line_num;
-            match &parsed_line.kind {
-                LineKind::DtoDef { name, properties } => {
-                    if let Some(&first) = defined_dtos_lines.get(name) {
-                        diagnostics.push(diag_err(line_num, format!(
-                            "Duplicate DTO definition '{}' (first defined on line {})",
-                            name, first + 1)));
-                    } else {
-                        defined_dtos.insert(name.clone());
-                        defined_dtos_lines.insert(name.clone(), line_num);
-                    }
-                    for prop in properties {
-                        let base = prop.trim_end_matches('?');
-                        let pname = match base.find('(') {
-                            Some(p) => base[..p].to_string(),
-                            None => base.to_string(),
-                        };
-                        dto_properties.entry(name.clone()).or_default().push((line_num, pname));
-                    }
-                    first_pass_dto = Some(name.clone());
-                    last_dto_name = Some(name.clone());
-                }
-                LineKind::DtoProperty { name, .. } => {
-                    if let Some(d) = &first_pass_dto {
-                        dto_properties.entry(d.clone()).or_default().push((line_num, name.clone()));
-                    }
-                }
-                LineKind::DtoArrayProperty { property_name, .. } => {
-                    if let Some(d) = &first_pass_dto {
-                        dto_properties.entry(d.clone()).or_default().push((line_num, property_name.clone()));
-                    }
-                }
-                LineKind::DtoDesc { .. } => {
-                    if let Some(d) = &last_dto_name {
-                        dto_has_desc.insert(d.clone());
-                    }
-                }
-                LineKind::Empty => {
-                    first_pass_dto = None;
-                }
-                LineKind::TypDef { name, type_name, .. } => {
-                    if let Some(&first) = defined_types_lines.get(name) {
-                        diagnostics.push(diag_err(line_num, format!(
-                            "Duplicate type definition '{}' (first defined on line {})",
-                            name, first + 1)));
-                    } else {
-                        defined_types.insert(name.clone(), type_name.clone());
-                        defined_types_lines.insert(name.clone(), line_num);
-                    }
-                }
-                LineKind::NonDef { name } => {
-                    if let Some(&first) = defined_nouns_lines.get(name) {
-                        diagnostics.push(diag_err(line_num, format!(
-                            "Duplicate noun definition '{}' (first defined on line {})",
-                            name, first + 1)));
-                    } else {
-                        defined_nouns_lines.insert(name.clone(), line_num);
-                    }
+        // Closed siblings are read from disk after the lock is released
+        let mut closed = Vec::new();
+        {
+            let docs = self.documents.read().await;
+            for sibling in siblings {
+                match Url::from_file_path(&sibling).ok().and_then(|u| docs.get(&u)) {
+                    Some(doc) => table.add_lines(&doc.parsed),
+                    None => closed.push(sibling),
                 }
-                _ => {}
             }
         }
-
-        // Every property used in a [DTO] must resolve to a declared type — a
-        // [TYP], a nested [DTO] (direct name or the <Name>Dto convention). Mirrors
-        // the TS parser's check so the LSP flags the same missing-TYP errors.
-        for (dto_name, props) in &dto_properties {
-            for (prop_line, pname) in props {
-                let resolved = defined_types.contains_key(pname)
-                    || defined_dtos.contains(pname)
-                    || defined_dtos.contains(&format!("{}Dto", to_pascal(pname)));
-                if !resolved {
-                    diagnostics.push(diag_err(*prop_line, format!(
-                        "[DTO] {}: property \"{}\" has no [TYP] or [DTO] — declare \"[TYP] {}: <type>\"",
-                        dto_name, pname, pname)));
-                }
+        for sibling in closed {
+            if let Ok(text) = tokio::fs::read_to_string(&sibling).await {
+                table.add_lines(&parse_document(&text));
             }
         }
+        table
+    }
 
-        // Second-pass state.
-        let mut method_signatures: HashMap<String, (usize, Vec<String>, String)> = HashMap::new();
-        let mut poly_stack: Vec<usize> = Vec::new(); // indents of open [PLY] scopes
-        let mut in_req = false;
-        let mut last_step_indent: Option<usize> = None;
-        let mut current_req_output: Option<String> = None;
-        let mut last_step_output: Option<String> = None;
-        let mut last_step_line: Option<usize> = None;
-        let mut last_was_req = false;
-        let mut consecutive_empty: usize = 0;
-
-        // Second pass: structure + shape validation.
-        for parsed_line in &lines {
-            let line_num = parsed_line.line_num;
-
-            // Close [PLY] scopes whose body has ended (indentation dropped to/below
-            // the [PLY] line). Faults and tags handle their own scope, so only
-            // step-like lines participate here.
-            if let Some(li) = step_like_indent(&parsed_line.kind) {
-                while let Some(&p) = poly_stack.last() {
-                    if li <= p {
-                        poly_stack.pop();
-                    } else {
-                        break;
-                    }
-                }
-            }
-            let depth = poly_stack.len();
-            let step_expected = if depth == 0 { 4 } else { poly_stack.last().unwrap() + 4 };
-
-            match &parsed_line.kind {
-                LineKind::Mod { .. } => {
-                    in_req = false;
-                    poly_stack.clear();
-                    last_was_req = false;
-                    consecutive_empty = 0;
-                }
-
-                LineKind::Ent { input, output, indent, .. } => {
-                    if *indent != 0 {
-                        diagnostics.push(diag_err(line_num, "[ENT] must start at column 0".to_string()));
-                    }
-                    if !input.is_empty() && !input.ends_with("Dto") && !input.starts_with('{') {
-                        diagnostics.push(diag_err(line_num, format!("[ENT] input must be a DTO, got '{}'", input)));
-                    }
-                    if !output.ends_with("Dto") {
-                        diagnostics.push(diag_err(line_num, format!("[ENT] output must be a DTO, got '{}'", output)));
-                    }
-                    in_req = false;
-                    poly_stack.clear();
-                    last_was_req = false;
-                    consecutive_empty = 0;
-                }
-
-                LineKind::Req { noun, verb, input, output, indent, modifier, .. } => {
-                    // The previous REQ's last step must have returned its output DTO.
-                    if let (Some(ro), Some(so), Some(sl)) = (&current_req_output, &last_step_output, last_step_line) {
-                        if ro != so {
-                            diagnostics.push(diag_err(sl, format!("Last step must return '{}' (REQ output), got '{}'", ro, so)));
-                        }
-                    }
-                    if let Some(m) = modifier {
-                        // Parity with the TS parser: the core modifier keeps its
-                        // specific message; any other modifier gets the generic one.
-                        if m == "core" {
-                            diagnostics.push(diag_err(line_num, "[REQ:core] is invalid — coordinators are module-level".to_string()));
-                        } else {
-                            diagnostics.push(diag_err(line_num, "[REQ] does not take a modifier".to_string()));
-                        }
-                    }
-                    if *indent != 0 {
-                        diagnostics.push(diag_err(line_num, "[REQ] must start at column 0".to_string()));
-                    }
-                    let key = format!("{}.{}", noun, verb);
-                    if seen_reqs.contains(&key) {
-                        diagnostics.push(diag_err(line_num, format!("Duplicate REQ: {}", key)));
-                    }
-                    seen_reqs.insert(key);
-                    if !input.is_empty() && !input.ends_with("Dto") && !input.starts_with('{') {
-                        diagnostics.push(diag_err(line_num, format!("REQ input must be a DTO, got '{}'", input)));
-                    }
-                    if !output.ends_with("Dto") {
-                        diagnostics.push(diag_err(line_num, format!("REQ output must be a DTO, got '{}'", output)));
-                    }
-                    if last_was_req && consecutive_empty < 2 {
-                        diagnostics.push(diag_warn(line_num, "Expected double blank line between requirements".to_string()));
-                    }
-                    in_req = true;
-                    poly_stack.clear();
-                    current_req_output = Some(output.clone());
-                    last_step_output = None;
-                    last_step_line = None;
-                    last_step_indent = None;
-                    last_was_req = true;
-                    consecutive_empty = 0;
-                }
-
-                LineKind::Step { noun, verb, indent, params, output, is_static } => {
-                    if !in_req {
-                        diagnostics.push(diag_err(line_num, "Step outside [REQ]".to_string()));
-                        continue;
-                    }
-                    if *indent != step_expected {
-                        diagnostics.push(diag_err(line_num, format!("Step should be indented {} spaces, got {}", step_expected, indent)));
-                    }
-                    check_sig(&mut diagnostics, &mut method_signatures, line_num, noun, verb, *is_static, params, output);
-                    if output.is_empty() {
-                        diagnostics.push(diag_err(line_num, "Step missing return type".to_string()));
-                    }
-                    last_step_output = Some(output.clone());
-                    last_step_line = Some(line_num);
-                    last_step_indent = Some(*indent);
-                    last_was_req = false;
-                    consecutive_empty = 0;
-                }
-
-                LineKind::BoundaryStep { prefix, noun, verb, indent, params, output, is_static } => {
-                    if !in_req {
-                        diagnostics.push(diag_err(line_num, "Boundary step outside [REQ]".to_string()));
-                        continue;
-                    }
-                    if *indent != step_expected {
-                        diagnostics.push(diag_err(line_num, format!("Boundary step should be indented {} spaces, got {}", step_expected, indent)));
-                    }
-                    check_sig(&mut diagnostics, &mut method_signatures, line_num, noun, verb, *is_static, params, output);
-                    let valid = ["db:", "fs:", "mq:", "ex:", "os:", "lg:"];
-                    if !valid.contains(&prefix.as_str()) {
-                        diagnostics.push(diag_err(line_num, format!("Invalid boundary prefix: {}", prefix)));
-                    }
-                    for param in params {
-                        if !is_dto_or_primitive(param, &defined_types) {
-                            diagnostics.push(diag_err(line_num, format!("{} boundary parameter must be a DTO or primitive, got '{}'", prefix, param)));
-                        }
-                    }
-                    if !is_dto_or_primitive(output, &defined_types) {
-                        diagnostics.push(diag_err(line_num, format!("{} boundary must return a DTO or primitive, got '{}'", prefix, output)));
-                    }
-                    last_step_output = Some(output.clone());
-                    last_step_line = Some(line_num);
-                    last_step_indent = Some(*indent);
-                    last_was_req = false;
-                    consecutive_empty = 0;
-                }
-
-                LineKind::Fault { indent, .. } => {
-                    if last_step_indent.is_none() {
-                        diagnostics.push(diag_err(line_num, "Orphan fault: not under a step".to_string()));
-                    } else {
-                        let expected = last_step_indent.unwrap() + 2;
-                        if *indent != expected {
-                            diagnostics.push(diag_err(line_num, format!("Fault should be indented {} spaces (2 more than step), got {}", expected, indent)));
-                        }
-                    }
-                    last_was_req = false;
-                    consecutive_empty = 0;
-                }
-
-                LineKind::Ply { noun, verb, params, output, indent, is_static } => {
-                    if !in_req {
-                        diagnostics.push(diag_err(line_num, "[PLY] outside [REQ]".to_string()));
-                        continue;
-                    }
-                    if *indent != step_expected {
-                        diagnostics.push(diag_err(line_num, format!("[PLY] should be indented {} spaces, got {}", step_expected, indent)));
-                    }
-                    check_sig(&mut diagnostics, &mut method_signatures, line_num, noun, verb, *is_static, params, output);
-                    poly_stack.push(*indent);
-                    last_step_output = Some(output.clone());
-                    last_step_line = Some(line_num);
-                    last_step_indent = Some(*indent);
-                    last_was_req = false;
-                    consecutive_empty = 0;
-                }
-
-                LineKind::Cse { name, indent } => {
-                    if poly_stack.is_empty() {
-                        diagnostics.push(diag_err(line_num, format!("[CSE] {} must be inside a [PLY] block", name)));
-                    } else {
-                        let expected = poly_stack.last().unwrap() + 4;
-                        if *indent != expected {
-                            diagnostics.push(diag_err(line_num, format!("[CSE] should be indented {} spaces, got {}", expected, indent)));
-                        }
-                    }
-                    last_step_indent = None;
-                    last_was_req = false;
-                    consecutive_empty = 0;
-                }
-
-                LineKind::DtoDef { name, .. } => {
-                    if !name.ends_with("Dto") {
-                        diagnostics.push(diag_err(line_num, format!("DTO name '{}' must end in 'Dto'", name)));
-                    }
-                    in_req = false;
-                    poly_stack.clear();
-                    last_step_indent = None;
-                    last_was_req = false;
-                    consecutive_empty = 0;
-                }
-
-                LineKind::TypDef { name, type_name, modifier } => {
-                    if !is_valid_primitive_type(type_name) {
-                        if type_name.ends_with("Dto") {
-                            diagnostics.push(diag_err(line_num, format!("Type '{}' cannot reference DTO '{}' - types must be primitives", name, type_name)));
-                        } else if defined_types.contains_key(type_name) {
-                            diagnostics.push(diag_err(line_num, format!("Type '{}' cannot reference type '{}' - types must be primitives", name, type_name)));
-                        }
-                    }
-                    if let Some(m) = modifier {
-                        for msg in validate_typ_modifiers(m, name, type_name) {
-                            diagnostics.push(diag_err(line_num, msg));
-                        }
-                    }
-                    in_req = false;
-                    poly_stack.clear();
-                    last_was_req = false;
-                    consecutive_empty = 0;
-                }
-
-                LineKind::NonDef { .. } => {
-                    in_req = false;
-                    poly_stack.clear();
-                    last_was_req = false;
-                    consecutive_empty = 0;
-                }
-
-                LineKind::Ret { value, indent } => {
-                    if !in_req {
-                        diagnostics.push(diag_err(line_num, "[RET] outside [REQ]".to_string()));
-                        continue;
-                    }
-                    if *indent != step_expected {
-                        diagnostics.push(diag_err(line_num, format!("[RET] should be indented {} spaces, got {}", step_expected, indent)));
-                    }
-                    last_step_output = Some(value.clone());
-                    last_step_line = Some(line_num);
-                    last_step_indent = Some(*indent);
-                    last_was_req = false;
-                    consecutive_empty = 0;
-                }
-
-                LineKind::New { indent, .. } => {
-                    if !in_req {
-                        diagnostics.push(diag_err(line_num, "[NEW] outside [REQ]".to_string()));
-                        continue;
-                    }
-                    if *indent != step_expected {
-                        diagnostics.push(diag_err(line_num, format!("[NEW] should be indented {} spaces, got {}", step_expected, indent)));
-                    }
-                    last_step_indent = Some(*indent);
-                    last_was_req = false;
-                    consecutive_empty = 0;
-                }
-
-                LineKind::MultilineContinuation { expected_indent, actual_indent } => {
-                    if expected_indent != actual_indent {
-                        diagnostics.push(diag_err(line_num, format!(
-                            "Inconsistent indentation: expected {} spaces, got {}",
-                            expected_indent, actual_indent)));
-                    }
-                    consecutive_empty = 0;
-                }
+    /// The shared rules from `rune_parser::validate`, as LSP diagnostics.
+    /// `siblings` holds DTOs/TYPs defined in the other .rune files of the
+    /// document's directory; references to them resolve as if local.
+    fn document_diagnostics(doc: &OpenDocument, siblings: &SymbolTable) -> Vec<Diagnostic> {
+        let opts = ValidateOptions { config: &doc.config, siblings };
+        let config_error = doc.config_error.as_ref().map(|e| Diagnostic {
+            range: Range::default(),
+            severity: Some(DiagnosticSeverity::ERROR),
+            message: format!("{} (using the default rules)", e),
+            ..Default::default()
+        });
+        config_error
+            .into_iter()
+            .chain(validate_parsed(&doc.text, &doc.parsed, opts).into_iter().map(|d| to_lsp(&doc.rope, d)))
+            .collect()
+    }
 
-                LineKind::Unknown(text) => {
-                    let msg = if text.contains('.') && !text.contains('(') {
-                        "Missing parameters: expected 'noun.verb(args): type'".to_string()
-                    } else if text.contains('(') && !text.contains(':') {
-                        "Missing return type after ':'".to_string()
-                    } else if text.starts_with('[') {
-                        text.clone()
-                    } else {
-                        format!("Unexpected '{}' - expected a tag, step, fault, or definition", text)
-                    };
-                    diagnostics.push(diag_err(line_num, msg));
-                    consecutive_empty = 0;
-                }
+    /// Split out of the publish-to-client path so the corpus-parity tests can
+    /// drive validation directly.
+    #[cfg(test)]
+    fn compute_diagnostics_with(text: &str, config: &Config, siblings: &SymbolTable) -> Vec<Diagnostic> {
+        Self::document_diagnostics(&OpenDocument::new(Rope::from_str(text), config.clone()), siblings)
+    }
 
-                LineKind::Empty => {
-                    consecutive_empty += 1;
-                }
+    /// `compute_diagnostics_with` under the default config
+    #[cfg(test)]
+    fn compute_diagnostics(text: &str) -> Vec<Diagnostic> {
+        Self::compute_diagnostics_with(text, &Config::default(), &SymbolTable::default())
+    }
 
-                // Definitions handled in the first pass; descriptions / refs are
-                // prose with no second-pass checks.
-                LineKind::DtoDesc { .. }
-                | LineKind::TypDesc { .. }
-                | LineKind::NonDesc { .. }
-                | LineKind::DtoProperty { .. }
-                | LineKind::DtoArrayProperty { .. }
-                | LineKind::DtoRef(_) => {
-                    consecutive_empty = 0;
-                }
+}
 
-                LineKind::Comment { .. } => {}
-            }
+/// Hover text for a `[TYP]`, following alias chains (`[TYP] endpoint: url`,
+/// `[TYP] url: string`) down to the type they finally resolve to.
+fn typ_hover(word: &str, typ_defs: &HashMap<String, (String, Option<String>)>) -> Option<String> {
+    let (_, desc) = typ_defs.get(word)?;
+    let mut visited: HashSet<&str> = HashSet::from([word]);
+    let mut chain: Vec<&str> = Vec::new();
+    let mut current = word;
+    let mut cyclic = false;
+    while let Some((type_name, _)) = typ_defs.get(current) {
+        if !visited.insert(type_name) {
+            cyclic = true;
+            break;
         }
+        chain.push(type_name);
+        current = type_name;
+    }
 
-        // Final REQ's last step must return its output DTO.
-        if let (Some(ro), Some(so), Some(sl)) = (&current_req_output, &last_step_output, last_step_line) {
-            if ro != so {
-                diagnostics.push(diag_err(sl, format!("Last step must return '{}' (REQ output), got '{}'", ro, so)));
-            }
-        }
+    let resolved = if cyclic {
+        "(cyclic)".to_string()
+    } else {
+        chain.iter().map(|t| format!("`{}`", t)).collect::<Vec<_>>().join(" → ")
+    };
+    Some(match desc {
+        Some(d) => format!("**{}**: {}\n\n{}", word, resolved, d),
+        None => format!("**{}**: {}", word, resolved),
+    })
+}
 
-        // Duplicate DTO properties within the same DTO.
-        for (dto_name, props) in &dto_properties {
-            let mut seen: HashMap<&String, usize> = HashMap::new();
-            for (line_num, prop_name) in props {
-                if let Some(&first) = seen.get(prop_name) {
-                    diagnostics.push(diag_err(*line_num, format!(
-                        "Duplicate property '{}' in {} (first defined on line {})",
-                        prop_name, dto_name, first + 1)));
-                } else {
-                    seen.insert(prop_name, *line_num);
-                }
+/// Inlay hints after step outputs that are [TYP] aliases, naming the type
+/// the alias finally resolves to (`: endpoint` gets ` (string)`). Only lines
+/// in `lines` get hints; cyclic aliases get none.
+fn output_type_hints(parsed: &[ParsedLine], lines: std::ops::RangeInclusive<u32>) -> Vec<InlayHint> {
+    let typ_defs: HashMap<&str, &str> = parsed
+        .iter()
+        .filter_map(|l| match &l.kind {
+            LineKind::TypDef { name, type_name, .. } => Some((name.as_str(), type_name.as_str())),
+            _ => None,
+        })
+        .collect();
+    let resolve = |name: &str| {
+        let mut visited: HashSet<&str> = HashSet::from([name]);
+        let mut current = *typ_defs.get(name)?;
+        while let Some(next) = typ_defs.get(current) {
+            if !visited.insert(current) {
+                return None;
             }
+            current = next;
         }
-
-        // Every DTO needs a description.
-        for (dto_name, line_num) in &defined_dtos_lines {
-            if !dto_has_desc.contains(dto_name) {
-                diagnostics.push(diag_err(*line_num, format!(
-                    "DTO '{}' is missing a description (add a 4-space indented description on the next line)",
-                    dto_name)));
-            }
+        Some(current)
+    };
+
+    let mut hints = Vec::new();
+    for parsed_line in parsed {
+        let (LineKind::Step { output, .. } | LineKind::BoundaryStep { output, .. } | LineKind::Ply { output, .. }) =
+            &parsed_line.kind
+        else {
+            continue;
+        };
+        let (Some(span), Some(resolved)) = (parsed_line.spans.output, resolve(output)) else { continue };
+        if resolved == output || !lines.contains(&(parsed_line.line_num as u32)) {
+            continue;
         }
-
-        diagnostics
+        hints.push(InlayHint {
+            position: Position { line: parsed_line.line_num as u32, character: span.end as u32 },
+            label: InlayHintLabel::String(format!("({})", resolved)),
+            kind: Some(InlayHintKind::TYPE),
+            text_edits: None,
+            tooltip: None,
+            padding_left: Some(true),
+            padding_right: None,
+            data: None,
+        });
     }
+    hints
+}
+
+/// Drop diagnostics repeating an earlier one's line, severity and message;
+/// overlapping passes can report the same problem twice.
+fn dedup_diagnostics(diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+    let mut seen = BTreeSet::new();
+    diagnostics
+        .into_iter()
+        .filter(|d| seen.insert((d.range.start.line, d.severity, d.message.clone())))
+        .collect()
 }
 
 fn line_range(line: usize) -> Range {
@@ -498,217 +255,194 @@ fn line_range(line: usize) -> Range {
     }
 }
 
-fn to_pascal(s: &str) -> String {
-    s.split(|c| c == '-' || c == '_')
-        .filter(|w| !w.is_empty())
-        .map(|w| {
-            let mut ch = w.chars();
-            match ch.next() {
-                Some(f) => f.to_uppercase().collect::<String>() + ch.as_str(),
-                None => String::new(),
-            }
-        })
-        .collect()
-}
-
-fn diag_err(line: usize, message: String) -> Diagnostic {
-    Diagnostic {
-        range: line_range(line),
-        severity: Some(DiagnosticSeverity::ERROR),
-        message,
-        ..Default::default()
+/// The LSP range of a token's char-column `span` on `line`, or the whole
+/// line when the span isn't known
+fn span_range(rope: &Rope, line: usize, span: Option<Span>) -> Range {
+    match span {
+        Some(span) => Range { start: utf16_position(rope, line, span.start), end: utf16_position(rope, line, span.end) },
+        None => line_range(line),
     }
 }
 
-fn diag_warn(line: usize, message: String) -> Diagnostic {
+fn to_lsp(rope: &Rope, diagnostic: validate::Diagnostic) -> Diagnostic {
     Diagnostic {
-        range: line_range(line),
-        severity: Some(DiagnosticSeverity::WARNING),
-        message,
+        range: span_range(rope, diagnostic.line, diagnostic.span),
+        severity: Some(match diagnostic.severity {
+            Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+        }),
+        code: Some(NumberOrString::String(diagnostic.code.to_string())),
+        message: diagnostic.message,
         ..Default::default()
     }
 }
 
-/// Indent of the lines that participate in [PLY] scope nesting.
-fn step_like_indent(kind: &LineKind) -> Option<usize> {
-    match kind {
-        LineKind::Step { indent, .. }
-        | LineKind::BoundaryStep { indent, .. }
-        | LineKind::Ply { indent, .. }
-        | LineKind::Cse { indent, .. }
-        | LineKind::Ret { indent, .. }
-        | LineKind::New { indent, .. } => Some(*indent),
-        _ => None,
+/// Markdown for a noun that steps call: its class name, purity (with the
+/// boundaries it crosses) and one bullet per distinct method signature.
+fn noun_summary(parsed: &[ParsedLine], noun: &str) -> Option<String> {
+    let mut prefixes: Vec<&str> = Vec::new();
+    let mut methods: Vec<String> = Vec::new();
+    for parsed_line in parsed {
+        let (prefix, verb, params, output, is_static) = match &parsed_line.kind {
+            LineKind::Step { noun: n, verb, params, output, is_static, .. }
+            | LineKind::Ply { noun: n, verb, params, output, is_static, .. } if n == noun => {
+                (None, verb, params, output, *is_static)
+            }
+            LineKind::BoundaryStep { prefix, noun: n, verb, params, output, is_static, .. } if n == noun => {
+                (Some(prefix.as_str()), verb, params, output, *is_static)
+            }
+            _ => continue,
+        };
+        if let Some(prefix) = prefix
+            && !prefixes.contains(&prefix)
+        {
+            prefixes.push(prefix);
+        }
+        let sep = if is_static { "::" } else { "." };
+        let method = format!("- `{}{}({}): {}`", sep, verb, params.join(", "), output);
+        if !methods.contains(&method) {
+            methods.push(method);
+        }
+    }
+    if methods.is_empty() {
+        return None;
     }
+    let purity = if prefixes.is_empty() { "pure".to_string() } else { format!("impure: {}", prefixes.join(" ")) };
+    Some(format!("**{}** (noun, {})\n\n{}", to_pascal_case(noun), purity, methods.join("\n")))
 }
 
-/// A `noun.verb` (or `Noun::verb`) must keep one signature throughout a document.
-fn check_sig(
-    diagnostics: &mut Vec<Diagnostic>,
-    sigs: &mut HashMap<String, (usize, Vec<String>, String)>,
-    line_num: usize,
-    noun: &str,
-    verb: &str,
-    is_static: bool,
-    params: &[String],
-    output: &str,
-) {
-    let sep = if is_static { "::" } else { "." };
-    let key = format!("{}{}{}", noun, sep, verb);
-    if let Some((first_line, first_params, first_output)) = sigs.get(&key) {
-        if first_params != params || first_output != output {
-            diagnostics.push(diag_err(line_num, format!(
-                "Inconsistent signature for '{}': expected ({}) -> {} (from line {}), got ({}) -> {}",
-                key,
-                first_params.join(", "),
-                first_output,
-                first_line + 1,
-                params.join(", "),
-                output)));
-        }
-    } else {
-        sigs.insert(key, (line_num, params.to_vec(), output.to_string()));
+/// Whole-block templates on an otherwise empty line: a `[REQ]` at column 0, a
+/// `[PLY]` with its first `[CSE]` at step indentation. The bodies carry their
+/// own indentation, so clients must insert them as-is.
+fn snippet_completions(line: &str, col: usize) -> Vec<CompletionItem> {
+    if !line.trim().is_empty() {
+        return Vec::new();
     }
+    let (label, detail, body) = match col {
+        0 => (
+            "[REQ] block",
+            "requirement with its first step",
+            "[REQ] ${1:noun}.${2:verb}(${3:InputDto}): ${4:OutputDto}\n    $0",
+        ),
+        4 => (
+            "[PLY] block",
+            "polymorphic dispatch with its first case",
+            "[PLY] ${1:noun}.${2:verb}(${3:InputDto}): ${4:OutputDto}\n        [CSE] ${5:case}\n        $0",
+        ),
+        _ => return Vec::new(),
+    };
+    vec![CompletionItem {
+        label: label.to_string(),
+        kind: Some(CompletionItemKind::SNIPPET),
+        detail: Some(detail.to_string()),
+        insert_text: Some(body.to_string()),
+        insert_text_format: Some(InsertTextFormat::SNIPPET),
+        insert_text_mode: Some(InsertTextMode::AS_IS),
+        ..Default::default()
+    }]
 }
 
-/// Check if a type is a raw primitive (string, number, boolean, etc.)
-fn is_primitive(s: &str) -> bool {
-    matches!(
-        s,
-        "string" | "number" | "boolean" | "void" | "Uint8Array" | "Primitive"
-    )
+fn scope_completions(parsed: &[ParsedLine], line: usize) -> Vec<CompletionItem> {
+    scope_at(parsed, line)
+        .into_iter()
+        .map(|name| CompletionItem {
+            label: name,
+            kind: Some(CompletionItemKind::VARIABLE),
+            detail: Some("in scope".to_string()),
+            ..Default::default()
+        })
+        .collect()
 }
 
-/// Check if a value is valid for boundary crossing:
-/// - DTOs (ends in "Dto")
-/// - Raw primitives (string, number, boolean, void, Uint8Array)
-/// - Type names that resolve to primitives (e.g., `url: string`)
-fn is_dto_or_primitive(s: &str, defined_types: &HashMap<String, String>) -> bool {
-    // DTOs are always valid at boundaries
-    if s.ends_with("Dto") {
-        return true;
-    }
-
-    // Raw primitives are valid
-    if is_primitive(s) {
-        return true;
+/// Fields of the in-scope DTO named before a trailing dot: `recording.` offers
+/// `RecordingDto`'s properties, as does `RecordingDto.`.
+fn property_completions(parsed: &[ParsedLine], line: usize, prefix: &str) -> Vec<CompletionItem> {
+    let Some(before_dot) = prefix.strip_suffix('.') else { return Vec::new() };
+    let start = before_dot
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .map_or(0, |p| p + 1);
+    let word = &before_dot[start..];
+    if word.is_empty() {
+        return Vec::new();
     }
-
-    // Check if it's a type name that resolves to a primitive
-    if let Some(underlying_type) = defined_types.get(s) {
-        return is_primitive(underlying_type);
+    let scope = scope_at(parsed, line);
+    let Some(dto) = [word.to_string(), format!("{}Dto", to_pascal_case(word))]
+        .into_iter()
+        .find(|name| name.ends_with("Dto") && scope.contains(name))
+    else {
+        return Vec::new();
+    };
+
+    let Some(def) = parsed.iter().position(|l| matches!(&l.kind, LineKind::DtoDef { name, .. } if *name == dto)) else {
+        return Vec::new();
+    };
+    let mut fields: Vec<String> = match &parsed[def].kind {
+        LineKind::DtoDef { properties, .. } => properties.clone(),
+        _ => Vec::new(),
+    };
+    for l in &parsed[def + 1..] {
+        match &l.kind {
+            LineKind::DtoProperty { name, .. } => fields.push(name.clone()),
+            LineKind::DtoArrayProperty { property_name, .. } => fields.push(property_name.clone()),
+            LineKind::DtoDesc { .. } | LineKind::DtoRef(_) => {}
+            _ => break,
+        }
     }
 
-    false
+    fields
+        .iter()
+        .map(|field| {
+            let (field, _) = split_optional(field);
+            let label = match parse_array_property(field) {
+                Some((base, suffix)) => format!("{}{}", base, suffix),
+                None => field.to_string(),
+            };
+            CompletionItem {
+                label,
+                kind: Some(CompletionItemKind::FIELD),
+                detail: Some(format!("{} property", dto)),
+                ..Default::default()
+            }
+        })
+        .collect()
 }
 
-/// Check if a type expression is valid for [TYP] definitions
-/// Valid: primitives, generics (Array<T>, Record<K,V>), tuples ([a, b]), string enums
-fn is_valid_primitive_type(s: &str) -> bool {
-    let s = s.trim();
-
-    // Raw primitives
-    if is_primitive(s) {
-        return true;
-    }
-
-    // String enum types like "genie" | "fiveNine"
-    if s.contains('"') && s.contains('|') {
-        return true;
-    }
-
-    // Generic types like Array<url>, Record<string, Primitive>
-    if s.contains('<') && s.ends_with('>') {
-        let base = s.split('<').next().unwrap_or("");
-        // Allow any generic - the inner types will be validated separately if needed
-        return matches!(base, "Array" | "Record" | "Map" | "Set" | "Promise" | "Partial" | "Required" | "Pick" | "Omit" | "ReturnType");
+/// After `[CSE] ` inside a [PLY] block, the members of the string-union
+/// [TYP] named like the block's noun (`[TYP] provider: "genie" | "fiveNine"`
+/// for `[PLY] provider.get`).
+fn case_completions(parsed: &[ParsedLine], line: usize, prefix: &str) -> Vec<CompletionItem> {
+    let Some(partial) = prefix.trim_start().strip_prefix("[CSE]") else { return Vec::new() };
+    if !partial.starts_with(' ') || partial.trim().contains(' ') {
+        return Vec::new();
     }
-
-    // Tuple types like [id, name]
-    if s.starts_with('[') && s.ends_with(']') {
-        return true;
-    }
-
-    false
-}
-
-/// Validate a `[TYP:...]` constraint-modifier list (e.g. `ext,uuid` or
-/// `min=0,max=100`) against the design contract §5. Returns one message per
-/// problem, byte-identical to the TS engine + studio so all three emit the
-/// same diagnostics. `name` is the type name, `declared_type` the primitive it
-/// aliases (e.g. "string", "number").
-/// Mirrors the TS engine's `^-?\d+(\.\d+)?$` numeric-value check exactly:
-/// plain decimals only — no exponents, no leading `+`, no bare `.5` / `5.`.
-fn is_plain_decimal(v: &str) -> bool {
-    let s = v.strip_prefix('-').unwrap_or(v);
-    let mut parts = s.splitn(2, '.');
-    let all_digits = |p: &str| !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit());
-    let int = parts.next().unwrap_or("");
-    let frac = parts.next();
-    all_digits(int) && frac.map_or(true, all_digits)
-}
-
-fn validate_typ_modifiers(raw: &str, name: &str, declared_type: &str) -> Vec<String> {
-    let mut errors = Vec::new();
-    for item in raw.split(',') {
-        let item = item.trim();
-        if item.is_empty() {
-            continue;
-        }
-        // `min=0` splits into id + value; bare modifiers have no value.
-        // NO trim around '=' — the TS engine slices at indexOf('=') verbatim,
-        // so `min = 5` yields the unknown modifier `min ` there; mirror that.
-        let (id, value) = match item.split_once('=') {
-            Some((i, v)) => (i, Some(v)),
-            None => (item, None),
-        };
-        // Required base type per modifier; None = ext/core/example (no base requirement).
-        let base: Option<&str> = match id {
-            "ext" | "core" | "example" => None,
-            "uuid" | "email" | "url" | "nonempty" => Some("string"),
-            "int" | "min" | "max" | "positive" => Some("number"),
-            _ => {
-                errors.push(format!(
-                    "[TYP] unknown modifier \"{}\" (allowed: ext, core, uuid, email, url, nonempty, int, min=<n>, max=<n>, positive, example=<value>)",
-                    id
-                ));
-                continue;
-            }
-        };
-        let takes_value = id == "min" || id == "max";
-        let takes_text = id == "example";
-        if takes_value {
-            let numeric = value.map(is_plain_decimal).unwrap_or(false);
-            if !numeric {
-                errors.push(format!(
-                    "[TYP] modifier \"{}\" requires a numeric value (e.g. min=0)",
-                    id
-                ));
-                continue;
-            }
-        } else if takes_text {
-            // Free-text value, mirrors the TS engine: required and non-empty.
-            if value.map_or(true, |v| v.is_empty()) {
-                errors.push(format!(
-                    "[TYP] modifier \"{}\" requires a value (e.g. example=orders)",
-                    id
-                ));
-                continue;
-            }
-        } else if value.is_some() {
-            errors.push(format!("[TYP] modifier \"{}\" does not take a value", id));
-            continue;
-        }
-        if let Some(b) = base {
-            if declared_type != b {
-                errors.push(format!(
-                    "[TYP] modifier \"{}\" requires a {} type, but \"{}\" is {}",
-                    id, b, name, declared_type
-                ));
+    let indent = prefix.len() - prefix.trim_start().len();
+    let mut noun = None;
+    for l in parsed[..line.min(parsed.len())].iter().rev() {
+        match &l.kind {
+            LineKind::Ply { noun: ply_noun, indent: ply_indent, .. } if *ply_indent < indent => {
+                noun = Some(ply_noun);
+                break;
             }
+            LineKind::Req { .. } | LineKind::Empty => break,
+            _ => {}
         }
     }
-    errors
+    let Some(noun) = noun else { return Vec::new() };
+    let members = parsed.iter().find_map(|l| match &l.kind {
+        LineKind::TypDef { name, type_name, .. } if name == noun => union_members(type_name),
+        _ => None,
+    });
+
+    members
+        .into_iter()
+        .flatten()
+        .map(|member| CompletionItem {
+            label: member.to_string(),
+            kind: Some(CompletionItemKind::ENUM_MEMBER),
+            detail: Some(format!("{} case", noun)),
+            ..Default::default()
+        })
+        .collect()
 }
 
 #[tower_lsp::async_trait]
@@ -717,11 +451,20 @@ impl LanguageServer for Backend {
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                    TextDocumentSyncKind::FULL,
+                    TextDocumentSyncKind::INCREMENTAL,
                 )),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
                 definition_provider: Some(OneOf::Left(true)),
                 references_provider: Some(OneOf::Left(true)),
+                rename_provider: Some(OneOf::Left(true)),
+                document_symbol_provider: Some(OneOf::Left(true)),
+                folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+                inlay_hint_provider: Some(OneOf::Left(true)),
+                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                signature_help_provider: Some(SignatureHelpOptions {
+                    trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+                    ..Default::default()
+                }),
                 completion_provider: Some(CompletionOptions {
                     trigger_characters: Some(vec![
                         ":".to_string(),
@@ -748,20 +491,23 @@ impl LanguageServer for Backend {
 
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
         let uri = params.text_document.uri;
-        let text = params.text_document.text;
-        let rope = Rope::from_str(&text);
+        let doc = self.load(&uri, Rope::from_str(&params.text_document.text));
 
-        self.documents.write().await.insert(uri.clone(), rope);
+        self.documents.write().await.insert(uri.clone(), doc);
         self.validate(&uri).await;
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
         let uri = params.text_document.uri;
-        if let Some(change) = params.content_changes.into_iter().next() {
-            let rope = Rope::from_str(&change.text);
-            self.documents.write().await.insert(uri.clone(), rope);
-            self.validate(&uri).await;
+        {
+            let mut docs = self.documents.write().await;
+            let mut rope = docs.remove(&uri).map(|d| d.rope).unwrap_or_default();
+            for change in params.content_changes {
+                apply_change(&mut rope, change);
+            }
+            docs.insert(uri.clone(), self.load(&uri, rope));
         }
+        self.validate(&uri).await;
     }
 
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
@@ -776,25 +522,24 @@ impl LanguageServer for Backend {
         let pos = params.text_document_position.position;
 
         let docs = self.documents.read().await;
-        let Some(rope) = docs.get(&uri) else {
+        let Some(doc) = docs.get(&uri) else {
             return Ok(None);
         };
 
-        let text = rope.to_string();
-        let lines_vec: Vec<&str> = text.lines().collect();
+        let lines_vec: Vec<&str> = doc.text.lines().collect();
         let current_line = lines_vec.get(pos.line as usize).unwrap_or(&"");
         let col = pos.character as usize;
         let prefix = &current_line[..col.min(current_line.len())];
 
         let mut items = Vec::new();
 
-        // Boundary prefixes
+        // Boundary prefixes, including any from rune.toml
         if prefix.trim().is_empty() || prefix.ends_with(' ') {
-            for bp in ["db:", "fs:", "mq:", "ex:", "os:", "lg:"] {
+            for (bp, description) in doc.config.boundaries() {
                 items.push(CompletionItem {
-                    label: bp.to_string(),
+                    label: bp,
                     kind: Some(CompletionItemKind::KEYWORD),
-                    detail: Some(boundary_detail(bp)),
+                    detail: Some(description),
                     ..Default::default()
                 });
             }
@@ -824,6 +569,8 @@ impl LanguageServer for Backend {
             }
         }
 
+        items.extend(snippet_completions(current_line, col));
+
         // Common types (after colon)
         if prefix.ends_with(':') || prefix.ends_with(": ") {
             for t in ["string", "number", "boolean", "void"] {
@@ -847,12 +594,19 @@ impl LanguageServer for Backend {
         }
 
         // Extract existing nouns, DTOs, faults from document
-        let parsed = parse_document(&text);
+        let parsed = &doc.parsed;
+
+        // Variables in scope (inside a step's parentheses)
+        if prefix.matches('(').count() > prefix.matches(')').count() {
+            items.extend(scope_completions(parsed, pos.line as usize));
+        }
+        items.extend(property_completions(parsed, pos.line as usize, prefix));
+        items.extend(case_completions(parsed, pos.line as usize, prefix));
         let mut nouns: HashSet<String> = HashSet::new();
         let mut dtos: HashSet<String> = HashSet::new();
         let mut faults: HashSet<String> = HashSet::new();
 
-        for parsed_line in &parsed {
+        for parsed_line in parsed {
             match &parsed_line.kind {
                 LineKind::Req { noun, .. }
                 | LineKind::Step { noun, .. }
@@ -914,13 +668,12 @@ impl LanguageServer for Backend {
         let pos = params.text_document_position_params.position;
 
         let docs = self.documents.read().await;
-        let Some(rope) = docs.get(&uri) else {
+        let Some(doc) = docs.get(&uri) else {
             return Ok(None);
         };
 
-        let text = rope.to_string();
-        let lines: Vec<&str> = text.lines().collect();
-        let parsed = parse_document(&text);
+        let lines: Vec<&str> = doc.text.lines().collect();
+        let parsed = &doc.parsed;
 
         let line_num = pos.line as usize;
         if line_num >= parsed.len() {
@@ -973,12 +726,9 @@ impl LanguageServer for Backend {
 
         // Build DTO definitions map with properties
         let mut dto_defs: HashMap<String, Vec<String>> = HashMap::new();
-        for parsed_line in &parsed {
-            match &parsed_line.kind {
-                LineKind::DtoDef { name, properties } => {
-                    dto_defs.insert(name.clone(), properties.clone());
-                }
-                _ => {}
+        for parsed_line in parsed {
+            if let LineKind::DtoDef { name, properties } = &parsed_line.kind {
+                dto_defs.insert(name.clone(), properties.clone());
             }
         }
 
@@ -992,12 +742,7 @@ impl LanguageServer for Backend {
         }
 
         // Check if it's a TYP reference
-        if let Some((type_name, desc)) = typ_defs.get(&word) {
-            let content = if let Some(d) = desc {
-                format!("**{}**: `{}`\n\n{}", word, type_name, d)
-            } else {
-                format!("**{}**: `{}`", word, type_name)
-            };
+        if let Some(content) = typ_hover(&word, &typ_defs) {
             return Ok(Some(Hover {
                 contents: HoverContents::Markup(MarkupContent {
                     kind: MarkupKind::Markdown,
@@ -1007,13 +752,16 @@ impl LanguageServer for Backend {
             }));
         }
 
-        // Check if it's a NON reference
-        if let Some(desc) = non_defs.get(&word) {
-            let content = if let Some(d) = desc {
-                format!("**{}** (noun)\n\n{}", word, d)
-            } else {
-                format!("**{}** (noun)", word)
+        // Check if it's a noun: its [NON] description and/or inferred methods
+        let summary = noun_summary(parsed, &word);
+        if non_defs.contains_key(&word) || summary.is_some() {
+            let mut content = match summary {
+                Some(summary) => summary,
+                None => format!("**{}** (noun)", word),
             };
+            if let Some(Some(d)) = non_defs.get(&word) {
+                content = format!("{}\n\n{}", content, d);
+            }
             return Ok(Some(Hover {
                 contents: HoverContents::Markup(MarkupContent {
                     kind: MarkupKind::Markdown,
@@ -1024,38 +772,28 @@ impl LanguageServer for Backend {
         }
 
         // Check if it's a DTO reference
-        if word.ends_with("Dto") {
-            if let Some(props) = dto_defs.get(&word) {
-                let content = if props.is_empty() {
-                    format!("**{}** {{}}", word)
-                } else {
-                    format!("**{}** {{ {} }}", word, props.join(", "))
-                };
-                return Ok(Some(Hover {
-                    contents: HoverContents::Markup(MarkupContent {
-                        kind: MarkupKind::Markdown,
-                        value: content,
-                    }),
-                    range: None,
-                }));
-            }
+        if word.ends_with("Dto")
+            && let Some(props) = dto_defs.get(&word)
+        {
+            let content = if props.is_empty() {
+                format!("**{}** {{}}", word)
+            } else {
+                format!("**{}** {{ {} }}", word, props.join(", "))
+            };
+            return Ok(Some(Hover {
+                contents: HoverContents::Markup(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: content,
+                }),
+                range: None,
+            }));
         }
 
         // Check if it's a boundary prefix
-        let boundary_prefixes = ["db:", "fs:", "mq:", "ex:", "os:", "lg:"];
-        for bp in boundary_prefixes {
-            if current_line.trim().starts_with(bp) && col <= current_line.find(bp).unwrap_or(0) + 3 {
-                return Ok(Some(Hover {
-                    contents: HoverContents::Markup(MarkupContent {
-                        kind: MarkupKind::Markdown,
-                        value: format!("**{}** {}", bp, boundary_detail(bp)),
-                    }),
-                    range: None,
-                }));
-            }
-        }
-
-        Ok(None)
+        Ok(boundary_hover(current_line, col, &doc.config).map(|value| Hover {
+            contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value }),
+            range: None,
+        }))
     }
 
     async fn goto_definition(
@@ -1065,19 +803,18 @@ impl LanguageServer for Backend {
         let uri = params.text_document_position_params.text_document.uri;
         let pos = params.text_document_position_params.position;
 
-        let docs = self.documents.read().await;
-        let Some(rope) = docs.get(&uri) else {
+        let Some((word, definition)) = self
+            .with_parsed(&uri, |doc| {
+                let current_line = doc.text.lines().nth(pos.line as usize).unwrap_or("");
+                let word = get_word_at_position(current_line, pos.character as usize);
+                let definition = definition_line(&doc.rope, &doc.parsed, &word);
+                (word, definition)
+            })
+            .await
+        else {
             return Ok(None);
         };
 
-        let text = rope.to_string();
-        let lines: Vec<&str> = text.lines().collect();
-        let parsed = parse_document(&text);
-
-        let current_line = lines.get(pos.line as usize).unwrap_or(&"");
-        let col = pos.character as usize;
-        let word = get_word_at_position(current_line, col);
-
         if word.is_empty() {
             self.client
                 .log_message(MessageType::INFO, "gd: word is empty")
@@ -1089,122 +826,284 @@ impl LanguageServer for Backend {
             .log_message(MessageType::INFO, format!("gd: looking for '{}'", word))
             .await;
 
-        // Build maps of definitions with their line numbers
-        let mut typ_lines: HashMap<String, usize> = HashMap::new();
-        let mut dto_lines: HashMap<String, usize> = HashMap::new();
-        let mut non_lines: HashMap<String, usize> = HashMap::new();
-
-        for parsed_line in &parsed {
-            match &parsed_line.kind {
-                LineKind::TypDef { name, .. } => {
-                    typ_lines.insert(name.clone(), parsed_line.line_num);
-                }
-                LineKind::DtoDef { name, properties: _ } => {
-                    dto_lines.insert(name.clone(), parsed_line.line_num);
-                }
-                LineKind::NonDef { name } => {
-                    non_lines.insert(name.clone(), parsed_line.line_num);
-                }
-                _ => {}
-            }
-        }
+        if let Some((kind, range)) = definition {
+            self.client
+                .log_message(MessageType::INFO, format!("gd: found {} at line {}", kind, range.start.line))
+                .await;
+            return Ok(Some(GotoDefinitionResponse::Array(vec![Location {
+                uri: uri.clone(),
+                range,
+            }])));
+        }
 
         self.client
-            .log_message(MessageType::INFO, format!("gd: typ_lines keys: {:?}", typ_lines.keys().collect::<Vec<_>>()))
+            .log_message(MessageType::INFO, format!("gd: '{}' not found in typ_lines, dto_lines, non_lines, or noun_lines", word))
             .await;
 
-        // Find TYP definition
-        if let Some(&line_num) = typ_lines.get(&word) {
-            self.client
-                .log_message(MessageType::INFO, format!("gd: found TYP at line {}", line_num))
-                .await;
-            return Ok(Some(GotoDefinitionResponse::Array(vec![Location {
-                uri: uri.clone(),
-                range: line_range(line_num),
-            }])));
-        }
+        Ok(None)
+    }
 
-        // Find DTO definition
-        if let Some(&line_num) = dto_lines.get(&word) {
-            self.client
-                .log_message(MessageType::INFO, format!("gd: found DTO at line {}", line_num))
-                .await;
-            return Ok(Some(GotoDefinitionResponse::Array(vec![Location {
-                uri: uri.clone(),
-                range: line_range(line_num),
-            }])));
-        }
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        let uri = params.text_document_position.text_document.uri;
+        let pos = params.text_document_position.position;
 
-        // Find NON definition
-        if let Some(&line_num) = non_lines.get(&word) {
-            self.client
-                .log_message(MessageType::INFO, format!("gd: found NON at line {}", line_num))
-                .await;
-            return Ok(Some(GotoDefinitionResponse::Array(vec![Location {
-                uri: uri.clone(),
-                range: line_range(line_num),
-            }])));
+        let locations = self
+            .with_parsed(&uri, |doc| {
+                reference_locations(&doc.rope, &doc.parsed, &uri, pos)
+            })
+            .await
+            .unwrap_or_default();
+
+        if locations.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(locations))
         }
+    }
 
-        self.client
-            .log_message(MessageType::INFO, format!("gd: '{}' not found in typ_lines, dto_lines, or non_lines", word))
-            .await;
+    async fn document_symbol(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> Result<Option<DocumentSymbolResponse>> {
+        let symbols = self.with_parsed(&params.text_document.uri, |doc| document_symbols(&doc.parsed)).await;
+        Ok(symbols.map(DocumentSymbolResponse::Nested))
+    }
 
-        Ok(None)
+    async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
+        Ok(self.with_parsed(&params.text_document.uri, |doc| folding_ranges(&doc.parsed)).await)
     }
 
-    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+    async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+        let lines = params.range.start.line..=params.range.end.line;
+        Ok(self.with_parsed(&params.text_document.uri, |doc| output_type_hints(&doc.parsed, lines)).await)
+    }
+
+    async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
+        let pos = params.text_document_position_params.position;
+        let uri = &params.text_document_position_params.text_document.uri;
+        let help = self.with_parsed(uri, |doc| signature_help_at(&doc.rope, &doc.parsed, pos));
+        Ok(help.await.flatten())
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
         let uri = params.text_document_position.text_document.uri;
         let pos = params.text_document_position.position;
 
-        let docs = self.documents.read().await;
-        let Some(rope) = docs.get(&uri) else {
+        let Some(edits) = self.with_parsed(&uri, |doc| rename_edits(&doc.rope, &doc.parsed, pos, &params.new_name)).await else {
             return Ok(None);
         };
+        let edits = edits.map_err(tower_lsp::jsonrpc::Error::invalid_params)?;
 
-        let text = rope.to_string();
-        let lines: Vec<&str> = text.lines().collect();
-
-        let current_line = lines.get(pos.line as usize).unwrap_or(&"");
-        let col = pos.character as usize;
-        let word = get_word_at_position(current_line, col);
+        Ok(edits.map(|edits| WorkspaceEdit {
+            changes: Some(HashMap::from([(uri, edits)])),
+            ..Default::default()
+        }))
+    }
 
-        if word.is_empty() {
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let uri = params.text_document.uri;
+        let line = params.range.start.line as usize;
+        let Some(actions) = self.with_parsed(&uri, |doc| static_call_actions(&doc.rope, &doc.parsed, &uri, line)).await else {
             return Ok(None);
+        };
+        Ok(if actions.is_empty() { None } else { Some(actions) })
+    }
+}
+
+/// Quick-fix for the step on `line`: an instance call on a noun nothing in
+/// scope provides becomes static (`x.foo` -> `x::foo`), and a static call on
+/// a noun that is in scope becomes an instance call.
+fn static_call_actions(rope: &Rope, parsed: &[ParsedLine], uri: &Url, line: usize) -> Vec<CodeActionOrCommand> {
+    let Some(parsed_line) = parsed.get(line) else { return Vec::new() };
+    let (noun, verb, is_static) = match &parsed_line.kind {
+        LineKind::Step { noun, verb, is_static, .. } | LineKind::BoundaryStep { noun, verb, is_static, .. } => {
+            (noun, verb, *is_static)
         }
+        _ => return Vec::new(),
+    };
+
+    let Some(in_scope) = noun_in_scope(parsed, line, noun) else { return Vec::new() };
+
+    let (from, to, title) = match (is_static, in_scope) {
+        (false, false) => (".", "::", format!("Make static: {}::{}", noun, verb)),
+        (true, true) => ("::", ".", format!("Call on the in-scope {}: {}.{}", noun, noun, verb)),
+        _ => return Vec::new(),
+    };
+
+    // The separator sits right after the noun
+    let Some(noun_span) = parsed_line.spans.noun else { return Vec::new() };
+    let start = noun_span.end;
+    let end = start + from.chars().count();
+    if rope.line(line).chars().skip(start).take(end - start).ne(from.chars()) {
+        return Vec::new();
+    }
+    let range = Range { start: utf16_position(rope, line, start), end: utf16_position(rope, line, end) };
+    let edit = TextEdit { range, new_text: to.to_string() };
+    vec![CodeActionOrCommand::CodeAction(CodeAction {
+        title,
+        kind: Some(CodeActionKind::QUICKFIX),
+        edit: Some(WorkspaceEdit {
+            changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+            ..Default::default()
+        }),
+        ..Default::default()
+    })]
+}
 
-        let mut locations = Vec::new();
-
-        // Find all references to this word
-        for (i, line) in lines.iter().enumerate() {
-            if line.contains(&word) {
-                // Find column position of the word in this line
-                if let Some(col_start) = line.find(&word) {
-                    locations.push(Location {
-                        uri: uri.clone(),
-                        range: Range {
-                            start: Position {
-                                line: i as u32,
-                                character: col_start as u32,
-                            },
-                            end: Position {
-                                line: i as u32,
-                                character: (col_start + word.len()) as u32,
-                            },
-                        },
-                    });
-                }
+/// Signature of the call whose parentheses enclose `pos`, taken from its
+/// first use elsewhere in the document. The active parameter is the number
+/// of top-level commas between the `(` and the cursor.
+fn signature_help_at(rope: &Rope, parsed: &[ParsedLine], pos: Position) -> Option<SignatureHelp> {
+    let (line, col) = char_position(rope, pos);
+    let before: Vec<char> = rope.line(line).chars().take(col).collect();
+
+    let mut depth = 0;
+    let mut commas = 0;
+    let mut open = None;
+    for (i, c) in before.iter().enumerate().rev() {
+        match c {
+            ')' => depth += 1,
+            '(' if depth == 0 => {
+                open = Some(i);
+                break;
             }
+            '(' => depth -= 1,
+            ',' if depth == 0 => commas += 1,
+            _ => {}
+        }
+    }
+    let open = open?;
+
+    let callee: String = before[..open]
+        .iter()
+        .rev()
+        .take_while(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | ':'))
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    // Drop a boundary prefix: `db:user.load` -> `user.load`, but keep `Id::create`
+    let callee = match callee.split_once(':') {
+        Some((prefix, rest)) if !prefix.is_empty() && !rest.starts_with(':') => rest,
+        _ => callee.as_str(),
+    };
+
+    let sigs = method_signatures(parsed.iter().filter(|l| l.line_num != line));
+    let (_, params, output) = sigs.get(callee)?;
+
+    let mut label = format!("{}(", callee);
+    let mut parameters = Vec::new();
+    for (i, param) in params.iter().enumerate() {
+        if i > 0 {
+            label.push_str(", ");
         }
+        let start = label.chars().count() as u32;
+        label.push_str(param);
+        parameters.push(ParameterInformation {
+            label: ParameterLabel::LabelOffsets([start, start + param.chars().count() as u32]),
+            documentation: None,
+        });
+    }
+    label.push_str(&format!("): {}", output));
+
+    Some(SignatureHelp {
+        signatures: vec![SignatureInformation {
+            label,
+            documentation: None,
+            parameters: Some(parameters),
+            active_parameter: None,
+        }],
+        active_signature: Some(0),
+        active_parameter: Some(commas),
+    })
+}
 
-        if locations.is_empty() {
-            Ok(None)
-        } else {
-            Ok(Some(locations))
+/// Char index in `rope` of an LSP position (whose `character` counts UTF-16
+/// code units), clamped to the document.
+fn position_to_char(rope: &Rope, pos: Position) -> usize {
+    let line = (pos.line as usize).min(rope.len_lines().saturating_sub(1));
+    let line_start = rope.line_to_char(line);
+    let line_end = if line + 1 < rope.len_lines() {
+        rope.line_to_char(line + 1)
+    } else {
+        rope.len_chars()
+    };
+    let start_cu = rope.char_to_utf16_cu(line_start);
+    let end_cu = rope.char_to_utf16_cu(line_end);
+    let cu = (start_cu + pos.character as usize).min(end_cu);
+    rope.utf16_cu_to_char(cu)
+}
+
+/// Line and char column in `rope` of an LSP position
+fn char_position(rope: &Rope, pos: Position) -> (usize, usize) {
+    let char_idx = position_to_char(rope, pos);
+    let line = rope.char_to_line(char_idx);
+    (line, char_idx - rope.line_to_char(line))
+}
+
+/// LSP position (UTF-16 `character`) of a char column on `line` of `rope`,
+/// clamped to the line
+fn utf16_position(rope: &Rope, line: usize, col: usize) -> Position {
+    let line_start = rope.line_to_char(line);
+    let col = col.min(rope.line(line).len_chars());
+    let cu = rope.char_to_utf16_cu(line_start + col) - rope.char_to_utf16_cu(line_start);
+    Position { line: line as u32, character: cu as u32 }
+}
+
+/// Apply one `didChange` event: a ranged edit is spliced into the rope, a
+/// change without a range replaces the whole document.
+fn apply_change(rope: &mut Rope, change: TextDocumentContentChangeEvent) {
+    match change.range {
+        Some(range) => {
+            let start = position_to_char(rope, range.start);
+            let end = position_to_char(rope, range.end).max(start);
+            rope.remove(start..end);
+            rope.insert(start, &change.text);
         }
+        None => *rope = Rope::from_str(&change.text),
     }
 }
 
+/// Where `word` is defined: a [TYP], [DTO] or [NON] line, or else the first
+/// step that introduces the noun (calls it, constructs it, or returns it).
+/// The range covers just the name when its span is known.
+fn definition_line(rope: &Rope, parsed: &[ParsedLine], word: &str) -> Option<(&'static str, Range)> {
+    let mut typ_lines: HashMap<&str, (usize, Option<Span>)> = HashMap::new();
+    let mut dto_lines: HashMap<&str, (usize, Option<Span>)> = HashMap::new();
+    let mut non_lines: HashMap<&str, (usize, Option<Span>)> = HashMap::new();
+    let mut noun_lines: HashMap<&str, (usize, Option<Span>)> = HashMap::new();
+
+    for parsed_line in parsed {
+        let line = parsed_line.line_num;
+        let spans = parsed_line.spans;
+        match &parsed_line.kind {
+            LineKind::TypDef { name, .. } => {
+                typ_lines.entry(name).or_insert((line, spans.name));
+            }
+            LineKind::DtoDef { name, .. } => {
+                dto_lines.entry(name).or_insert((line, spans.name));
+            }
+            LineKind::NonDef { name } => {
+                non_lines.entry(name).or_insert((line, spans.name));
+            }
+            LineKind::Step { noun, output, .. }
+            | LineKind::BoundaryStep { noun, output, .. }
+            | LineKind::Ply { noun, output, .. } => {
+                noun_lines.entry(noun).or_insert((line, spans.noun));
+                noun_lines.entry(output).or_insert((line, spans.output));
+            }
+            LineKind::New { class_name, .. } => {
+                noun_lines.entry(class_name).or_insert((line, spans.name));
+            }
+            _ => {}
+        }
+    }
+
+    [("TYP", &typ_lines), ("DTO", &dto_lines), ("NON", &non_lines), ("noun", &noun_lines)]
+        .into_iter()
+        .find_map(|(kind, lines)| lines.get(word).map(|&(line, span)| (kind, span_range(rope, line, span))))
+}
+
 fn get_word_at_position(line: &str, col: usize) -> String {
     let chars: Vec<char> = line.chars().collect();
     if col >= chars.len() {
@@ -1224,18 +1123,380 @@ fn get_word_at_position(line: &str, col: usize) -> String {
     chars[start..end].iter().collect()
 }
 
-fn boundary_detail(prefix: &str) -> String {
-    match prefix {
-        "db:" => "database / persistence".to_string(),
-        "fs:" => "file system (local)".to_string(),
-        "mq:" => "message queue".to_string(),
-        "ex:" => "external service / provider".to_string(),
-        "os:" => "object storage (S3, GCS)".to_string(),
-        "lg:" => "logs".to_string(),
-        _ => "boundary".to_string(),
+/// Character columns of every whole-word occurrence of `word` in `line`, using
+/// the same identifier boundaries as `get_word_at_position`.
+fn word_occurrences(line: &str, word: &str) -> Vec<usize> {
+    let chars: Vec<char> = line.chars().collect();
+    let target: Vec<char> = word.chars().collect();
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let mut cols = Vec::new();
+    if target.is_empty() {
+        return cols;
+    }
+    let mut i = 0;
+    while i + target.len() <= chars.len() {
+        if chars[i..i + target.len()] == target[..]
+            && (i == 0 || !is_ident(chars[i - 1]))
+            && chars.get(i + target.len()).is_none_or(|c| !is_ident(*c))
+        {
+            cols.push(i);
+            i += target.len();
+        } else {
+            i += 1;
+        }
+    }
+    cols
+}
+
+/// Lines that are prose (descriptions, comments, `[DOC]` blocks) rather than code
+fn prose_lines(parsed: &[ParsedLine]) -> HashSet<usize> {
+    parsed
+        .iter()
+        .filter(|l| {
+            matches!(
+                l.kind,
+                LineKind::DtoDesc { .. }
+                    | LineKind::TypDesc { .. }
+                    | LineKind::NonDesc { .. }
+                    | LineKind::Comment { .. }
+                    | LineKind::DocDef { .. }
+                    | LineKind::DocLine { .. }
+            )
+        })
+        .map(|l| l.line_num)
+        .collect()
+}
+
+/// `line` without its inline `//` comment
+fn code_part(line: &str) -> &str {
+    match comment_start(line) {
+        Some(p) => &line[..p],
+        None => line,
+    }
+}
+
+/// Every whole-word use of the identifier under `pos` outside prose, one
+/// location per occurrence.
+fn reference_locations(rope: &Rope, parsed: &[ParsedLine], uri: &Url, pos: Position) -> Vec<Location> {
+    let (line, col) = char_position(rope, pos);
+    let word = get_word_at_position(&String::from(rope.line(line)), col);
+    if word.is_empty() {
+        return Vec::new();
+    }
+    let prose_lines = prose_lines(parsed);
+    let width = word.chars().count();
+
+    let mut locations = Vec::new();
+    for (i, l) in rope.lines().enumerate() {
+        if prose_lines.contains(&i) {
+            continue;
+        }
+        for start in word_occurrences(code_part(&String::from(l)), &word) {
+            locations.push(Location {
+                uri: uri.clone(),
+                range: Range { start: utf16_position(rope, i, start), end: utf16_position(rope, i, start + width) },
+            });
+        }
+    }
+    locations
+}
+
+/// Edits renaming the `[DTO]`/`[TYP]` under the cursor everywhere it appears as
+/// a whole word. Prose (descriptions, comments) is left alone. `Ok(None)` when
+/// the cursor isn't on a DTO or type; `Err` when the new name is unusable.
+fn rename_edits(rope: &Rope, parsed: &[ParsedLine], pos: Position, new_name: &str) -> std::result::Result<Option<Vec<TextEdit>>, String> {
+    let (line, col) = char_position(rope, pos);
+    let word = get_word_at_position(&String::from(rope.line(line)), col);
+    if word.is_empty() {
+        return Ok(None);
+    }
+
+    let mut renamable: HashSet<String> = HashSet::new();
+    let mut defined: HashSet<String> = HashSet::new();
+    for parsed_line in parsed {
+        match &parsed_line.kind {
+            LineKind::DtoDef { name, .. } | LineKind::TypDef { name, .. } => {
+                renamable.insert(name.clone());
+                defined.insert(name.clone());
+            }
+            LineKind::NonDef { name } => {
+                defined.insert(name.clone());
+            }
+            _ => {}
+        }
+    }
+    let prose_lines = prose_lines(parsed);
+
+    if !renamable.contains(&word) {
+        return Ok(None);
+    }
+    if new_name.is_empty() || !new_name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(format!("'{}' is not a valid name", new_name));
+    }
+    if new_name != word && defined.contains(new_name) {
+        return Err(format!("'{}' is already defined", new_name));
+    }
+
+    let width = word.chars().count();
+    let mut edits = Vec::new();
+    for (i, l) in rope.lines().enumerate() {
+        if prose_lines.contains(&i) {
+            continue;
+        }
+        for start in word_occurrences(code_part(&String::from(l)), &word) {
+            edits.push(TextEdit {
+                range: Range { start: utf16_position(rope, i, start), end: utf16_position(rope, i, start + width) },
+                new_text: new_name.to_string(),
+            });
+        }
+    }
+    Ok(Some(edits))
+}
+
+#[allow(deprecated)] // `DocumentSymbol::deprecated` must still be initialised
+fn symbol(name: String, detail: Option<String>, kind: SymbolKind, line: usize) -> DocumentSymbol {
+    DocumentSymbol {
+        name,
+        detail,
+        kind,
+        tags: None,
+        deprecated: None,
+        range: line_range(line),
+        selection_range: line_range(line),
+        children: None,
+    }
+}
+
+/// Push `child` under `parent`, stretching the parent's range to cover it.
+fn adopt(parent: &mut DocumentSymbol, child: DocumentSymbol) {
+    parent.range.end = child.range.end;
+    parent.children.get_or_insert_with(Vec::new).push(child);
+}
+
+fn is_top_level(kind: &LineKind) -> bool {
+    matches!(
+        kind,
+        LineKind::Req { .. }
+            | LineKind::Ent { .. }
+            | LineKind::Mod { .. }
+            | LineKind::DtoDef { .. }
+            | LineKind::TypDef { .. }
+            | LineKind::NonDef { .. }
+    )
+}
+
+/// Last non-blank line in `parsed[from..to]`, falling back to `from`.
+fn last_content_line(parsed: &[ParsedLine], from: usize, to: usize) -> usize {
+    (from..to)
+        .rev()
+        .find(|&i| !matches!(parsed[i].kind, LineKind::Empty))
+        .unwrap_or(from)
+}
+
+fn region(start: usize, end: usize) -> FoldingRange {
+    FoldingRange {
+        start_line: start as u32,
+        start_character: None,
+        end_line: end as u32,
+        end_character: None,
+        kind: Some(FoldingRangeKind::Region),
+        collapsed_text: None,
     }
 }
 
+/// Folds for each `[REQ]` (up to the next top-level block, trailing blank
+/// lines excluded), each `[PLY]` body and `[CSE]` branch inside it, and each
+/// `[DTO]` that spans several lines.
+fn folding_ranges(parsed: &[ParsedLine]) -> Vec<FoldingRange> {
+    let mut ranges = Vec::new();
+    for (i, parsed_line) in parsed.iter().enumerate() {
+        match &parsed_line.kind {
+            LineKind::Req { .. } => {
+                let next = (i + 1..parsed.len()).find(|&j| is_top_level(&parsed[j].kind)).unwrap_or(parsed.len());
+                ranges.push((i, last_content_line(parsed, i, next)));
+            }
+            LineKind::Ply { indent, .. } | LineKind::Cse { indent, .. } => {
+                // A [PLY] body is everything indented deeper. Case steps sit at
+                // the [CSE]'s own indent, so a case runs until the next case or
+                // a shallower line. Either ends at a blank line.
+                let is_cse = matches!(parsed_line.kind, LineKind::Cse { .. });
+                let mut end = i;
+                for (j, l) in parsed.iter().enumerate().skip(i + 1) {
+                    let ends_here = match &l.kind {
+                        LineKind::Empty => true,
+                        LineKind::Cse { .. } if is_cse => l.indent <= *indent,
+                        _ if is_cse => l.indent < *indent,
+                        _ => l.indent <= *indent,
+                    };
+                    if ends_here || is_top_level(&l.kind) {
+                        break;
+                    }
+                    end = j;
+                }
+                ranges.push((i, end));
+            }
+            LineKind::DtoDef { .. } => {
+                let end = (i + 1..parsed.len())
+                    .take_while(|&j| {
+                        matches!(
+                            parsed[j].kind,
+                            LineKind::DtoDesc { .. } | LineKind::DtoProperty { .. } | LineKind::DtoArrayProperty { .. } | LineKind::DtoRef(_)
+                        )
+                    })
+                    .last()
+                    .unwrap_or(i);
+                ranges.push((i, end));
+            }
+            _ => {}
+        }
+    }
+    ranges
+        .into_iter()
+        .filter(|(start, end)| end > start)
+        .map(|(start, end)| region(parsed[start].line_num, parsed[end].line_num))
+        .collect()
+}
+
+/// Outline of a document: each `[REQ]` with its steps (and `[PLY]` → `[CSE]`
+/// → steps) nested underneath, followed by the `[DTO]`/`[TYP]`/`[NON]`
+/// definitions in source order.
+fn document_symbols(lines: &[ParsedLine]) -> Vec<DocumentSymbol> {
+    let mut out: Vec<DocumentSymbol> = Vec::new();
+    let mut req: Option<DocumentSymbol> = None;
+    let mut ply: Option<(DocumentSymbol, usize)> = None;
+    let mut cse: Option<DocumentSymbol> = None;
+
+    fn close_ply(
+        req: &mut Option<DocumentSymbol>,
+        ply: &mut Option<(DocumentSymbol, usize)>,
+        cse: &mut Option<DocumentSymbol>,
+    ) {
+        if let Some((mut p, _)) = ply.take() {
+            if let Some(c) = cse.take() {
+                adopt(&mut p, c);
+            }
+            if let Some(r) = req.as_mut() {
+                adopt(r, p);
+            }
+        }
+    }
+
+    fn close_req(
+        out: &mut Vec<DocumentSymbol>,
+        req: &mut Option<DocumentSymbol>,
+        ply: &mut Option<(DocumentSymbol, usize)>,
+        cse: &mut Option<DocumentSymbol>,
+    ) {
+        close_ply(req, ply, cse);
+        if let Some(r) = req.take() {
+            out.push(r);
+        }
+    }
+
+    for parsed in lines {
+        let line = parsed.line_num;
+        let step = match &parsed.kind {
+            LineKind::Req { noun, verb, input, output, .. } => {
+                close_req(&mut out, &mut req, &mut ply, &mut cse);
+                req = Some(symbol(
+                    format!("{}.{}", noun, verb),
+                    Some(format!("({}): {}", input, output)),
+                    SymbolKind::FUNCTION,
+                    line,
+                ));
+                continue;
+            }
+            LineKind::DtoDef { name, .. } => {
+                close_req(&mut out, &mut req, &mut ply, &mut cse);
+                out.push(symbol(name.clone(), None, SymbolKind::STRUCT, line));
+                continue;
+            }
+            LineKind::TypDef { name, type_name, .. } => {
+                close_req(&mut out, &mut req, &mut ply, &mut cse);
+                out.push(symbol(name.clone(), Some(type_name.clone()), SymbolKind::TYPE_PARAMETER, line));
+                continue;
+            }
+            LineKind::NonDef { name } => {
+                close_req(&mut out, &mut req, &mut ply, &mut cse);
+                out.push(symbol(name.clone(), None, SymbolKind::NAMESPACE, line));
+                continue;
+            }
+            LineKind::Ply { noun, verb, params, output, indent, .. } if req.is_some() => {
+                close_ply(&mut req, &mut ply, &mut cse);
+                ply = Some((
+                    symbol(
+                        format!("[PLY] {}.{}", noun, verb),
+                        Some(format!("({}): {}", params.join(", "), output)),
+                        SymbolKind::INTERFACE,
+                        line,
+                    ),
+                    *indent,
+                ));
+                continue;
+            }
+            LineKind::Cse { name, .. } => {
+                if let Some((p, _)) = ply.as_mut() {
+                    if let Some(c) = cse.take() {
+                        adopt(p, c);
+                    }
+                    cse = Some(symbol(format!("[CSE] {}", name), None, SymbolKind::ENUM_MEMBER, line));
+                }
+                continue;
+            }
+            LineKind::Step { noun, verb, params, output, indent, is_static, .. } => {
+                let sep = if *is_static { "::" } else { "." };
+                (format!("{}{}{}", noun, sep, verb), params, output, *indent)
+            }
+            LineKind::BoundaryStep { prefix, noun, verb, params, output, indent, is_static, .. } => {
+                let sep = if *is_static { "::" } else { "." };
+                (format!("{}{}{}{}", prefix, noun, sep, verb), params, output, *indent)
+            }
+            _ => continue,
+        };
+
+        let (name, params, output, indent) = step;
+        if req.is_none() {
+            continue;
+        }
+        if ply.as_ref().is_some_and(|(_, ply_indent)| indent <= *ply_indent) {
+            close_ply(&mut req, &mut ply, &mut cse);
+        }
+        let child = symbol(
+            name,
+            Some(format!("({}): {}", params.join(", "), output)),
+            SymbolKind::METHOD,
+            line,
+        );
+        match (cse.as_mut(), ply.as_mut(), req.as_mut()) {
+            (Some(c), _, _) => adopt(c, child),
+            (None, Some((p, _)), _) => adopt(p, child),
+            (None, None, Some(r)) => adopt(r, child),
+            _ => {}
+        }
+    }
+    close_req(&mut out, &mut req, &mut ply, &mut cse);
+    out
+}
+
+/// rune.toml for the document's directory, re-read on every call so edits
+/// apply without a restart. Documents without a directory get the defaults.
+fn config_for(uri: &Url) -> std::result::Result<Config, String> {
+    match uri.to_file_path().ok().as_deref().and_then(|p| p.parent()) {
+        Some(dir) => Config::discover(dir),
+        None => Ok(Config::default()),
+    }
+}
+
+/// Hover text when `col` sits on the boundary prefix of `line`
+fn boundary_hover(line: &str, col: usize, config: &Config) -> Option<String> {
+    let start = line.len() - line.trim_start().len();
+    config
+        .boundaries()
+        .into_iter()
+        .find(|(bp, _)| line[start..].starts_with(bp.as_str()) && col <= start + bp.len())
+        .map(|(bp, description)| format!("**{}** {}", bp, description))
+}
+
 #[tokio::main]
 async fn main() {
     let stdin = tokio::io::stdin();
@@ -1308,94 +1569,1015 @@ mod tests {
         );
     }
 
-    // --- [TYP] constraint-modifier validator (design §5) -------------------
+    #[test]
+    fn every_diagnostic_carries_a_known_code() {
+        for path in rune_files("invalid") {
+            let text = std::fs::read_to_string(&path).unwrap();
+            for diag in Backend::compute_diagnostics(&text) {
+                let Some(NumberOrString::String(code)) = &diag.code else {
+                    panic!("{:?}: no code on '{}'", path, diag.message);
+                };
+                assert!(rune_parser::codes::DiagnosticCode::parse(code).is_some(), "unknown code {}", code);
+            }
+        }
+    }
+
+    // --- rename ------------------------------------------------------------
+
+    const RENAME_DOC: &str = "[REQ] user.get(IdDto): UserDto
+    db:user.load(IdDto): UserDto
+      not-found
+
+[DTO] IdDto: id
+    the IdDto lookup key
+[DTO] UserDto: id, name
+    a user
+[TYP] id: string
+    an id
+[TYP] name: string
+    a name
+";
+
+    #[test]
+    fn rename_dto_touches_every_reference() {
+        let edits = rename_edits(&Rope::from_str(RENAME_DOC), &parse_document(RENAME_DOC), Position::new(4, 7), "KeyDto").unwrap().unwrap();
+        let lines: Vec<u32> = edits.iter().map(|e| e.range.start.line).collect();
+        // REQ input, step param, definition — not the description line.
+        assert_eq!(lines, vec![0, 1, 4]);
+        assert!(edits.iter().all(|e| e.new_text == "KeyDto"));
+        assert_eq!(edits[0].range.start.character, 15);
+        assert_eq!(edits[0].range.end.character, 20);
+    }
+
+    #[test]
+    fn rename_does_not_touch_substrings() {
+        // Renaming `id` must leave `IdDto` alone.
+        let edits = rename_edits(&Rope::from_str(RENAME_DOC), &parse_document(RENAME_DOC), Position::new(8, 6), "key").unwrap().unwrap();
+        let lines: Vec<u32> = edits.iter().map(|e| e.range.start.line).collect();
+        assert_eq!(lines, vec![4, 6, 8]);
+    }
 
     #[test]
-    fn typ_modifier_ok_compose() {
-        assert!(validate_typ_modifiers("ext,uuid", "externalId", "string").is_empty());
-        assert!(validate_typ_modifiers("min=0,max=100", "qty", "number").is_empty());
-        assert!(validate_typ_modifiers("nonempty", "name", "string").is_empty());
-        assert!(validate_typ_modifiers("core", "id", "string").is_empty());
-        assert!(validate_typ_modifiers("int", "count", "number").is_empty());
-        assert!(validate_typ_modifiers("positive", "amount", "number").is_empty());
-        assert!(validate_typ_modifiers("example=orders", "tableName", "string").is_empty());
-        assert!(validate_typ_modifiers("ext,example=42", "qty", "number").is_empty());
+    fn rename_rejects_collisions() {
+        let err = rename_edits(&Rope::from_str(RENAME_DOC), &parse_document(RENAME_DOC), Position::new(4, 7), "UserDto").unwrap_err();
+        assert!(err.contains("already defined"));
     }
 
     #[test]
-    fn typ_modifier_unknown() {
-        assert_eq!(
-            validate_typ_modifiers("bogus", "id", "string"),
-            vec!["[TYP] unknown modifier \"bogus\" (allowed: ext, core, uuid, email, url, nonempty, int, min=<n>, max=<n>, positive, example=<value>)".to_string()]
-        );
+    fn rename_and_references_count_utf16_code_units() {
+        // '𝄞' is one char but two UTF-16 units, so `id` starts at unit 19
+        let text = "[DTO] NoteDto: 𝄞, id\n    a note\n[TYP] id: string\n    an id\n";
+        let rope = Rope::from_str(text);
+        let edits = rename_edits(&rope, &parse_document(text), Position::new(0, 19), "key").unwrap().unwrap();
+        let ranges: Vec<_> = edits.iter().map(|e| (e.range.start, e.range.end)).collect();
+        assert_eq!(ranges, vec![(Position::new(0, 19), Position::new(0, 21)), (Position::new(2, 6), Position::new(2, 8))]);
+
+        let uri = Url::parse("file:///tmp/spec.rune").unwrap();
+        let locations = reference_locations(&rope, &parse_document(text), &uri, Position::new(0, 19));
+        assert_eq!(locations[0].range.start, Position::new(0, 19));
     }
 
     #[test]
-    fn typ_modifier_example_needs_value() {
-        assert_eq!(
-            validate_typ_modifiers("example", "tableName", "string"),
-            vec!["[TYP] modifier \"example\" requires a value (e.g. example=orders)".to_string()]
-        );
-        assert_eq!(
-            validate_typ_modifiers("example=", "tableName", "string"),
-            vec!["[TYP] modifier \"example\" requires a value (e.g. example=orders)".to_string()]
-        );
+    fn rename_leaves_doc_blocks_alone() {
+        let text = format!("{}[DOC] Why IdDto\n    IdDto is a lookup key\n", RENAME_DOC);
+        let edits = rename_edits(&Rope::from_str(&text), &parse_document(&text), Position::new(4, 7), "KeyDto").unwrap().unwrap();
+        let lines: Vec<u32> = edits.iter().map(|e| e.range.start.line).collect();
+        assert_eq!(lines, vec![0, 1, 4]);
+
+        let uri = Url::parse("file:///tmp/spec.rune").unwrap();
+        let locations = reference_locations(&Rope::from_str(&text), &parse_document(&text), &uri, Position::new(4, 7));
+        assert!(locations.iter().all(|l| l.range.start.line < 12));
     }
 
     #[test]
-    fn typ_modifier_wrong_base() {
-        assert_eq!(
-            validate_typ_modifiers("uuid", "count", "number"),
-            vec!["[TYP] modifier \"uuid\" requires a string type, but \"count\" is number".to_string()]
-        );
+    fn rename_ignores_non_definitions() {
+        assert!(rename_edits(&Rope::from_str(RENAME_DOC), &parse_document(RENAME_DOC), Position::new(1, 8), "store").unwrap().is_none());
+    }
+
+    // --- document symbols --------------------------------------------------
+
+    #[test]
+    fn document_symbols_nest_steps_and_cases() {
+        let doc = "[REQ] notify.send(NotifyDto): ReceiptDto
+    [NEW] channel
+    channel.prepare(NotifyDto): NotifyDto
+    [PLY] channel.deliver(NotifyDto): ReceiptDto
+        [CSE] email
+        ex:smtp.send(NotifyDto): ReceiptDto
+          timeout
+        [CSE] sms
+        ex:carrier.send(NotifyDto): ReceiptDto
+    [RET] ReceiptDto
+
+[NON] channel
+    a delivery channel
+
+[DTO] NotifyDto: message
+    a message
+
+[TYP] message: string
+    the message
+";
+        let symbols = document_symbols(&parse_document(doc));
+        let names: Vec<&str> = symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["notify.send", "channel", "NotifyDto", "message"]);
+        let kinds: Vec<SymbolKind> = symbols.iter().map(|s| s.kind).collect();
         assert_eq!(
-            validate_typ_modifiers("int", "name", "string"),
-            vec!["[TYP] modifier \"int\" requires a number type, but \"name\" is string".to_string()]
+            kinds,
+            vec![SymbolKind::FUNCTION, SymbolKind::NAMESPACE, SymbolKind::STRUCT, SymbolKind::TYPE_PARAMETER]
         );
+
+        let req = &symbols[0];
+        assert_eq!(req.range.start.line, 0);
+        assert_eq!(req.range.end.line, 8);
+        let children = req.children.as_ref().unwrap();
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0].name, "channel.prepare");
+        assert_eq!(children[0].kind, SymbolKind::METHOD);
+
+        let ply = &children[1];
+        assert_eq!(ply.name, "[PLY] channel.deliver");
+        let cases = ply.children.as_ref().unwrap();
+        let case_names: Vec<&str> = cases.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(case_names, vec!["[CSE] email", "[CSE] sms"]);
+        let email_steps = cases[0].children.as_ref().unwrap();
+        assert_eq!(email_steps[0].name, "ex:smtp.send");
+        assert_eq!(email_steps[0].range.start.line, 5);
+        assert_eq!(cases[1].children.as_ref().unwrap()[0].name, "ex:carrier.send");
+    }
+
+    // --- incremental sync --------------------------------------------------
+
+    fn edit(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: Some(Range {
+                start: Position { line: start.0, character: start.1 },
+                end: Position { line: end.0, character: end.1 },
+            }),
+            range_length: None,
+            text: text.to_string(),
+        }
     }
 
     #[test]
-    fn typ_modifier_bad_value() {
+    fn incremental_edits_apply_in_order() {
+        let mut rope = Rope::from_str("[REQ] user.get(IdDto): UserDto\n    db:user.load(IdDto): UserDto\n");
+        let changes = vec![
+            // rename the verb
+            edit((0, 11), (0, 14), "fetch"),
+            // insert a fault line after the step
+            edit((2, 0), (2, 0), "      not-found\n"),
+            // delete across a line break: join the step onto the REQ line and undo it
+            edit((0, 32), (1, 4), " "),
+            edit((0, 32), (0, 33), "\n    "),
+        ];
+        for change in changes {
+            apply_change(&mut rope, change);
+        }
         assert_eq!(
-            validate_typ_modifiers("min", "qty", "number"),
-            vec!["[TYP] modifier \"min\" requires a numeric value (e.g. min=0)".to_string()]
+            rope.to_string(),
+            "[REQ] user.fetch(IdDto): UserDto\n    db:user.load(IdDto): UserDto\n      not-found\n"
         );
-        assert_eq!(
-            validate_typ_modifiers("max=abc", "qty", "number"),
-            vec!["[TYP] modifier \"max\" requires a numeric value (e.g. min=0)".to_string()]
+    }
+
+    #[test]
+    fn full_change_replaces_document() {
+        let mut rope = Rope::from_str("old text\n");
+        apply_change(
+            &mut rope,
+            TextDocumentContentChangeEvent { range: None, range_length: None, text: "new\n".to_string() },
         );
+        assert_eq!(rope.to_string(), "new\n");
+    }
+
+    #[test]
+    fn positions_count_utf16_code_units() {
+        // 'é' is one UTF-16 unit, '𝄞' is two; LSP columns count units, not chars.
+        let mut rope = Rope::from_str("// 𝄞é x\n");
+        apply_change(&mut rope, edit((0, 5), (0, 6), "y"));
+        assert_eq!(rope.to_string(), "// 𝄞y x\n");
+    }
+
+    // --- cyclic DTOs -------------------------------------------------------
+
+    fn cycle_messages(doc: &str) -> Vec<(u32, String)> {
+        Backend::compute_diagnostics(doc)
+            .into_iter()
+            .filter(|d| d.message.starts_with("Cyclic DTO reference"))
+            .map(|d| (d.range.start.line, d.message))
+            .collect()
+    }
+
+    #[test]
+    fn two_dto_cycle_is_reported_once() {
+        let doc = "[DTO] ADto: BDto
+    an a
+[DTO] BDto: ADto
+    a b
+";
+        assert_eq!(cycle_messages(doc), vec![(2, "Cyclic DTO reference: ADto -> BDto -> ADto".to_string())]);
+    }
+
+    #[test]
+    fn self_reference_is_a_cycle() {
+        let doc = "[DTO] NodeDto: NodeDto
+    a node pointing at itself
+";
+        assert_eq!(cycle_messages(doc), vec![(0, "Cyclic DTO reference: NodeDto -> NodeDto".to_string())]);
     }
 
     #[test]
-    fn typ_modifier_unexpected_value() {
+    fn convention_names_form_cycles_but_not_wrappers() {
+        let doc = "[DTO] OrderDto: customer
+    an order
+[DTO] CustomerDto: order
+    a customer
+
+[DTO] IdDto: id
+    a wrapped id
+";
         assert_eq!(
-            validate_typ_modifiers("uuid=5", "id", "string"),
-            vec!["[TYP] modifier \"uuid\" does not take a value".to_string()]
+            cycle_messages(doc),
+            vec![(2, "Cyclic DTO reference: OrderDto -> CustomerDto -> OrderDto".to_string())]
         );
     }
 
-    // Parity with the TS engine's `^-?\d+(\.\d+)?$` value check: the f64
-    // grammar (exponents, leading +, bare dots) must be REJECTED, and
-    // whitespace around `=` is NOT trimmed (`min = 5` → unknown "min ").
     #[test]
-    fn typ_modifier_value_grammar_matches_engine() {
-        let bad = |raw: &str| {
-            assert_eq!(
-                validate_typ_modifiers(raw, "qty", "number"),
-                vec!["[TYP] modifier \"min\" requires a numeric value (e.g. min=0)".to_string()],
-                "expected bad-value for {raw}"
-            );
+    fn typ_shadows_dto_convention_for_cycles() {
+        // `price` is a [TYP], not a reference back to PriceDto.
+        let doc = "[DTO] PriceDto: price
+    a price
+
+[TYP] price: number
+    an amount
+";
+        assert!(cycle_messages(doc).is_empty());
+    }
+
+    // --- faults ------------------------------------------------------------
+
+    #[test]
+    fn duplicate_fault_under_one_step_warns() {
+        let doc = "[REQ] user.get(IdDto): UserDto
+    db:user.load(IdDto): UserDto
+      not-found timeout
+      not-found
+    user.check(UserDto): UserDto
+      not-found
+";
+        let dupes: Vec<(u32, DiagnosticSeverity)> = Backend::compute_diagnostics(doc)
+            .into_iter()
+            .filter(|d| d.message.starts_with("Duplicate fault"))
+            .map(|d| (d.range.start.line, d.severity.unwrap()))
+            .collect();
+        // the second step's `not-found` is a different step, so not a duplicate
+        assert_eq!(dupes, vec![(3, DiagnosticSeverity::WARNING)]);
+    }
+
+    // --- hover ---------------------------------------------------------------
+
+    fn typ_map(defs: &[(&str, &str)]) -> HashMap<String, (String, Option<String>)> {
+        defs.iter().map(|(n, t)| (n.to_string(), (t.to_string(), None))).collect()
+    }
+
+    #[test]
+    fn typ_hover_resolves_alias_chain() {
+        let defs = typ_map(&[("endpoint", "url"), ("url", "link"), ("link", "string")]);
+        let hover = typ_hover("endpoint", &defs).unwrap();
+        assert_eq!(hover, "**endpoint**: `url` → `link` → `string`");
+        assert_eq!(typ_hover("link", &defs).unwrap(), "**link**: `string`");
+    }
+
+    #[test]
+    fn typ_hover_reports_cycles() {
+        let defs = typ_map(&[("endpoint", "url"), ("url", "endpoint")]);
+        assert_eq!(typ_hover("endpoint", &defs).unwrap(), "**endpoint**: (cyclic)");
+    }
+
+    #[test]
+    fn inlay_hint_shows_what_an_output_alias_resolves_to() {
+        let text = "[REQ] file.save(FileDto): IdDto
+    fs:disk.locate(FileDto): endpoint
+    file.parse(FileDto): name
+
+[TYP] endpoint: url
+    where it lives
+[TYP] url: string
+    a url
+[TYP] name: string
+    a name
+";
+        let parsed = parse_document(text);
+        let hints = output_type_hints(&parsed, 0..=u32::MAX);
+        let labels: Vec<(Position, String)> = hints
+            .into_iter()
+            .map(|h| match h.label {
+                InlayHintLabel::String(label) => (h.position, label),
+                InlayHintLabel::LabelParts(_) => panic!("expected a plain label"),
+            })
+            .collect();
+        assert_eq!(labels, vec![
+            (Position::new(1, 37), "(string)".to_string()),
+            (Position::new(2, 29), "(string)".to_string()),
+        ]);
+        assert!(output_type_hints(&parsed, 3..=9).is_empty());
+    }
+
+    // --- unreachable steps ---------------------------------------------------
+
+    fn unreachable_lines(doc: &str) -> Vec<u32> {
+        Backend::compute_diagnostics(doc)
+            .into_iter()
+            .filter(|d| d.message == "Unreachable step after [RET]")
+            .map(|d| d.range.start.line)
+            .collect()
+    }
+
+    #[test]
+    fn steps_after_top_level_ret_are_unreachable() {
+        let doc = "[REQ] user.get(IdDto): UserDto
+    db:user.load(IdDto): UserDto
+    [RET] UserDto
+    user.audit(UserDto): UserDto
+    db:user.touch(UserDto): UserDto
+
+
+[REQ] user.put(UserDto): UserDto
+    db:user.save(UserDto): UserDto
+";
+        assert_eq!(unreachable_lines(doc), vec![3, 4]);
+    }
+
+    #[test]
+    fn ret_in_case_only_ends_its_branch() {
+        let doc = "[REQ] notify.send(NotifyDto): ReceiptDto
+    [PLY] channel.deliver(NotifyDto): ReceiptDto
+        [CSE] email
+        ex:smtp.send(NotifyDto): ReceiptDto
+        [RET] ReceiptDto
+        ex:smtp.retry(NotifyDto): ReceiptDto
+        [CSE] sms
+        ex:carrier.send(NotifyDto): ReceiptDto
+    channel.log(ReceiptDto): ReceiptDto
+";
+        assert_eq!(unreachable_lines(doc), vec![5]);
+    }
+
+    // --- scope completion ----------------------------------------------------
+
+    #[test]
+    fn completion_offers_prior_step_outputs_in_parens() {
+        let doc = "[REQ] recording.register(GetRecordingDto): IdDto
+    id::create(providerName): id
+    db:x.set()
+
+[DTO] GetRecordingDto: providerName, url(s)
+    the request
+";
+        let parsed = parse_document(doc);
+        let items = scope_completions(&parsed, 2);
+        let labels: Vec<&str> = items.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(labels, vec!["GetRecordingDto", "providerName", "urls", "id"]);
+        assert!(items.iter().all(|i| i.kind == Some(CompletionItemKind::VARIABLE)));
+        assert!(items.iter().all(|i| i.detail.as_deref() == Some("in scope")));
+    }
+
+    #[test]
+    fn completion_offers_dto_fields_after_a_dot() {
+        let doc = "[REQ] recording.save(IdDto): IdDto
+    db:recording.load(IdDto): RecordingDto
+    db:recording.store(recording.
+
+[DTO] RecordingDto: id, name
+    a stored recording
+";
+        let parsed = parse_document(doc);
+        let items = property_completions(&parsed, 2, "    db:recording.store(recording.");
+        let labels: Vec<&str> = items.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(labels, vec!["id", "name"]);
+        assert!(items.iter().all(|i| i.kind == Some(CompletionItemKind::FIELD)));
+        // Not in scope before the step that returns it
+        assert!(property_completions(&parsed, 1, "    db:recording.load(recording.").is_empty());
+        // A noun followed by a dot is not a DTO
+        assert!(property_completions(&parsed, 2, "    db:user.").is_empty());
+    }
+
+    #[test]
+    fn completion_offers_union_members_after_cse() {
+        let doc = "[REQ] call.get(CallDto): DataDto
+    [PLY] provider.get(CallDto): DataDto
+        [CSE] genie
+        ex:genie.fetch(CallDto): DataDto
+        [CSE] 
+
+[TYP] provider: \"genie\" | \"fiveNine\"
+    the provider
+";
+        let parsed = parse_document(doc);
+        let items = case_completions(&parsed, 4, "        [CSE] ");
+        let labels: Vec<&str> = items.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(labels, vec!["genie", "fiveNine"]);
+        assert!(items.iter().all(|i| i.kind == Some(CompletionItemKind::ENUM_MEMBER)));
+        // Only right after the tag, and only inside the block
+        assert!(case_completions(&parsed, 4, "        [CSE]").is_empty());
+        assert!(case_completions(&parsed, 4, "    [RET] ").is_empty());
+        assert!(case_completions(&parsed, 6, "[CSE] ").is_empty());
+    }
+
+    #[test]
+    fn block_snippets_on_empty_lines() {
+        let req = snippet_completions("", 0);
+        assert_eq!(req.len(), 1);
+        assert_eq!(req[0].label, "[REQ] block");
+        assert_eq!(req[0].insert_text_format, Some(InsertTextFormat::SNIPPET));
+        assert!(req[0].insert_text.as_deref().unwrap().starts_with("[REQ] ${1:noun}.${2:verb}(${3:InputDto}): ${4:OutputDto}\n"));
+
+        let ply = snippet_completions("    ", 4);
+        assert_eq!(ply[0].label, "[PLY] block");
+        assert!(ply[0].insert_text.as_deref().unwrap().contains("\n        [CSE] ${5:case}"));
+
+        assert!(snippet_completions("[R", 0).is_empty());
+        assert!(snippet_completions("  ", 2).is_empty());
+    }
+
+    #[test]
+    fn scope_drops_outputs_of_other_cases() {
+        let doc = "[REQ] notify.send(NotifyDto): ReceiptDto
+    [PLY] channel.deliver(NotifyDto): ReceiptDto
+        [CSE] email
+        smtp.render(NotifyDto): body
+        [CSE] sms
+        ex:carrier.send()
+";
+        let parsed = parse_document(doc);
+        assert_eq!(scope_at(&parsed, 5), vec!["NotifyDto", "ReceiptDto"]);
+        assert_eq!(scope_at(&parsed, 4), vec!["NotifyDto", "ReceiptDto", "body"]);
+    }
+
+    // --- folding -------------------------------------------------------------
+
+    #[test]
+    fn folds_reqs_plys_cases_and_dtos() {
+        let doc = "[REQ] user.get(IdDto): UserDto
+    db:user.load(IdDto): UserDto
+      not-found
+
+
+[REQ] notify.send(NotifyDto): ReceiptDto
+    [PLY] channel.deliver(NotifyDto): ReceiptDto
+        [CSE] email
+        ex:smtp.send(NotifyDto): ReceiptDto
+          timeout
+        [CSE] sms
+        ex:carrier.send(NotifyDto): ReceiptDto
+    [RET] ReceiptDto
+
+[DTO] NotifyDto: message
+    a message to deliver
+    across two lines
+[TYP] message: string
+";
+        let ranges: Vec<(u32, u32)> = folding_ranges(&parse_document(doc)).iter().map(|r| (r.start_line, r.end_line)).collect();
+        assert_eq!(ranges, vec![(0, 2), (5, 12), (6, 11), (7, 9), (10, 11), (14, 16)]);
+        assert!(folding_ranges(&parse_document(doc)).iter().all(|r| r.kind == Some(FoldingRangeKind::Region)));
+    }
+
+    // --- line length ---------------------------------------------------------
+
+    #[test]
+    fn line_length_follows_config() {
+        let doc = format!("// {}\n", "x".repeat(87));
+        let config = Config::from_toml("max_line_length = 100").unwrap();
+        assert!(Backend::compute_diagnostics_with(&doc, &config, &SymbolTable::default()).is_empty());
+        let diags = Backend::compute_diagnostics(&doc);
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].message, "Line exceeds 80 columns (90 chars)");
+    }
+
+    #[test]
+    fn line_length_counts_chars_not_bytes() {
+        let doc = format!("// {}\n", "é".repeat(77));
+        assert!(Backend::compute_diagnostics(&doc).is_empty());
+    }
+
+    // --- array types ---------------------------------------------------------
+
+    #[test]
+    fn boundary_step_may_return_dto_array() {
+        let doc = "[REQ] search.run(QueryDto): FooDto
+    ex:api.list(QueryDto): FooDto[]
+    db:cache.keep(FooDto): Array<FooDto>
+    foo.first(FooDto): FooDto
+
+[DTO] QueryDto: query
+    a query
+[DTO] FooDto: query
+    a result
+[TYP] query: string
+    the query
+";
+        let boundary: Vec<String> = Backend::compute_diagnostics(doc)
+            .into_iter()
+            .map(|d| d.message)
+            .filter(|m| m.contains("boundary"))
+            .collect();
+        assert!(boundary.is_empty(), "{:?}", boundary);
+
+        let parsed = parse_document(doc);
+        assert_eq!(scope_at(&parsed, 3), vec!["QueryDto", "query", "FooDto"]);
+    }
+
+    // --- workspace -----------------------------------------------------------
+
+    #[tokio::test]
+    async fn open_siblings_use_their_cached_parse() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let dir = tempfile::tempdir().unwrap();
+        let spec = dir.path().join("flows.rune");
+        let sibling = dir.path().join("types.rune");
+        std::fs::write(&sibling, "[TYP] name: string\n    a name\n").unwrap();
+
+        // The unsaved buffer wins over what is on disk, without a second parse
+        let uri = Url::from_file_path(&sibling).unwrap();
+        let open = backend.load(&uri, Rope::from_str("[DTO] AddressDto: name\n    an address\n"));
+        backend.documents.write().await.insert(uri, open);
+        let table = backend.sibling_symbols(&spec, dir.path()).await;
+        assert!(table.dtos.contains("AddressDto"));
+        assert!(!table.types.contains_key("name"));
+        assert_eq!(backend.parses.load(std::sync::atomic::Ordering::Relaxed), 1);
+
+        // Closed ones come from disk
+        std::fs::write(dir.path().join("ids.rune"), "[TYP] id: string\n    an id\n").unwrap();
+        let table = backend.sibling_symbols(&spec, dir.path()).await;
+        assert_eq!(table.types.get("id").map(String::as_str), Some("string"));
+    }
+
+    #[test]
+    fn sibling_definitions_resolve_references() {
+        let flows = "[DTO] UserDto: name, address
+    a user
+";
+        let unresolved = |siblings: &SymbolTable| {
+            Backend::compute_diagnostics_with(flows, &Config::default(), siblings)
+                .into_iter()
+                .filter(|d| d.message.contains("has no [TYP] or [DTO]"))
+                .count()
         };
-        bad("min=1e3");
-        bad("min=+5");
-        bad("min=.5");
-        bad("min=5.");
-        bad("min=");
-        assert!(validate_typ_modifiers("min=-3", "qty", "number").is_empty());
-        assert!(validate_typ_modifiers("min=1.25", "qty", "number").is_empty());
-        assert_eq!(
-            validate_typ_modifiers("min = 5", "qty", "number"),
-            vec!["[TYP] unknown modifier \"min \" (allowed: ext, core, uuid, email, url, nonempty, int, min=<n>, max=<n>, positive, example=<value>)".to_string()]
-        );
+        assert_eq!(unresolved(&SymbolTable::default()), 2);
+
+        let siblings = SymbolTable::from_lines(&parse_document(
+            "[TYP] name: string\n    a name\n\n[DTO] AddressDto: name\n    an address\n",
+        ));
+        assert_eq!(unresolved(&siblings), 0);
+    }
+
+    #[test]
+    fn goto_noun_prefers_non_declaration() {
+        let text = "[REQ] file.save(FileDto): IdDto\n    db:storage.save(FileDto): IdDto\n\n[NON] storage\n    where files live\n";
+        let line_of = |text: &str, word| {
+            definition_line(&Rope::from_str(text), &parse_document(text), word).map(|(kind, range)| (kind, range.start.line))
+        };
+        assert_eq!(line_of(text, "storage"), Some(("NON", 3)));
+
+        let without_non = "[REQ] file.save(FileDto): IdDto\n    db:storage.save(FileDto): IdDto\n    storage.index(IdDto): IdDto\n";
+        assert_eq!(line_of(without_non, "storage"), Some(("noun", 1)));
+    }
+
+    #[test]
+    fn goto_range_covers_just_the_name() {
+        let text = "[REQ] file.save(FileDto): IdDto\n    db:storage.save(FileDto): IdDto\n\n[DTO] FileDto: name\n    a file\n";
+        let (rope, parsed) = (Rope::from_str(text), parse_document(text));
+        let (kind, range) = definition_line(&rope, &parsed, "FileDto").unwrap();
+        assert_eq!(kind, "DTO");
+        assert_eq!((range.start, range.end), (Position::new(3, 6), Position::new(3, 13)));
+
+        let (_, range) = definition_line(&rope, &parsed, "storage").unwrap();
+        assert_eq!((range.start, range.end), (Position::new(1, 7), Position::new(1, 14)));
+    }
+
+    #[test]
+    fn mixed_purity_noun_warns_when_enabled() {
+        let text = "[REQ] report.build(IdDto): ReportDto\n    db:report.load(IdDto): ReportDto\n    report.compute(ReportDto): ReportDto\n    [RET] ReportDto\n";
+        let config = Config { warn_mixed_purity: true, ..Config::default() };
+        let mixed: Vec<_> = Backend::compute_diagnostics_with(text, &config, &SymbolTable::default())
+            .into_iter()
+            .filter(|d| d.message.contains("mixes boundary and pure"))
+            .collect();
+        assert_eq!(mixed.len(), 2);
+        assert!(mixed.iter().all(|d| d.severity == Some(DiagnosticSeverity::WARNING)));
+        assert_eq!(mixed.iter().map(|d| d.range.start.line).collect::<HashSet<_>>(), HashSet::from([1, 2]));
+
+        // Off by default
+        assert!(!Backend::compute_diagnostics(text).iter().any(|d| d.message.contains("mixes boundary")));
+    }
+
+    #[test]
+    fn fs_path_param_warns_when_enabled() {
+        let text = "[REQ] file.read(IdDto): DataDto\n    id::create(IdDto): id\n    fs:disk.read(id): data\n    fs:disk.load(path): data\n    [RET] DataDto\n\n[TYP] path: string\n    a file path\n\n[TYP] data: string\n    file contents\n";
+        let config = Config { warn_fs_path_params: true, ..Config::default() };
+        let warnings: Vec<_> = Backend::compute_diagnostics_with(text, &config, &SymbolTable::default())
+            .into_iter()
+            .filter(|d| d.message == "fs: path parameter should be a string")
+            .collect();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].range.start.line, 2);
+        assert_eq!(warnings[0].severity, Some(DiagnosticSeverity::WARNING));
+
+        // Off by default
+        assert!(!Backend::compute_diagnostics(text).iter().any(|d| d.message.starts_with("fs: path")));
+    }
+
+    #[test]
+    fn custom_boundary_prefix_parses_and_hovers() {
+        let config = Config::from_toml("[boundaries]\ncache = \"caching layer\"\n").unwrap();
+        let text = "[REQ] session.get(IdDto): SessionDto\n    cache:session.load(IdDto): SessionDto\n";
+        let lines = config.parse(text);
+        assert!(matches!(&lines[1].kind, LineKind::BoundaryStep { prefix, .. } if prefix == "cache:"));
+        assert!(!Backend::compute_diagnostics_with(text, &config, &SymbolTable::default())
+            .iter()
+            .any(|d| d.message.contains("cache")));
+
+        let line = "    cache:session.load(IdDto): SessionDto";
+        assert_eq!(boundary_hover(line, 6, &config).as_deref(), Some("**cache:** caching layer"));
+        assert_eq!(boundary_hover("    db:user.load(IdDto): UserDto", 5, &config).as_deref(), Some("**db:** database / persistence"));
+        assert!(boundary_hover(line, 20, &config).is_none());
+    }
+
+    #[test]
+    fn nested_dto_properties_resolve_through_their_type() {
+        let text = "[DTO] FooDto:\n    a foo\n    name: string\n    tags: tag(s)\n    owner: owner\n\n[TYP] tag: string\n    a label\n";
+        let undefined: Vec<_> = Backend::compute_diagnostics(text)
+            .into_iter()
+            .filter(|d| d.code == Some(NumberOrString::String("REF001".to_string())))
+            .collect();
+        assert_eq!(undefined.len(), 1);
+        assert_eq!(undefined[0].range.start.line, 4);
+    }
+
+    #[test]
+    fn req_output_never_produced_is_reported_on_the_req() {
+        let text = "[REQ] user.get(IdDto): UserDto\n    db:user.load(IdDto): RowDto\n    row.check(RowDto): RowDto\n";
+        let diags = Backend::compute_diagnostics(text);
+        let missing: Vec<_> = diags.iter().filter(|d| d.message == "No step produces the required output 'UserDto'").collect();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].range.start.line, 0);
+        // The last-step check still fires on the last step
+        assert!(diags.iter().any(|d| d.range.start.line == 2 && d.message.starts_with("Last step must return 'UserDto'")));
+
+        // Produced mid-flow: only the last-step check applies
+        let text = "[REQ] user.get(IdDto): UserDto\n    db:user.load(IdDto): UserDto\n    user.check(UserDto): RowDto\n";
+        assert!(!Backend::compute_diagnostics(text).iter().any(|d| d.message.starts_with("No step produces")));
+    }
+
+    #[test]
+    fn quick_fix_toggles_static_separator() {
+        let uri = Url::parse("file:///tmp/spec.rune").unwrap();
+        let text = "[REQ] file.save(FileDto): IdDto\n    db:x.foo(FileDto): x\n    x::bar(x): IdDto\n    y.baz(x): IdDto\n";
+
+        // `y` is not in scope: `.` becomes `::`
+        let actions = static_call_actions(&Rope::from_str(text), &parse_document(text), &uri, 3);
+        assert_eq!(actions.len(), 1);
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else { panic!("expected a code action") };
+        let edits = &action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(edits[0].new_text, "::");
+        assert_eq!(edits[0].range.start, Position { line: 3, character: 5 });
+        assert_eq!(edits[0].range.end, Position { line: 3, character: 6 });
+
+        // `x` is in scope, so the static call becomes an instance call
+        let actions = static_call_actions(&Rope::from_str(text), &parse_document(text), &uri, 2);
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else { panic!("expected a code action") };
+        let edits = &action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(edits[0].new_text, ".");
+        assert_eq!(edits[0].range.end.character - edits[0].range.start.character, 2);
+
+        // Only steps get the fix
+        assert!(static_call_actions(&Rope::from_str(text), &parse_document(text), &uri, 0).is_empty());
+
+        // The range is in UTF-16 units: '𝄞' in the boundary prefix takes two
+        let text = "[REQ] file.save(FileDto): IdDto\n    d𝄞:y.baz(FileDto): IdDto\n";
+        let actions = static_call_actions(&Rope::from_str(text), &parse_document(text), &uri, 1);
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else { panic!("expected a code action") };
+        let edits = &action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!((edits[0].range.start, edits[0].range.end), (Position::new(1, 9), Position::new(1, 10)));
+    }
+
+    #[test]
+    fn references_match_whole_words_only() {
+        let uri = Url::parse("file:///tmp/spec.rune").unwrap();
+        let text = "[REQ] id.check(IdDto): Id\n    id::parse(IdDto): Id // an Id\n    id.valid(Id, Id): Id\n\n[TYP] Id: string\n    an Id\n";
+        let locations = reference_locations(&Rope::from_str(text), &parse_document(text), &uri, Position::new(4, 6));
+        let found: Vec<(u32, u32)> = locations.iter().map(|l| (l.range.start.line, l.range.start.character)).collect();
+        // Never inside IdDto, comments or descriptions; every occurrence on a line
+        assert_eq!(found, vec![(0, 23), (1, 22), (2, 13), (2, 17), (2, 22), (4, 6)]);
+        assert!(locations.iter().all(|l| l.range.end.character - l.range.start.character == 2));
+    }
+
+    #[test]
+    fn dto_param_must_be_in_scope_when_strict() {
+        let text = "[REQ] user.update(UpdateUserDto): UserDto\n    db:user.save(UserDto): UserDto\n    user.check(UpdateUserDto): UserDto\n\n[DTO] UpdateUserDto: name\n    changes\n\n[DTO] UserDto: name\n    a user\n\n[TYP] name: string\n    a name\n";
+        let config = Config { strict_dto_params: true, ..Config::default() };
+        let errors: Vec<_> = Backend::compute_diagnostics_with(text, &config, &SymbolTable::default())
+            .into_iter()
+            .filter(|d| d.code == Some(NumberOrString::String("STP004".to_string())))
+            .collect();
+        // UserDto is defined but nothing has produced it yet; the REQ input is fine
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].range.start.line, 1);
+        assert_eq!(errors[0].severity, Some(DiagnosticSeverity::ERROR));
+
+        // Off by default
+        assert!(!Backend::compute_diagnostics(text).iter().any(|d| d.message.contains("not in scope")));
+    }
+
+    #[test]
+    fn tab_indentation_is_an_error() {
+        let text = "[REQ] user.get(IdDto): UserDto\n\tdb:user.load(IdDto): UserDto\n  \t\tuser.check(UserDto): UserDto\n";
+        let tabs: Vec<_> = Backend::compute_diagnostics(text)
+            .into_iter()
+            .filter(|d| d.message == "Use spaces for indentation, tabs are not allowed")
+            .collect();
+        assert_eq!(tabs.len(), 2);
+        assert_eq!((tabs[0].range.start, tabs[0].range.end), (Position { line: 1, character: 0 }, Position { line: 1, character: 1 }));
+        assert_eq!((tabs[1].range.start, tabs[1].range.end), (Position { line: 2, character: 2 }, Position { line: 2, character: 4 }));
+        assert_eq!(tabs[0].severity, Some(DiagnosticSeverity::ERROR));
+    }
+
+    #[test]
+    fn noun_summary_lists_methods_and_purity() {
+        let text = "[REQ] user.get(IdDto): UserDto\n    user::parse(IdDto): id\n    db:user.load(id): UserDto\n    db:user.load(id): UserDto\n";
+        let summary = noun_summary(&parse_document(text), "user").unwrap();
+        assert!(summary.starts_with("**User** (noun, impure: db:)"));
+        assert!(summary.contains("- `::parse(IdDto): id`"));
+        assert!(summary.contains("- `.load(id): UserDto`"));
+        assert_eq!(summary.matches(".load(").count(), 1);
+
+        // Only called statically, still a pure noun
+        let text = "[REQ] x.run(IdDto): IdDto\n    id::create(IdDto): IdDto\n";
+        assert_eq!(noun_summary(&parse_document(text), "id").unwrap(), "**Id** (noun, pure)\n\n- `::create(IdDto): IdDto`");
+        assert!(noun_summary(&parse_document(text), "nobody").is_none());
+    }
+
+    #[test]
+    fn crlf_document_has_no_phantom_diagnostics() {
+        let text = "[DTO] FooDto: a\r\n    desc\r\n\r\n[TYP] a: string\r\n    an a\r";
+        let diags = Backend::compute_diagnostics(text);
+        assert!(diags.is_empty(), "{:?}", diags);
+
+        // Exactly 80 columns before the `\r` is still within the limit
+        let text = format!("// {}\r\n", "x".repeat(77));
+        assert!(Backend::compute_diagnostics(&text).is_empty());
+    }
+
+    #[test]
+    fn void_req_needs_no_output_step() {
+        let text = "[REQ] log.write(EntryDto): void\n    db:log.append(EntryDto): IdDto\n\n[DTO] EntryDto: id\n    a log entry\n\n[DTO] IdDto: id\n    an id\n\n[TYP] id: string\n    the id\n";
+        let diags = Backend::compute_diagnostics(text);
+        assert!(diags.is_empty(), "{:?}", diags);
+
+        let diags = Backend::compute_diagnostics("[REQ] log.write(EntryDto): string\n");
+        assert!(diags.iter().any(|d| d.message == "REQ output must be a DTO or void, got 'string'"));
+    }
+
+    #[test]
+    fn warns_on_a_case_without_steps() {
+        let text = "[REQ] notify.send(NotifyDto): ReceiptDto
+    [PLY] channel.deliver(NotifyDto): ReceiptDto
+        [CSE] email
+        ex:smtp.send(NotifyDto): ReceiptDto
+        [CSE] sms
+        [CSE] push
+        // not yet
+";
+        let diags = Backend::compute_diagnostics(text);
+        let empty: Vec<_> = diags
+            .iter()
+            .filter(|d| d.code == Some(NumberOrString::String("PLY003".to_string())))
+            .map(|d| (d.range.start.line, d.message.as_str(), d.severity))
+            .collect();
+        assert_eq!(empty, vec![
+            (4, "Case 'sms' has no steps", Some(DiagnosticSeverity::WARNING)),
+            (5, "Case 'push' has no steps", Some(DiagnosticSeverity::WARNING)),
+        ]);
+    }
+
+    #[test]
+    fn repeated_diagnostics_are_published_once() {
+        let text = "[REQ] user.get(IdDto): UserDto\n    user.check(IdDto): UserDto\n      oops oops\n";
+        let config = Config { faults: Some(Vec::new()), ..Config::default() };
+        let diagnostics = Backend::compute_diagnostics_with(text, &config, &SymbolTable::default());
+        let unknown = |ds: &[Diagnostic]| ds.iter().filter(|d| d.message.starts_with("Unknown fault 'oops'")).count();
+        assert_eq!(unknown(&diagnostics), 2);
+        let deduped = dedup_diagnostics(diagnostics.clone());
+        assert_eq!(unknown(&deduped), 1);
+        // Everything else survives, in order
+        let others: Vec<_> = diagnostics.iter().filter(|d| !d.message.starts_with("Unknown fault")).collect();
+        let kept: Vec<_> = deduped.iter().filter(|d| !d.message.starts_with("Unknown fault")).collect();
+        assert_eq!(others, kept);
+    }
+
+    #[test]
+    fn diagnostics_point_at_the_offending_token() {
+        let text = "[REQ] user.get(IdDto): UserDto\n    db:user.load(IdDto): RowDto\n    db:user.load(UserDto): RowDto\n\n[DTO] User: id\n    a user\n";
+        let diags = Backend::compute_diagnostics(text);
+        let range_of = |prefix: &str| diags.iter().find(|d| d.message.starts_with(prefix)).map(|d| d.range).unwrap();
+        // The verb of the call that disagrees with the first `user.load`
+        assert_eq!(range_of("Inconsistent signature"), Range {
+            start: Position { line: 2, character: 12 },
+            end: Position { line: 2, character: 16 },
+        });
+        // The output of the last step
+        assert_eq!(range_of("Last step must return").start, Position { line: 2, character: 27 });
+        // The DTO name
+        assert_eq!(range_of("DTO name 'User'"), Range {
+            start: Position { line: 4, character: 6 },
+            end: Position { line: 4, character: 10 },
+        });
+    }
+
+    #[test]
+    fn diagnostic_ranges_count_utf16_code_units() {
+        // '𝄞' is one char but two UTF-16 units, so column 80 is unit 81
+        let text = format!("// 𝄞{}\n", "x".repeat(82));
+        let diags = Backend::compute_diagnostics(&text);
+        let too_long = diags.iter().find(|d| d.message.starts_with("Line exceeds")).unwrap();
+        assert_eq!((too_long.range.start, too_long.range.end), (Position::new(0, 81), Position::new(0, 87)));
+    }
+
+    #[test]
+    fn signature_help_tracks_active_parameter() {
+        let text = "[REQ] x.run(RunDto): OutDto\n    x.foo(a, b): OutDto\n    x.foo(a, \n";
+        let help = signature_help_at(&Rope::from_str(text), &parse_document(text), Position::new(2, 13)).unwrap();
+        assert_eq!(help.active_parameter, Some(1));
+        assert_eq!(help.signatures[0].label, "x.foo(a, b): OutDto");
+        let params = help.signatures[0].parameters.as_ref().unwrap();
+        assert!(matches!(params[1].label, ParameterLabel::LabelOffsets([9, 10])));
+
+        // Boundary prefixes are ignored when matching the callee
+        let text = "[REQ] x.run(RunDto): OutDto\n    db:x.foo(a, b): OutDto\n    db:x.foo(\n";
+        assert_eq!(signature_help_at(&Rope::from_str(text), &parse_document(text), Position::new(2, 13)).unwrap().active_parameter, Some(0));
+
+        // Outside any parentheses there is nothing to show
+        assert!(signature_help_at(&Rope::from_str(text), &parse_document(text), Position::new(1, 2)).is_none());
+
+        // The cursor is in UTF-16 units: right after '𝄞' is still the first parameter
+        let text = "[REQ] x.run(RunDto): OutDto\n    x.foo(a, b): OutDto\n    x.foo(𝄞, b\n";
+        assert_eq!(signature_help_at(&Rope::from_str(text), &parse_document(text), Position::new(2, 12)).unwrap().active_parameter, Some(0));
+    }
+
+    #[test]
+    fn class_type_never_constructed_warns() {
+        let unused = "[REQ] file.save(FileDto): IdDto\n    metadata.set(FileDto): IdDto\n\n[TYP] metadata: Class\n    file metadata\n";
+        assert!(!Backend::compute_diagnostics(unused).iter().any(|d| d.message.starts_with("Class type")));
+
+        let config = Config { warn_unconstructed_classes: true, ..Config::default() };
+        let diagnostics = |text: &str| Backend::compute_diagnostics_with(text, &config, &SymbolTable::default());
+        let warnings: Vec<_> = diagnostics(unused)
+            .into_iter()
+            .filter(|d| d.message.starts_with("Class type 'metadata'"))
+            .collect();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].range.start.line, 3);
+
+        let constructed = "[REQ] file.save(FileDto): IdDto\n    [NEW] metadata\n    metadata.set(FileDto): IdDto\n\n[TYP] metadata: Class\n    file metadata\n";
+        assert!(!diagnostics(constructed).iter().any(|d| d.message.starts_with("Class type")));
+
+        let static_only = "[REQ] file.save(FileDto): IdDto\n    metadata::from(FileDto): IdDto\n\n[TYP] metadata: Class\n    file metadata\n";
+        assert!(!diagnostics(static_only).iter().any(|d| d.message.starts_with("Class type")));
+    }
+
+    #[test]
+    fn cse_names_match_union_type() {
+        let text = "[REQ] call.fetch(CallDto): DataDto\n    [PLY] provider.get(CallDto): DataDto\n        [CSE] genie\n        ex:genie.fetch(CallDto): DataDto\n        [CSE] custom\n        ex:custom.fetch(CallDto): DataDto\n\n[TYP] provider: \"genie\" | \"fiveNine\"\n    who serves the call\n";
+        let diags = Backend::compute_diagnostics(text);
+        let not_member: Vec<_> = diags.iter().filter(|d| d.message.starts_with("[CSE] custom is not a member")).collect();
+        assert_eq!(not_member.len(), 1);
+        assert_eq!(not_member[0].range.start.line, 4);
+        assert_eq!(not_member[0].severity, Some(DiagnosticSeverity::ERROR));
+        let missing: Vec<_> = diags.iter().filter(|d| d.message.contains("no [CSE] for union member")).collect();
+        assert_eq!(missing.len(), 1);
+        assert!(missing[0].message.contains("'fiveNine'"));
+        assert_eq!(missing[0].range.start.line, 1);
+        assert_eq!(missing[0].severity, Some(DiagnosticSeverity::WARNING));
+    }
+
+    #[tokio::test]
+    async fn malformed_rune_toml_is_reported() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("rune.toml"), "max_line_length = \"120\"\n").unwrap();
+        let uri = Url::from_file_path(dir.path().join("spec.rune")).unwrap();
+
+        let doc = service.inner().load(&uri, Rope::from_str("[TYP] id: string\n    an id\n"));
+        assert_eq!(doc.config, Config::default());
+        let diagnostics = Backend::document_diagnostics(&doc, &SymbolTable::default());
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].message.starts_with("Invalid rune config "), "{}", diagnostics[0].message);
+        assert!(diagnostics[0].message.ends_with("(using the default rules)"));
+    }
+
+    #[tokio::test]
+    async fn handlers_share_one_parse_per_change() {
+        // Only handlers that stay quiet towards the client: nothing drains
+        // `_socket`, so a log message would block.
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let uri = Url::parse("file:///tmp/rune-cache-test/spec.rune").unwrap();
+        let at = |line, character| TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            position: Position { line, character },
+        };
+
+        backend
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem::new(uri.clone(), "rune".to_string(), 1, RENAME_DOC.to_string()),
+            })
+            .await;
+        assert_eq!(backend.parses.load(std::sync::atomic::Ordering::Relaxed), 1);
+
+        let hover = backend
+            .hover(HoverParams { text_document_position_params: at(4, 8), work_done_progress_params: Default::default() })
+            .await
+            .unwrap();
+        assert!(hover.is_some());
+        let references = backend
+            .references(ReferenceParams {
+                text_document_position: at(4, 8),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+                context: ReferenceContext { include_declaration: true },
+            })
+            .await
+            .unwrap();
+        assert!(references.is_some_and(|r| r.len() > 1));
+        backend
+            .completion(CompletionParams {
+                text_document_position: at(1, 4),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+                context: None,
+            })
+            .await
+            .unwrap();
+        let document = || TextDocumentIdentifier { uri: uri.clone() };
+        let symbols = backend
+            .document_symbol(DocumentSymbolParams {
+                text_document: document(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await
+            .unwrap();
+        assert!(symbols.is_some());
+        backend
+            .folding_range(FoldingRangeParams {
+                text_document: document(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await
+            .unwrap();
+        backend
+            .signature_help(SignatureHelpParams {
+                text_document_position_params: at(1, 17),
+                work_done_progress_params: Default::default(),
+                context: None,
+            })
+            .await
+            .unwrap();
+        let rename = backend
+            .rename(RenameParams {
+                text_document_position: at(4, 8),
+                new_name: "KeyDto".to_string(),
+                work_done_progress_params: Default::default(),
+            })
+            .await
+            .unwrap();
+        assert!(rename.is_some());
+        backend
+            .code_action(CodeActionParams {
+                text_document: document(),
+                range: Range::new(Position::new(1, 4), Position::new(1, 4)),
+                context: Default::default(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(backend.parses.load(std::sync::atomic::Ordering::Relaxed), 1);
+
+        backend
+            .did_change(DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier::new(uri.clone(), 2),
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range: None,
+                    range_length: None,
+                    text: format!("{}// edited\n", RENAME_DOC),
+                }],
+            })
+            .await;
+        assert_eq!(backend.parses.load(std::sync::atomic::Ordering::Relaxed), 2);
+        let edited = backend.with_parsed(&uri, |doc| doc.parsed.len()).await;
+        assert_eq!(edited, Some(RENAME_DOC.lines().count() + 1));
     }
 }
@@ -0,0 +1,62 @@
+//! `parse_document` over a large synthetic spec: `cargo bench -p rune-parser`
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use rune_parser::parse_document;
+
+/// `count` requirements, each with pure, boundary and multi-line steps,
+/// faults, a polymorphic block and the DTOs and types they use
+fn synthetic_spec(count: usize) -> String {
+    let mut spec = String::new();
+    for i in 0..count {
+        spec.push_str(&format!(
+            "[REQ] order{i}.place(PlaceOrder{i}Dto): Receipt{i}Dto
+    order{i}::create(items, customer): order{i}
+    order{i}.price(discount): total  // applies promotions
+    db:order{i}.save(
+      order{i},
+      total
+    ): Receipt{i}Dto
+      not-found timed-out
+      conflict
+    [PLY] order{i}.ship(address): shipment
+      [CSE] express
+        courier::book(address): shipment
+      [CSE] standard
+        post::queue(address): shipment
+    [RET] Receipt{i}Dto
+
+[DTO] PlaceOrder{i}Dto: items, customer, discount, address
+    what the customer asks for
+[DTO] Receipt{i}Dto: total, shipment
+    what the customer gets back
+
+"
+        ));
+    }
+    spec.push_str(
+        "[TYP] items: string[]
+    line items
+[TYP] customer: string
+    who is ordering
+[TYP] discount: number
+    percentage off
+[TYP] address: string
+    where it goes
+[TYP] total: number
+    amount charged
+[TYP] shipment: string
+    tracking id
+",
+    );
+    spec
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let spec = synthetic_spec(500);
+    c.bench_function("parse_document/500 requirements", |b| b.iter(|| parse_document(black_box(&spec))));
+}
+
+criterion_group!(benches, bench_parse);
+criterion_main!(benches);
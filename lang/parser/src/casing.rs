@@ -0,0 +1,97 @@
+//! Case conversions for identifiers taken from .rune specs
+
+/// Split an identifier into words on `_`, `-`, spaces and case changes.
+/// An acronym stays one word ("HTTPServer" -> HTTP, Server) and digits stick
+/// to the word before them ("base64Url" -> base64, Url). Pascal and camel case
+/// keep the rest of each word as written, so acronyms survive ("userID").
+fn words(s: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let chars: Vec<(usize, char)> = s.char_indices().collect();
+    let mut start: Option<usize> = None;
+
+    for (i, &(pos, c)) in chars.iter().enumerate() {
+        if c == '_' || c == '-' || c.is_whitespace() {
+            if let Some(st) = start.take() {
+                words.push(&s[st..pos]);
+            }
+            continue;
+        }
+        if let Some(st) = start
+            && c.is_uppercase()
+        {
+            let prev = chars[i - 1].1;
+            let next_is_lower = chars.get(i + 1).is_some_and(|&(_, n)| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_is_lower) {
+                words.push(&s[st..pos]);
+                start = Some(pos);
+            }
+        }
+        start.get_or_insert(pos);
+    }
+    if let Some(st) = start {
+        words.push(&s[st..]);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// "provider" -> "Provider", "five_nine" -> "FiveNine"
+pub fn to_pascal_case(s: &str) -> String {
+    words(s).into_iter().map(capitalize).collect()
+}
+
+/// "FiveNine" -> "fiveNine", "get-recording" -> "getRecording"
+pub fn to_camel_case(s: &str) -> String {
+    words(s)
+        .into_iter()
+        .enumerate()
+        .map(|(i, w)| if i == 0 { w.to_lowercase() } else { capitalize(w) })
+        .collect()
+}
+
+/// "GetRecordingDto" -> "get-recording-dto", "IdDto" -> "id-dto"
+pub fn to_kebab_case(s: &str) -> String {
+    words(s).into_iter().map(str::to_lowercase).collect::<Vec<_>>().join("-")
+}
+
+/// "GetRecordingDto" -> "get_recording_dto"
+pub fn to_snake_case(s: &str) -> String {
+    words(s).into_iter().map(str::to_lowercase).collect::<Vec<_>>().join("_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_between_cases() {
+        // input, pascal, camel, kebab, snake
+        let table = [
+            ("id", "Id", "id", "id", "id"),
+            ("IdDto", "IdDto", "idDto", "id-dto", "id_dto"),
+            ("GetRecordingDto", "GetRecordingDto", "getRecordingDto", "get-recording-dto", "get_recording_dto"),
+            ("fiveNine", "FiveNine", "fiveNine", "five-nine", "five_nine"),
+            ("five_nine", "FiveNine", "fiveNine", "five-nine", "five_nine"),
+            ("not-found", "NotFound", "notFound", "not-found", "not_found"),
+            ("HTTPServerDto", "HTTPServerDto", "httpServerDto", "http-server-dto", "http_server_dto"),
+            ("userID", "UserID", "userID", "user-id", "user_id"),
+            ("S3Dto", "S3Dto", "s3Dto", "s3-dto", "s3_dto"),
+            ("base64Url", "Base64Url", "base64Url", "base64-url", "base64_url"),
+            ("v2", "V2", "v2", "v2", "v2"),
+            ("", "", "", "", ""),
+        ];
+        for (input, pascal, camel, kebab, snake) in table {
+            assert_eq!(to_pascal_case(input), pascal, "pascal of {:?}", input);
+            assert_eq!(to_camel_case(input), camel, "camel of {:?}", input);
+            assert_eq!(to_kebab_case(input), kebab, "kebab of {:?}", input);
+            assert_eq!(to_snake_case(input), snake, "snake of {:?}", input);
+        }
+    }
+}
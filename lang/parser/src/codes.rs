@@ -0,0 +1,350 @@
+//! Diagnostic codes shared by `rune-syntax validate` and the LSP, with the
+//! longer explanations `rune-syntax explain <code>` prints.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagnosticCode {
+    LineTooLong,
+    ParseError,
+    Indentation,
+    OutOfScope,
+    RequirementShape,
+    DuplicateRequirement,
+    ReturnMismatch,
+    RequirementSpacing,
+    MissingReturnType,
+    InconsistentSignature,
+    UnreachableStep,
+    DtoParamNotInScope,
+    InvalidBoundary,
+    FsPathParam,
+    DuplicateFault,
+    UnknownFault,
+    FaultName,
+    DtoName,
+    DtoMissingDescription,
+    DuplicateDefinition,
+    DuplicateProperty,
+    CyclicDto,
+    TrailingComma,
+    UndefinedReference,
+    NonPrimitiveType,
+    InvalidTypeModifier,
+    TypeMissingDescription,
+    MixedPurity,
+    UnconstructedClass,
+    NounTypo,
+    UnproducedNoun,
+    CaseNotInUnion,
+    MissingUnionCase,
+    EmptyCase,
+}
+
+impl DiagnosticCode {
+    pub const ALL: [DiagnosticCode; 34] = [
+        Self::LineTooLong,
+        Self::ParseError,
+        Self::Indentation,
+        Self::OutOfScope,
+        Self::RequirementShape,
+        Self::DuplicateRequirement,
+        Self::ReturnMismatch,
+        Self::RequirementSpacing,
+        Self::MissingReturnType,
+        Self::InconsistentSignature,
+        Self::UnreachableStep,
+        Self::DtoParamNotInScope,
+        Self::InvalidBoundary,
+        Self::FsPathParam,
+        Self::DuplicateFault,
+        Self::UnknownFault,
+        Self::FaultName,
+        Self::DtoName,
+        Self::DtoMissingDescription,
+        Self::DuplicateDefinition,
+        Self::DuplicateProperty,
+        Self::CyclicDto,
+        Self::TrailingComma,
+        Self::UndefinedReference,
+        Self::NonPrimitiveType,
+        Self::InvalidTypeModifier,
+        Self::TypeMissingDescription,
+        Self::MixedPurity,
+        Self::UnconstructedClass,
+        Self::NounTypo,
+        Self::UnproducedNoun,
+        Self::CaseNotInUnion,
+        Self::MissingUnionCase,
+        Self::EmptyCase,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::LineTooLong => "LIN001",
+            Self::ParseError => "PAR001",
+            Self::Indentation => "IND001",
+            Self::OutOfScope => "SCP001",
+            Self::RequirementShape => "REQ001",
+            Self::DuplicateRequirement => "REQ002",
+            Self::ReturnMismatch => "REQ003",
+            Self::RequirementSpacing => "REQ004",
+            Self::MissingReturnType => "STP001",
+            Self::InconsistentSignature => "STP002",
+            Self::UnreachableStep => "STP003",
+            Self::DtoParamNotInScope => "STP004",
+            Self::InvalidBoundary => "BND001",
+            Self::FsPathParam => "BND002",
+            Self::DuplicateFault => "FLT001",
+            Self::UnknownFault => "FLT002",
+            Self::FaultName => "FLT003",
+            Self::DtoName => "DTO001",
+            Self::DtoMissingDescription => "DTO002",
+            Self::DuplicateDefinition => "DTO003",
+            Self::DuplicateProperty => "DTO004",
+            Self::CyclicDto => "DTO005",
+            Self::TrailingComma => "DTO006",
+            Self::UndefinedReference => "REF001",
+            Self::NonPrimitiveType => "TYP001",
+            Self::InvalidTypeModifier => "TYP002",
+            Self::TypeMissingDescription => "TYP003",
+            Self::MixedPurity => "NOU001",
+            Self::UnconstructedClass => "NOU002",
+            Self::NounTypo => "NOU003",
+            Self::UnproducedNoun => "NOU004",
+            Self::CaseNotInUnion => "PLY001",
+            Self::MissingUnionCase => "PLY002",
+            Self::EmptyCase => "PLY003",
+        }
+    }
+
+    /// Look a code up by its id, ignoring case ("req001" works too)
+    pub fn parse(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str().eq_ignore_ascii_case(code.trim()))
+    }
+
+    /// What the diagnostic means and how to fix it
+    pub fn explanation(self) -> &'static str {
+        match self {
+            Self::LineTooLong => "\
+A line is longer than the column limit (80, or `max_line_length` in rune.toml).
+
+Break long step signatures over several lines:
+
+    db:recording.save(
+        RecordingDto
+    ): IdDto",
+            Self::ParseError => "\
+The line is not a tag, step, fault or definition the parser understands.
+Steps look like `noun.verb(args): type`, boundary steps add a prefix
+(`db:user.load(IdDto): UserDto`) and faults are lowercase kebab-case words.
+
+    user.load(IdDto)          // missing return type
+    user.load(IdDto): UserDto // fixed",
+            Self::Indentation => "\
+Indentation carries meaning: [REQ] and definitions start at column 0, steps
+are indented 4 spaces (4 more per enclosing [PLY]), faults 2 more than their
+step, and multiline continuations 4 more than the line they continue. Indent
+with spaces; tabs are rejected.
+
+    [REQ] user.get(IdDto): UserDto
+        db:user.load(IdDto): UserDto
+          not-found",
+            Self::OutOfScope => "\
+Steps, faults, [PLY], [RET] and [CTR] only make sense inside a [REQ], a fault
+only under a step, and a [CSE] only inside a [PLY] block. Move the line under
+its parent or remove it.",
+            Self::RequirementShape => "\
+A [REQ] or [ENT] takes a DTO in and returns a DTO, and a plain [REQ] takes
+no modifier. A fire-and-forget [REQ] may return `void` instead.
+
+    [REQ] user.get(id): user       // wrong
+    [REQ] user.get(IdDto): UserDto // fixed",
+            Self::DuplicateRequirement => "\
+The same `noun.verb` requirement is declared twice. Rename one of them or
+merge their steps.",
+            Self::ReturnMismatch => "\
+The last step (or [RET]) of a requirement must produce the requirement's
+output type. When no step produces it at all, the [REQ] line itself is
+flagged: a step that builds the output DTO is probably missing.
+
+    [REQ] user.get(IdDto): UserDto
+        db:user.load(IdDto): UserDto
+        [RET] UserDto",
+            Self::RequirementSpacing => "\
+Requirements are separated by exactly two blank lines. `rune-syntax format`
+fixes this automatically.",
+            Self::MissingReturnType => "\
+Every step declares what it returns after a colon; use `void` when it
+returns nothing.
+
+    db:user.delete(IdDto): void",
+            Self::InconsistentSignature => "\
+The same method is called with different parameters or return type in
+different places. Generated code has one signature per method, so make the
+calls agree or give them different names.",
+            Self::UnreachableStep => "\
+A step follows a [RET] in the same scope, so it never runs. Remove it or move
+the [RET] after it.",
+            Self::DtoParamNotInScope => "\
+A step passes a DTO that is defined but not available at that point: it is
+neither the [REQ] input nor returned by an earlier step in the same branch.
+Usually a typo or a missing step. Enabled by `strict_dto_params = true` in
+rune.toml.
+
+    [REQ] user.update(UpdateUserDto): UserDto
+        db:user.save(UserDto): UserDto     // UserDto is not in scope yet",
+            Self::InvalidBoundary => "\
+Boundary steps use a known prefix (db:, fs:, mq:, ex:, os:, lg:, or one added
+under `[boundaries]` in rune.toml) and only pass DTOs or primitives across the
+boundary.
+
+    db:user.save(UserDto): IdDto",
+            Self::FsPathParam => "\
+The first parameter of an `fs:` step is its path, so it should be a string:
+a DTO, `string`, or a [TYP] declared as string. Enabled by
+`warn_fs_path_params = true` in rune.toml.
+
+    fs:disk.read(path): data
+    [TYP] path: string",
+            Self::DuplicateFault => "\
+The same fault is listed twice under one step. Keep one of them.",
+            Self::UnknownFault => "\
+A pure step raises a fault that no boundary raises and that is not in the
+fault vocabulary: the built-in names (not-found, timeout, conflict, ...) plus
+`faults` in rune.toml. Check the spelling, or add the fault to `faults` if it
+is intentional. Enabled by setting `faults` (even to `[]`).
+
+    faults = [\"empty-cart\"]",
+            Self::FaultName => "\
+A line under a step looks like a list of faults, but fault names are
+lowercase words joined by hyphens, so it was not read as one.
+
+      not_found notFound    // wrong
+      not-found             // fixed",
+            Self::DtoName => "\
+DTO names end in `Dto`.
+
+    [DTO] User: name    // wrong
+    [DTO] UserDto: name // fixed",
+            Self::DtoMissingDescription => "\
+Every [DTO] needs a description on the next line, indented 4 spaces.
+
+    [DTO] UserDto: name, email
+        a registered user",
+            Self::DuplicateDefinition => "\
+A [DTO], [TYP] or [NON] with this name is already defined. Remove or rename
+the second definition.",
+            Self::DuplicateProperty => "\
+A DTO lists the same property twice. Remove the duplicate.",
+            Self::CyclicDto => "\
+DTOs reference each other in a cycle, which can never be constructed. Break
+the cycle by referencing an id instead of the whole DTO.
+
+    [DTO] OrderDto: customer   // customer -> CustomerDto -> OrderDto
+    [DTO] OrderDto: customerId // fixed",
+            Self::TrailingComma => "\
+A [DTO] property list ends in a comma. The empty property is ignored, but it
+usually marks a half-finished edit: add the missing property or drop the
+comma.
+
+    [DTO] UserDto: id, name,   // warned
+    [DTO] UserDto: id, name    // fixed",
+            Self::UndefinedReference => "\
+A DTO property or signature names something that is not defined in this
+file (or, when validating a directory, any .rune file in it). Declare it:
+
+    [TYP] email: string
+        an email address",
+            Self::NonPrimitiveType => "\
+A [TYP] aliases a primitive (string, number, boolean, ...), not a DTO or
+another [TYP]. Use the DTO or the other type directly instead. Each member of
+a tuple type must be a primitive or a [TYP].
+
+    [TYP] pair: [id, FooDto]   // wrong
+    [TYP] pair: [id, name]     // fixed",
+            Self::InvalidTypeModifier => "\
+A [TYP] modifier is unknown or does not fit the type: `email` only applies
+to strings, `min=<n>` to numbers, and so on.
+
+    [TYP:email] age: number    // wrong
+    [TYP:min=0] age: number    // fixed",
+            Self::TypeMissingDescription => "\
+A [TYP] or [NON] has no description on the next line. [DTO]s always need
+one; enable `require_type_descriptions = true` in rune.toml to hold types
+and nouns to the same standard.
+
+    [TYP] email: string
+        an address we can reach the user at",
+            Self::MixedPurity => "\
+A noun has both boundary methods (`db:x.save`) and pure methods (`x.compute`),
+so everything it does is generated as impure. Usually the pure logic belongs
+to a separate noun. Enabled by `warn_mixed_purity = true` in rune.toml.",
+            Self::UnconstructedClass => "\
+A `[TYP] x: Class` declares a class that steps instantiate, but no [CTR] (or
+[NEW]) constructs it and no static method is called on it. Construct it
+before its first instance call, or drop the type. Enabled by
+`warn_unconstructed_classes = true` in rune.toml.
+
+    [CTR] metadata
+    metadata.set(FileDto): IdDto",
+            Self::NounTypo => "\
+A [REQ] noun appears only once and is one letter away from a noun other
+requirements use, so it is probably misspelled. Enabled by
+`warn_noun_typos = true` in rune.toml.
+
+    [REQ] recording.register(CallDto): IdDto
+    [REQ] recording.delete(IdDto): void
+    [REQ] recordng.get(IdDto): RecordingDto   // warned",
+            Self::UnproducedNoun => "\
+A noun is only ever called as an instance (`widget.spin(...)`): no step
+returns it, no [CTR]/[NEW] constructs it and no static method produces it,
+so nothing ever hands the steps a `widget`. A factory step is probably
+missing. Enabled by `warn_unproduced_nouns = true` in rune.toml.
+
+    [CTR] widget                 // or
+    widget::create(IdDto): widget",
+            Self::CaseNotInUnion => "\
+A [PLY] over a noun that has a string-union [TYP] branches on a [CSE] that is
+not one of the union's members. Rename the case or add the member.
+
+    [TYP] provider: \"genie\" | \"fiveNine\"
+    [PLY] provider.get(CallDto): DataDto
+        [CSE] genie
+        [CSE] fiveNine",
+            Self::MissingUnionCase => "\
+A member of the noun's string-union [TYP] has no [CSE] in the [PLY] block,
+so that variant is never handled. Add a [CSE] for it.",
+            Self::EmptyCase => "\
+A [CSE] is followed by another [CSE], the end of its [PLY] block or the end of
+the file without any step, so that branch would do nothing. Add its steps or
+remove the case.
+
+    [PLY] provider.get(CallDto): DataDto
+        [CSE] genie
+        ex:genie.fetch(CallDto): DataDto
+        [CSE] fiveNine                     // no steps",
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_round_trip() {
+        for code in DiagnosticCode::ALL {
+            assert_eq!(DiagnosticCode::parse(code.as_str()), Some(code));
+            assert!(!code.explanation().is_empty());
+        }
+        assert_eq!(DiagnosticCode::parse("req001"), Some(DiagnosticCode::RequirementShape));
+        assert_eq!(DiagnosticCode::parse("XYZ999"), None);
+    }
+}
@@ -0,0 +1,181 @@
+//! Project settings from `rune.toml` (or `.rune.toml`), shared by the CLI and
+//! the LSP so both report the same diagnostics.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::{parse_document_with, ParsedLine, DEFAULT_BOUNDARIES, DEFAULT_TAB_WIDTH};
+
+pub const DEFAULT_MAX_LINE_LENGTH: usize = 80;
+
+const CONFIG_FILES: [&str; 2] = ["rune.toml", ".rune.toml"];
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Longest allowed line, counted in chars
+    pub max_line_length: usize,
+    /// Warn when a noun has both boundary and pure methods. Off by default:
+    /// plenty of existing specs load and transform the same noun.
+    pub warn_mixed_purity: bool,
+    /// Warn when an `fs:` step's first param isn't a DTO or a string path
+    pub warn_fs_path_params: bool,
+    /// Require a DTO passed to a step to be in scope (the REQ input or an
+    /// earlier output). Off by default: the generator builds wrapper DTOs
+    /// like `IdDto` from in-scope fields, and plenty of specs rely on that.
+    pub strict_dto_params: bool,
+    /// Warn when a [REQ] noun used once is one edit away from a noun other
+    /// requirements share (`recordng` next to `recording`)
+    pub warn_noun_typos: bool,
+    /// Warn when a noun is only ever an instance receiver: no step returns
+    /// it, nothing constructs it and no static method produces it. Off by
+    /// default: the valid corpus calls plenty of such nouns.
+    pub warn_unproduced_nouns: bool,
+    /// Warn when a `[TYP] x: Class` is never constructed with [NEW]/[CTR]
+    /// nor called statically
+    pub warn_unconstructed_classes: bool,
+    /// Warn when a [TYP] or [NON] has no description line, as a [DTO] must
+    pub require_type_descriptions: bool,
+    /// Fault names pure steps may raise on top of the built-in vocabulary
+    /// (`not-found`, `timeout`, ...). Setting it, even to `[]`, turns on the
+    /// check for faults no boundary raises.
+    pub faults: Option<Vec<String>>,
+    /// Extra boundary prefixes on top of the defaults, e.g.
+    /// `[boundaries]` / `cache = "caching layer"`
+    pub boundaries: BTreeMap<String, String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_line_length: DEFAULT_MAX_LINE_LENGTH,
+            warn_mixed_purity: false,
+            warn_fs_path_params: false,
+            strict_dto_params: false,
+            warn_noun_typos: false,
+            warn_unproduced_nouns: false,
+            warn_unconstructed_classes: false,
+            require_type_descriptions: false,
+            faults: None,
+            boundaries: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Parse the contents of a config file
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| format!("Invalid rune config: {}", e))
+    }
+
+    /// Load the nearest config at or above `dir`, or the defaults when there
+    /// is none. A config that can't be read or parsed is an error rather than
+    /// silently falling back, so a typo doesn't quietly change the rules.
+    pub fn discover(dir: &Path) -> Result<Self, String> {
+        let Some(path) = find_config_file(dir) else {
+            return Ok(Self::default());
+        };
+        let text = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        toml::from_str(&text).map_err(|e| format!("Invalid rune config {}: {}", path.display(), e))
+    }
+}
+
+impl Config {
+    /// Every boundary prefix (with its trailing `:`) and its description: the
+    /// defaults first, then the configured ones.
+    pub fn boundaries(&self) -> Vec<(String, String)> {
+        let mut all: Vec<(String, String)> = DEFAULT_BOUNDARIES
+            .iter()
+            .map(|(prefix, description)| (prefix.to_string(), description.to_string()))
+            .collect();
+        for (name, description) in &self.boundaries {
+            let prefix = format!("{}:", name.trim_end_matches(':'));
+            match all.iter_mut().find(|(p, _)| *p == prefix) {
+                Some(existing) => existing.1 = description.clone(),
+                None => all.push((prefix, description.clone())),
+            }
+        }
+        all
+    }
+
+    pub fn boundary_description(&self, prefix: &str) -> Option<String> {
+        self.boundaries().into_iter().find(|(p, _)| p == prefix).map(|(_, d)| d)
+    }
+
+    /// Parse `text` recognising the configured boundary prefixes
+    pub fn parse(&self, text: &str) -> Vec<ParsedLine> {
+        let boundaries = self.boundaries();
+        let prefixes: Vec<&str> = boundaries.iter().map(|(p, _)| p.as_str()).collect();
+        parse_document_with(text, DEFAULT_TAB_WIDTH, &prefixes)
+    }
+}
+
+/// Walk up from `dir` to the first directory holding a config file
+pub fn find_config_file(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .flat_map(|d| CONFIG_FILES.iter().map(move |name| d.join(name)))
+        .find(|p| p.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_when_key_missing() {
+        let config = Config::from_toml("[project]\nname = \"demo\"\n").unwrap();
+        assert_eq!(config.max_line_length, DEFAULT_MAX_LINE_LENGTH);
+    }
+
+    #[test]
+    fn reads_max_line_length() {
+        let config = Config::from_toml("max_line_length = 120\n").unwrap();
+        assert_eq!(config.max_line_length, 120);
+        assert!(!config.warn_mixed_purity);
+        assert!(Config::from_toml("warn_mixed_purity = true\n").unwrap().warn_mixed_purity);
+    }
+
+    #[test]
+    fn reads_fault_vocabulary() {
+        assert_eq!(Config::default().faults, None);
+        let config = Config::from_toml("faults = [\"empty-cart\"]\n").unwrap();
+        assert_eq!(config.faults, Some(vec!["empty-cart".to_string()]));
+    }
+
+    #[test]
+    fn discovers_config_in_parent_directory() {
+        let root = tempfile::tempdir().unwrap();
+        let nested = root.path().join("specs/billing");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.path().join(".rune.toml"), "max_line_length = 100\n").unwrap();
+
+        assert_eq!(Config::discover(&nested).unwrap().max_line_length, 100);
+    }
+
+    #[test]
+    fn discover_reports_malformed_config() {
+        let root = tempfile::tempdir().unwrap();
+        assert_eq!(Config::discover(root.path()).unwrap(), Config::default());
+
+        fs::write(root.path().join("rune.toml"), "max_line_length = \"120\"\n").unwrap();
+        let err = Config::discover(root.path()).unwrap_err();
+        assert!(err.starts_with(&format!("Invalid rune config {}", root.path().join("rune.toml").display())), "{}", err);
+    }
+
+    #[test]
+    fn custom_boundaries_parse_as_boundary_steps() {
+        let config = Config::from_toml("[boundaries]\ncache = \"caching layer\"\n").unwrap();
+        assert_eq!(config.boundary_description("cache:").as_deref(), Some("caching layer"));
+        assert_eq!(config.boundary_description("db:").as_deref(), Some("database / persistence"));
+
+        let lines = config.parse("    cache:session.get(IdDto): SessionDto");
+        assert!(matches!(&lines[0].kind, crate::LineKind::BoundaryStep { prefix, noun, .. }
+            if prefix == "cache:" && noun == "session"));
+        // Without the config the prefix is not a boundary
+        let lines = Config::default().parse("    cache:session.get(IdDto): SessionDto");
+        assert!(!matches!(&lines[0].kind, crate::LineKind::BoundaryStep { .. }));
+    }
+}
@@ -1,11 +1,51 @@
 //! Fast line-based parser for rune files
 
+pub mod casing;
+pub mod codes;
+pub mod config;
+pub mod symbols;
+pub mod validate;
+
+/// Column width a leading tab expands to when no width is given.
+pub const DEFAULT_TAB_WIDTH: usize = 4;
+
+/// Built-in boundary prefixes and what each one stands for.
+pub const DEFAULT_BOUNDARIES: [(&str, &str); 6] = [
+    ("db:", "database / persistence"),
+    ("fs:", "file system (local)"),
+    ("mq:", "message queue"),
+    ("ex:", "external service / provider"),
+    ("os:", "object storage (S3, GCS)"),
+    ("lg:", "logs"),
+];
+
 #[derive(Debug, Clone)]
 pub struct ParsedLine {
     pub line_num: usize,
+    pub raw_indent: usize,  // leading whitespace as written (a tab counts as 1)
+    pub indent: usize,      // leading whitespace with tabs expanded
+    pub spans: Spans,
     pub kind: LineKind,
 }
 
+/// Char columns `start..end` of a token on its source line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Where the interesting tokens of a line sit. Signatures (REQ, ENT, steps,
+/// [PLY]) fill noun/verb/output; definitions and [CTR] fill name. A token
+/// on a continuation line of a wrapped signature is left as `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Spans {
+    pub noun: Option<Span>,
+    pub verb: Option<Span>,
+    pub output: Option<Span>,
+    pub name: Option<Span>,
+}
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub enum LineKind {
@@ -35,6 +75,7 @@ pub enum LineKind {
         output: String,
         indent: usize,
         is_static: bool,
+        comment: Option<String>,  // inline `// ...` after the signature
     },
     BoundaryStep {
         prefix: String,
@@ -44,6 +85,7 @@ pub enum LineKind {
         output: String,
         indent: usize,
         is_static: bool,
+        comment: Option<String>,  // inline `// ...` after the signature
     },
     Fault {
         names: Vec<String>,
@@ -56,6 +98,7 @@ pub enum LineKind {
         output: String,
         indent: usize,
         is_static: bool,
+        comment: Option<String>,  // inline `// ...` after the signature
     },
     Cse {
         name: String,
@@ -95,6 +138,15 @@ pub enum LineKind {
         text: String,
         indent: usize,
     },
+    /// `[DOC] title`: free prose that is kept but never validated
+    DocDef {
+        title: String,
+    },
+    /// An indented line of a `[DOC]` block, verbatim
+    DocLine {
+        text: String,
+        indent: usize,
+    },
     MultilineContinuation {
         expected_indent: usize,
         actual_indent: usize,
@@ -107,6 +159,9 @@ pub enum LineKind {
         value: String,
         indent: usize,
     },
+    /// `[CTR] class` — the canonical constructor tag. The legacy `[NEW]` is
+    /// its synonym in keywords.json, parses to the same variant, and
+    /// `rune-syntax migrate` rewrites it.
     New {
         class_name: String,
         indent: usize,
@@ -115,25 +170,166 @@ pub enum LineKind {
     Unknown(String),
 }
 
+/// A parsed .rune file with typed views over its definitions.
+///
+/// ```
+/// use rune_parser::Document;
+///
+/// let src = "[DTO] UserDto: name\n    a user\n\n[TYP] name: string\n    a name";
+/// let doc = Document::parse(src);
+/// assert_eq!(doc.dtos().count(), 1);
+/// assert_eq!(doc.types().next().unwrap().type_name, "string");
+/// assert_eq!(doc.symbol_at(0, 8), Some("UserDto"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Document {
+    source: Vec<String>,
+    lines: Vec<ParsedLine>,
+}
+
+/// A `[REQ]` line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReqView<'a> {
+    pub line: usize,
+    pub noun: &'a str,
+    pub verb: &'a str,
+    pub input: &'a str,
+    pub output: &'a str,
+}
+
+/// A `[DTO]` definition
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DtoView<'a> {
+    pub line: usize,
+    pub name: &'a str,
+    pub properties: &'a [String],
+}
+
+/// A `[TYP]` definition
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TypView<'a> {
+    pub line: usize,
+    pub name: &'a str,
+    pub type_name: &'a str,
+}
+
+impl Document {
+    pub fn parse(text: &str) -> Self {
+        Self {
+            source: text.lines().map(str::to_string).collect(),
+            lines: parse_document(text),
+        }
+    }
+
+    /// Every parsed line, in source order
+    pub fn lines(&self) -> &[ParsedLine] {
+        &self.lines
+    }
+
+    pub fn reqs(&self) -> impl Iterator<Item = ReqView<'_>> {
+        self.lines.iter().filter_map(|l| match &l.kind {
+            LineKind::Req { noun, verb, input, output, .. } => Some(ReqView {
+                line: l.line_num,
+                noun,
+                verb,
+                input,
+                output,
+            }),
+            _ => None,
+        })
+    }
+
+    pub fn dtos(&self) -> impl Iterator<Item = DtoView<'_>> {
+        self.lines.iter().filter_map(|l| match &l.kind {
+            LineKind::DtoDef { name, properties } => Some(DtoView {
+                line: l.line_num,
+                name,
+                properties,
+            }),
+            _ => None,
+        })
+    }
+
+    pub fn types(&self) -> impl Iterator<Item = TypView<'_>> {
+        self.lines.iter().filter_map(|l| match &l.kind {
+            LineKind::TypDef { name, type_name, .. } => Some(TypView {
+                line: l.line_num,
+                name,
+                type_name,
+            }),
+            _ => None,
+        })
+    }
+
+    /// The parsed line at a 0-based line number
+    pub fn line_at(&self, line: usize) -> Option<&ParsedLine> {
+        self.lines.iter().find(|l| l.line_num == line)
+    }
+
+    /// The identifier under a 0-based (line, column) position, if any
+    pub fn symbol_at(&self, line: usize, col: usize) -> Option<&str> {
+        let text = self.source.get(line)?;
+        let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+        let (byte, c) = text.char_indices().nth(col)?;
+        if !is_ident(c) {
+            return None;
+        }
+        let start = text[..byte]
+            .char_indices()
+            .rev()
+            .take_while(|&(_, c)| is_ident(c))
+            .last()
+            .map_or(byte, |(i, _)| i);
+        let end = text[byte..]
+            .char_indices()
+            .find(|&(_, c)| !is_ident(c))
+            .map_or(text.len(), |(i, _)| byte + i);
+        Some(&text[start..end])
+    }
+}
+
 pub fn parse_document(text: &str) -> Vec<ParsedLine> {
-    let mut results = Vec::new();
+    parse_document_with_tab_width(text, DEFAULT_TAB_WIDTH)
+}
+
+/// Parse with leading tabs expanded to `tab_width` columns, so tab-indented
+/// files get the same indent math as space-indented ones.
+pub fn parse_document_with_tab_width(text: &str, tab_width: usize) -> Vec<ParsedLine> {
+    let boundaries: Vec<&str> = DEFAULT_BOUNDARIES.iter().map(|(prefix, _)| *prefix).collect();
+    parse_document_with(text, tab_width, &boundaries)
+}
+
+/// Parse with an explicit set of boundary prefixes (each ending in `:`),
+/// e.g. the defaults plus the `[boundaries]` table of rune.toml.
+pub fn parse_document_with(text: &str, tab_width: usize, boundaries: &[&str]) -> Vec<ParsedLine> {
+    let source: Vec<&str> = source_lines(text).collect();
+    let mut results = Vec::with_capacity(source.len());
     let mut in_dto_block = false;
     let mut in_typ_block = false;
     let mut in_non_block = false;
+    // A [DOC] block runs, blank lines included, until the next line at column 0
+    let mut in_doc_block = false;
     let mut in_multiline_step = false;
     let mut paren_depth: i32 = 0;
     let mut multiline_indent: usize = 0;
+    // A [REQ] whose signature wraps: (index into results, modifier, text so far).
+    // Its line stays `Unknown` until the closing `):` completes the signature.
+    let mut pending_req: Option<(usize, Option<String>, String)> = None;
 
-    for (line_num, line) in text.lines().enumerate() {
+    for (line_num, &line) in source.iter().enumerate() {
         // Calculate leading whitespace (from original line)
-        let actual_indent = line.len() - line.trim_start().len();
+        let raw_indent = line.len() - line.trim_start().len();
+        let actual_indent = normalized_indent(line, tab_width);
 
         // Check for pure comment lines first
         let original_trimmed = line.trim();
-        if original_trimmed.starts_with("//") {
-            let comment_text = original_trimmed[2..].trim().to_string();
+        if let Some(comment) = original_trimmed.strip_prefix("//") {
+            let comment_text = comment.trim().to_string();
             results.push(ParsedLine {
                 line_num,
+                raw_indent,
+                indent: actual_indent,
+                spans: Spans::default(),
                 kind: LineKind::Comment {
                     text: comment_text,
                     indent: actual_indent,
@@ -142,11 +338,13 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
             continue;
         }
 
-        // Strip inline comments (// to end of line)
-        let line_without_comment = if let Some(comment_pos) = line.find("//") {
-            &line[..comment_pos]
-        } else {
-            line
+        // Strip inline comments (// to end of line), keeping the text for steps
+        let (line_without_comment, inline_comment) = match comment_start(line) {
+            Some(comment_pos) => {
+                let comment = line[comment_pos + 2..].trim();
+                (&line[..comment_pos], (!comment.is_empty()).then(|| comment.to_string()))
+            }
+            None => (line, None),
         };
 
         let trimmed = line_without_comment.trim();
@@ -158,23 +356,65 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
             in_multiline_step = false;
             paren_depth = 0;
             multiline_indent = 0;
-            results.push(ParsedLine { line_num, kind: LineKind::Empty });
+            pending_req = None;
+            results.push(ParsedLine { line_num, raw_indent, indent: actual_indent, spans: Spans::default(), kind: LineKind::Empty });
+            continue;
+        }
+
+        if in_doc_block {
+            if actual_indent > 0 {
+                results.push(ParsedLine {
+                    line_num,
+                    raw_indent,
+                    indent: actual_indent,
+                    spans: Spans::default(),
+                    kind: LineKind::DocLine {
+                        text: original_trimmed.to_string(),
+                        indent: actual_indent,
+                    },
+                });
+                continue;
+            }
+            in_doc_block = false;
+        }
+
+        // [DOC] title: prose, skipped by every check
+        if !in_multiline_step && let Some((_modifier, rest)) = match_tag(trimmed, "DOC") {
+            in_dto_block = false;
+            in_typ_block = false;
+            in_non_block = false;
+            in_doc_block = true;
+            pending_req = None;
+            let title = rest.trim().to_string();
+            results.push(ParsedLine { line_num, raw_indent, indent: actual_indent, spans: Spans::default(), kind: LineKind::DocDef { title } });
             continue;
         }
 
         // Track paren depth for multi-line detection
-        let open_parens = trimmed.matches('(').count();
-        let close_parens = trimmed.matches(')').count();
+        let (open_parens, close_parens) = paren_counts(trimmed);
 
         // If we're in a multi-line step, check if it closes
         if in_multiline_step {
             paren_depth = paren_depth + open_parens as i32 - close_parens as i32;
+            if let Some((_, _, sig)) = pending_req.as_mut() {
+                sig.push(' ');
+                sig.push_str(trimmed);
+            }
             if paren_depth <= 0 && trimmed.contains("):") {
                 in_multiline_step = false;
                 paren_depth = 0;
+                if let Some((idx, modifier, sig)) = pending_req.take()
+                    && let Some((noun, verb, input, output, is_camel_case)) = parse_req_signature(&sig)
+                {
+                    let indent = results[idx].indent;
+                    results[idx].kind = LineKind::Req { noun, verb, input, output, indent, is_camel_case, modifier };
+                }
             }
             results.push(ParsedLine {
                 line_num,
+                raw_indent,
+                indent: actual_indent,
+                spans: Spans::default(),
                 kind: LineKind::MultilineContinuation {
                     expected_indent: multiline_indent,
                     actual_indent,
@@ -190,9 +430,9 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
             in_non_block = false;
             let name = rest.trim().to_string();
             if !name.is_empty() {
-                results.push(ParsedLine { line_num, kind: LineKind::Mod { name } });
+                results.push(ParsedLine { line_num, raw_indent, indent: actual_indent, spans: Spans::default(), kind: LineKind::Mod { name } });
             } else {
-                results.push(ParsedLine { line_num, kind: LineKind::Unknown("[MOD] missing name".to_string()) });
+                results.push(ParsedLine { line_num, raw_indent, indent: actual_indent, spans: Spans::default(), kind: LineKind::Unknown("[MOD] missing name".to_string()) });
             }
             continue;
         }
@@ -202,10 +442,17 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
             in_dto_block = false;
             in_typ_block = false;
             in_non_block = false;
-            if let Some((noun, verb, input, output, is_camel_case)) = parse_req_signature(rest) {
-                results.push(ParsedLine { line_num, kind: LineKind::Req { noun, verb, input, output, indent: actual_indent, is_camel_case, modifier } });
+            if open_parens > close_parens {
+                // Signature wraps; continuation lines sit at step indent.
+                in_multiline_step = true;
+                paren_depth = open_parens as i32 - close_parens as i32;
+                multiline_indent = actual_indent + 4;
+                pending_req = Some((results.len(), modifier, rest.trim().to_string()));
+                results.push(ParsedLine { line_num, raw_indent, indent: actual_indent, spans: Spans::default(), kind: LineKind::Unknown("[REQ] missing signature".to_string()) });
+            } else if let Some((noun, verb, input, output, is_camel_case)) = parse_req_signature(rest) {
+                results.push(ParsedLine { line_num, raw_indent, indent: actual_indent, spans: Spans::default(), kind: LineKind::Req { noun, verb, input, output, indent: actual_indent, is_camel_case, modifier } });
             } else {
-                results.push(ParsedLine { line_num, kind: LineKind::Unknown("[REQ] missing signature".to_string()) });
+                results.push(ParsedLine { line_num, raw_indent, indent: actual_indent, spans: Spans::default(), kind: LineKind::Unknown("[REQ] missing signature".to_string()) });
             }
             continue;
         }
@@ -222,9 +469,9 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
             in_typ_block = false;
             in_non_block = false;
             if let Some((noun, verb, input, output, _cc)) = parse_req_signature(rest) {
-                results.push(ParsedLine { line_num, kind: LineKind::Ent { noun, verb, input, output, indent: actual_indent } });
+                results.push(ParsedLine { line_num, raw_indent, indent: actual_indent, spans: Spans::default(), kind: LineKind::Ent { noun, verb, input, output, indent: actual_indent } });
             } else {
-                results.push(ParsedLine { line_num, kind: LineKind::Unknown("[ENT] missing signature".to_string()) });
+                results.push(ParsedLine { line_num, raw_indent, indent: actual_indent, spans: Spans::default(), kind: LineKind::Unknown("[ENT] missing signature".to_string()) });
             }
             continue;
         }
@@ -242,9 +489,9 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
                     .map(|p| p.trim().to_string())
                     .filter(|p| !p.is_empty())
                     .collect();
-                results.push(ParsedLine { line_num, kind: LineKind::DtoDef { name, properties } });
+                results.push(ParsedLine { line_num, raw_indent, indent: actual_indent, spans: Spans::default(), kind: LineKind::DtoDef { name, properties } });
             } else {
-                results.push(ParsedLine { line_num, kind: LineKind::Unknown("[DTO] missing properties".to_string()) });
+                results.push(ParsedLine { line_num, raw_indent, indent: actual_indent, spans: Spans::default(), kind: LineKind::Unknown("[DTO] missing properties".to_string()) });
             }
             continue;
         }
@@ -259,9 +506,9 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
             if let Some(colon_pos) = rest.find(':') {
                 let name = rest[..colon_pos].trim().to_string();
                 let type_name = rest[colon_pos + 1..].trim().to_string();
-                results.push(ParsedLine { line_num, kind: LineKind::TypDef { name, type_name, modifier } });
+                results.push(ParsedLine { line_num, raw_indent, indent: actual_indent, spans: Spans::default(), kind: LineKind::TypDef { name, type_name, modifier } });
             } else {
-                results.push(ParsedLine { line_num, kind: LineKind::Unknown("[TYP] missing type".to_string()) });
+                results.push(ParsedLine { line_num, raw_indent, indent: actual_indent, spans: Spans::default(), kind: LineKind::Unknown("[TYP] missing type".to_string()) });
             }
             continue;
         }
@@ -273,9 +520,9 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
             in_non_block = true;
             let name = rest.trim().to_string();
             if !name.is_empty() {
-                results.push(ParsedLine { line_num, kind: LineKind::NonDef { name } });
+                results.push(ParsedLine { line_num, raw_indent, indent: actual_indent, spans: Spans::default(), kind: LineKind::NonDef { name } });
             } else {
-                results.push(ParsedLine { line_num, kind: LineKind::Unknown("[NON] missing name".to_string()) });
+                results.push(ParsedLine { line_num, raw_indent, indent: actual_indent, spans: Spans::default(), kind: LineKind::Unknown("[NON] missing name".to_string()) });
             }
             continue;
         }
@@ -284,6 +531,9 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
         if in_non_block && actual_indent == 4 && !trimmed.contains('.') && !trimmed.starts_with('[') {
             results.push(ParsedLine {
                 line_num,
+                raw_indent,
+                indent: actual_indent,
+                spans: Spans::default(),
                 kind: LineKind::NonDesc {
                     text: trimmed.to_string(),
                     indent: actual_indent,
@@ -296,6 +546,9 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
         if in_typ_block && actual_indent == 4 && !trimmed.contains('.') && !trimmed.starts_with('[') {
             results.push(ParsedLine {
                 line_num,
+                raw_indent,
+                indent: actual_indent,
+                spans: Spans::default(),
                 kind: LineKind::TypDesc {
                     text: trimmed.to_string(),
                     indent: actual_indent,
@@ -304,10 +557,21 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
             continue;
         }
 
+        // DTO property line (4-space indent, `name: type` or `tags: tag(s)`)
+        if in_dto_block && actual_indent == 4 && !trimmed.starts_with('[')
+            && let Some(kind) = parse_dto_property_line(trimmed)
+        {
+            results.push(ParsedLine { line_num, raw_indent, indent: actual_indent, spans: Spans::default(), kind });
+            continue;
+        }
+
         // DTO description line (4-space indent, plain text after [DTO])
         if in_dto_block && actual_indent == 4 && !trimmed.contains('.') && !trimmed.starts_with('[') {
             results.push(ParsedLine {
                 line_num,
+                raw_indent,
+                indent: actual_indent,
+                spans: Spans::default(),
                 kind: LineKind::DtoDesc {
                     text: trimmed.to_string(),
                     indent: actual_indent,
@@ -317,10 +581,13 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
         }
 
         // [PLY] polymorphic step
-        if trimmed.starts_with("[PLY]") {
-            if let Some((noun, verb, params, output, is_static)) = parse_signature(&trimmed[5..]) {
+        if let Some(rest) = trimmed.strip_prefix("[PLY]") {
+            if let Some((noun, verb, params, output, is_static)) = parse_signature(rest) {
                 results.push(ParsedLine {
                     line_num,
+                    raw_indent,
+                    indent: actual_indent,
+                    spans: Spans::default(),
                     kind: LineKind::Ply {
                         noun,
                         verb,
@@ -328,11 +595,15 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
                         output,
                         indent: actual_indent,
                         is_static,
+                        comment: inline_comment.clone(),
                     },
                 });
             } else {
                 results.push(ParsedLine {
                     line_num,
+                    raw_indent,
+                    indent: actual_indent,
+                    spans: Spans::default(),
                     kind: LineKind::Unknown("[PLY] missing signature".to_string()),
                 });
             }
@@ -340,11 +611,14 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
         }
 
         // [CSE] case inside polymorphic block
-        if trimmed.starts_with("[CSE]") {
-            let name = trimmed[5..].trim().to_string();
+        if let Some(rest) = trimmed.strip_prefix("[CSE]") {
+            let name = rest.trim().to_string();
             if !name.is_empty() {
                 results.push(ParsedLine {
                     line_num,
+                    raw_indent,
+                    indent: actual_indent,
+                    spans: Spans::default(),
                     kind: LineKind::Cse {
                         name,
                         indent: actual_indent,
@@ -353,6 +627,9 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
             } else {
                 results.push(ParsedLine {
                     line_num,
+                    raw_indent,
+                    indent: actual_indent,
+                    spans: Spans::default(),
                     kind: LineKind::Unknown("[CSE] missing case name".to_string()),
                 });
             }
@@ -360,19 +637,21 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
         }
 
         // Boundary step (db:, ex:, etc.)
-        let boundary_prefixes = ["db:", "fs:", "mq:", "ex:", "os:", "lg:"];
         let mut found_boundary = false;
-        for bp in boundary_prefixes {
-            if trimmed.starts_with(bp) {
+        for &bp in boundaries {
+            if let Some(rest) = trimmed.strip_prefix(bp) {
                 // Check if this is a complete line or start of multiline
                 if open_parens > close_parens || (trimmed.contains('(') && !trimmed.contains("):")) {
                     in_multiline_step = true;
                     paren_depth = open_parens as i32 - close_parens as i32;
                     multiline_indent = actual_indent;
                 }
-                if let Some((noun, verb, params, output, is_static)) = parse_signature(&trimmed[bp.len()..]) {
+                if let Some((noun, verb, params, output, is_static)) = parse_signature(rest) {
                     results.push(ParsedLine {
                         line_num,
+                        raw_indent,
+                        indent: actual_indent,
+                        spans: Spans::default(),
                         kind: LineKind::BoundaryStep {
                             prefix: bp.to_string(),
                             noun,
@@ -381,15 +660,19 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
                             output,
                             indent: actual_indent,
                             is_static,
+                            comment: inline_comment.clone(),
                         },
                     });
                     found_boundary = true;
                     break;
                 } else if in_multiline_step {
                     // Multi-line start - extract what we can
-                    if let Some((noun, verb, params, output, is_static)) = parse_partial_signature(&trimmed[bp.len()..]) {
+                    if let Some((noun, verb, params, output, is_static)) = parse_partial_signature(rest) {
                         results.push(ParsedLine {
                             line_num,
+                            raw_indent,
+                            indent: actual_indent,
+                            spans: Spans::default(),
                             kind: LineKind::BoundaryStep {
                                 prefix: bp.to_string(),
                                 noun,
@@ -398,6 +681,7 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
                                 output,
                                 indent: actual_indent,
                                 is_static,
+                                comment: inline_comment.clone(),
                             },
                         });
                         found_boundary = true;
@@ -411,11 +695,14 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
         }
 
         // [RET] value step
-        if trimmed.starts_with("[RET]") {
-            let value = trimmed[5..].trim().to_string();
+        if let Some(rest) = trimmed.strip_prefix("[RET]") {
+            let value = rest.trim().to_string();
             if !value.is_empty() {
                 results.push(ParsedLine {
                     line_num,
+                    raw_indent,
+                    indent: actual_indent,
+                    spans: Spans::default(),
                     kind: LineKind::Ret {
                         value,
                         indent: actual_indent,
@@ -424,21 +711,28 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
             } else {
                 results.push(ParsedLine {
                     line_num,
+                    raw_indent,
+                    indent: actual_indent,
+                    spans: Spans::default(),
                     kind: LineKind::Unknown("[RET] missing value".to_string()),
                 });
             }
             continue;
         }
 
-        // [NEW] / [CTR] class constructor shorthand (synonyms)
-        if let Some(rest) = trimmed
-            .strip_prefix("[NEW]")
-            .or_else(|| trimmed.strip_prefix("[CTR]"))
+        // [CTR] class constructor shorthand; [NEW] is the legacy synonym
+        if let Some((tag, rest)) = trimmed
+            .strip_prefix("[CTR]")
+            .map(|rest| ("[CTR]", rest))
+            .or_else(|| trimmed.strip_prefix("[NEW]").map(|rest| ("[NEW]", rest)))
         {
             let class_name = rest.trim().to_string();
             if !class_name.is_empty() {
                 results.push(ParsedLine {
                     line_num,
+                    raw_indent,
+                    indent: actual_indent,
+                    spans: Spans::default(),
                     kind: LineKind::New {
                         class_name,
                         indent: actual_indent,
@@ -447,7 +741,10 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
             } else {
                 results.push(ParsedLine {
                     line_num,
-                    kind: LineKind::Unknown("[CTR] missing class name".to_string()),
+                    raw_indent,
+                    indent: actual_indent,
+                    spans: Spans::default(),
+                    kind: LineKind::Unknown(format!("{} missing class name", tag)),
                 });
             }
             continue;
@@ -462,23 +759,24 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
                 multiline_indent = actual_indent;
             }
             if let Some((noun, verb, params, output, is_static)) = parse_signature(trimmed) {
-                results.push(ParsedLine { line_num, kind: LineKind::Step { noun, verb, params, output, indent: actual_indent, is_static } });
+                results.push(ParsedLine { line_num, raw_indent, indent: actual_indent, spans: Spans::default(), kind: LineKind::Step { noun, verb, params, output, indent: actual_indent, is_static, comment: inline_comment.clone() } });
                 continue;
             } else if let Some((noun, verb, params, output, is_static)) = parse_partial_signature(trimmed) {
-                results.push(ParsedLine { line_num, kind: LineKind::Step { noun, verb, params, output, indent: actual_indent, is_static } });
+                results.push(ParsedLine { line_num, raw_indent, indent: actual_indent, spans: Spans::default(), kind: LineKind::Step { noun, verb, params, output, indent: actual_indent, is_static, comment: inline_comment.clone() } });
                 continue;
             }
         }
 
         // Fault line (space-separated fault names, indented)
         if actual_indent >= 6 {
-            let parts: Vec<&str> = trimmed.split_whitespace().collect();
-            let all_faults = !parts.is_empty() && parts.iter().all(|p| is_fault_name(p));
-
-            if all_faults {
-                let faults: Vec<String> = parts.iter().map(|p| p.to_string()).collect();
+            // `trimmed` is non-empty, so there is at least one part
+            if trimmed.split_whitespace().all(is_fault_name) {
+                let faults: Vec<String> = trimmed.split_whitespace().map(str::to_string).collect();
                 results.push(ParsedLine {
                     line_num,
+                    raw_indent,
+                    indent: actual_indent,
+                    spans: Spans::default(),
                     kind: LineKind::Fault {
                         names: faults,
                         indent: actual_indent,
@@ -490,35 +788,115 @@ pub fn parse_document(text: &str) -> Vec<ParsedLine> {
 
         // DTO reference (ends in Dto)
         if trimmed.ends_with("Dto") && trimmed.chars().next().map(|c| c.is_uppercase()).unwrap_or(false) {
-            results.push(ParsedLine { line_num, kind: LineKind::DtoRef(trimmed.to_string()) });
+            results.push(ParsedLine { line_num, raw_indent, indent: actual_indent, spans: Spans::default(), kind: LineKind::DtoRef(trimmed.to_string()) });
             continue;
         }
 
-        results.push(ParsedLine { line_num, kind: LineKind::Unknown(trimmed.to_string()) });
+        results.push(ParsedLine { line_num, raw_indent, indent: actual_indent, spans: Spans::default(), kind: LineKind::Unknown(trimmed.to_string()) });
     }
 
+    for parsed in &mut results {
+        if let Some(line) = source.get(parsed.line_num) {
+            parsed.spans = line_spans(line, &parsed.kind);
+        }
+    }
     results
 }
 
+/// Locate a line's tokens by walking its source left to right, so a word
+/// that appears twice (`user.load(user): user`) resolves to the right one.
+fn line_spans(line: &str, kind: &LineKind) -> Spans {
+    let code = match comment_start(line) {
+        Some(p) => &line[..p],
+        None => line,
+    };
+    let indent = code.len() - code.trim_start().len();
+    let after_tag = code.find(']').map_or(indent, |p| p + 1);
+    let span = |start: usize, end: usize| Span { start: code[..start].chars().count(), end: code[..end].chars().count() };
+    let find = |from: usize, token: &str| {
+        if token.is_empty() {
+            return None;
+        }
+        code.get(from..)?.find(token).map(|p| (from + p, from + p + token.len()))
+    };
+
+    let signature = |from: usize, noun: &str, verb: &str, output: &str, is_static: bool| {
+        let mut spans = Spans::default();
+        let Some((noun_start, noun_end)) = find(from, noun) else { return spans };
+        spans.noun = Some(span(noun_start, noun_end));
+        let sep = if is_static { "::" } else { "." };
+        let Some((verb_start, verb_end)) = find(noun_end, verb).filter(|(s, _)| *s == noun_end + sep.len()) else {
+            return spans;
+        };
+        spans.verb = Some(span(verb_start, verb_end));
+        // The output follows the closing `):`, after any params that share its name
+        if let Some(close) = code[verb_end..].rfind("):").map(|p| verb_end + p + 2) {
+            spans.output = find(close, output).map(|(s, e)| span(s, e));
+        }
+        spans
+    };
+
+    match kind {
+        LineKind::Step { noun, verb, output, is_static, .. } => signature(indent, noun, verb, output, *is_static),
+        LineKind::BoundaryStep { prefix, noun, verb, output, is_static, .. } => {
+            signature(indent + prefix.len(), noun, verb, output, *is_static)
+        }
+        LineKind::Ply { noun, verb, output, is_static, .. } => signature(after_tag, noun, verb, output, *is_static),
+        LineKind::Req { noun, verb, output, is_camel_case: false, .. } | LineKind::Ent { noun, verb, output, .. } => {
+            signature(after_tag, noun, verb, output, false)
+        }
+        LineKind::DtoDef { name, .. } | LineKind::TypDef { name, .. } | LineKind::NonDef { name } => Spans {
+            name: find(after_tag, name).map(|(s, e)| span(s, e)),
+            ..Spans::default()
+        },
+        LineKind::New { class_name, .. } => Spans {
+            name: find(after_tag, class_name).map(|(s, e)| span(s, e)),
+            ..Spans::default()
+        },
+        _ => Spans::default(),
+    }
+}
+
+/// Width of the leading whitespace, with each tab advancing to the next
+/// multiple of `tab_width`.
+fn normalized_indent(line: &str, tab_width: usize) -> usize {
+    let mut col = 0;
+    for c in line.chars() {
+        match c {
+            '\t' => col = (col / tab_width.max(1) + 1) * tab_width.max(1),
+            c if c.is_whitespace() => col += 1,
+            _ => break,
+        }
+    }
+    col
+}
+
 /// Match `[TAG]` or `[TAG:modifier]` at the start of `trimmed`.
 /// Returns (modifier, remainder-after-the-tag). Mirrors the TS parser's matchTag.
 fn match_tag<'a>(trimmed: &'a str, tag: &str) -> Option<(Option<String>, &'a str)> {
-    let plain = format!("[{}]", tag);
-    if let Some(rest) = trimmed.strip_prefix(plain.as_str()) {
+    let after_tag = trimmed.strip_prefix('[')?.strip_prefix(tag)?;
+    if let Some(rest) = after_tag.strip_prefix(']') {
         return Some((None, rest.trim_start()));
     }
-    let prefix = format!("[{}:", tag);
-    if trimmed.starts_with(prefix.as_str()) {
-        if let Some(close) = trimmed.find(']') {
-            if close > prefix.len() {
-                let modifier = trimmed[prefix.len()..close].trim().to_string();
-                return Some((Some(modifier), trimmed[close + 1..].trim_start()));
-            }
-        }
+    let after_colon = after_tag.strip_prefix(':')?;
+    if let Some(close) = after_colon.find(']')
+        && close > 0
+    {
+        let modifier = after_colon[..close].trim().to_string();
+        return Some((Some(modifier), after_colon[close + 1..].trim_start()));
     }
     None
 }
 
+/// Number of `(` and `)` in `s`, in one pass
+fn paren_counts(s: &str) -> (usize, usize) {
+    s.bytes().fold((0, 0), |(open, close), b| match b {
+        b'(' => (open + 1, close),
+        b')' => (open, close + 1),
+        _ => (open, close),
+    })
+}
+
 fn parse_signature(s: &str) -> Option<(String, String, Vec<String>, String, bool)> {
     let s = s.trim();
     let paren_pos = s.find('(')?;
@@ -645,6 +1023,71 @@ fn is_fault_name(s: &str) -> bool {
         && s.chars().next().map(|c| c.is_lowercase()).unwrap_or(false)
 }
 
+/// The lines of a document without their `\n` or `\r\n` endings. Unlike
+/// `str::lines`, a stray `\r` on the last line is dropped too, so CRLF files
+/// measure the same as LF ones.
+pub fn source_lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().map(|line| line.strip_suffix('\r').unwrap_or(line))
+}
+
+/// Byte offset of the `//` that starts an inline comment. Only a `//` at the
+/// start of the line or after whitespace counts, and never one inside quotes,
+/// so `"a//b"` and `https://x` stay part of the code. A `'` only opens a quote
+/// at the start of a token (after whitespace, `:` or `|`), so the apostrophe
+/// in `the user's id // note` doesn't hide the comment.
+pub fn comment_start(line: &str) -> Option<usize> {
+    let bytes = line.as_bytes();
+    let mut quote: Option<u8> = None;
+    for (i, &b) in bytes.iter().enumerate() {
+        match quote {
+            Some(q) if b == q => quote = None,
+            Some(_) => {}
+            None if b == b'"' => quote = Some(b),
+            None if b == b'\'' && (i == 0 || bytes[i - 1].is_ascii_whitespace() || matches!(bytes[i - 1], b':' | b'|')) => {
+                quote = Some(b)
+            }
+            None if b == b'/' && bytes.get(i + 1) == Some(&b'/') && (i == 0 || bytes[i - 1].is_ascii_whitespace()) => {
+                return Some(i);
+            }
+            None => {}
+        }
+    }
+    None
+}
+
+/// Split the optional marker off an inline `[DTO]` property:
+/// "metadata?" -> ("metadata", true), "url(s)?" -> ("url(s)", true).
+/// The `?` always comes last, after any array suffix.
+pub fn split_optional(prop: &str) -> (&str, bool) {
+    match prop.strip_suffix('?') {
+        Some(stripped) => (stripped, true),
+        None => (prop, false),
+    }
+}
+
+/// An indented property of a `[DTO]` block. `tags: tag(s)` and `tag(s): tag`
+/// are array properties named `tags` over `tag`; `name: string` is a plain
+/// one. Lines without a colon are descriptions, not properties.
+fn parse_dto_property_line(line: &str) -> Option<LineKind> {
+    let (name, type_name) = line.split_once(':')?;
+    let (name, type_name) = (name.trim(), type_name.trim());
+    let is_name = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || "_?()".contains(c));
+    if !is_name(name) || type_name.is_empty() || type_name.contains(' ') {
+        return None;
+    }
+    if let Some((base_type, suffix)) = parse_array_property(type_name) {
+        return Some(LineKind::DtoArrayProperty { property_name: name.to_string(), base_type, suffix });
+    }
+    if let Some((base, suffix)) = parse_array_property(name) {
+        return Some(LineKind::DtoArrayProperty {
+            property_name: format!("{}{}", base, suffix),
+            base_type: type_name.to_string(),
+            suffix,
+        });
+    }
+    Some(LineKind::DtoProperty { name: name.to_string(), type_name: type_name.to_string() })
+}
+
 /// Parse array property syntax: name(suffix) -> (base_name, suffix)
 /// e.g., "url(s)" -> ("url", "s")
 /// e.g., "address(es)" -> ("address", "es")
@@ -686,6 +1129,16 @@ mod tests {
         assert_eq!(lines[0].line_num, 0);
     }
 
+    #[test]
+    fn test_parse_req_camel_case() {
+        let lines = parse_document("[REQ] registerRecording(CallDto): IdDto");
+        assert!(matches!(&lines[0].kind, LineKind::Req { noun, verb, input, output, is_camel_case: true, .. }
+            if noun == "recording" && verb == "register" && input == "CallDto" && output == "IdDto"));
+
+        let lines = parse_document("[REQ] register(CallDto): IdDto");
+        assert!(!matches!(lines[0].kind, LineKind::Req { .. }));
+    }
+
     #[test]
     fn test_parse_boundary_step() {
         let doc = "    db:metadata.set(id): void";
@@ -734,6 +1187,25 @@ mod tests {
         assert!(matches!(&lines[3].kind, LineKind::MultilineContinuation { expected_indent: 4, actual_indent: 4 }));
     }
 
+    #[test]
+    fn test_parse_multiline_req() {
+        let doc = "[REQ] recording.register(\n    GetRecordingRequestDto): IdDto\n    id::create(providerName): id";
+        let lines = parse_document(doc);
+        assert_eq!(lines[0].line_num, 0);
+        assert!(matches!(&lines[0].kind, LineKind::Req { noun, verb, input, output, .. }
+            if noun == "recording" && verb == "register" && input == "GetRecordingRequestDto" && output == "IdDto"));
+        assert!(matches!(&lines[1].kind, LineKind::MultilineContinuation { expected_indent: 4, actual_indent: 4 }));
+        assert!(matches!(&lines[2].kind, LineKind::Step { noun, .. } if noun == "id"));
+    }
+
+    #[test]
+    fn test_parse_unterminated_multiline_req() {
+        let doc = "[REQ] recording.register(\n    GetRecordingRequestDto\n\n[DTO] IdDto: id";
+        let lines = parse_document(doc);
+        assert!(matches!(&lines[0].kind, LineKind::Unknown(msg) if msg == "[REQ] missing signature"));
+        assert!(matches!(&lines[3].kind, LineKind::DtoDef { .. }));
+    }
+
     #[test]
     fn test_parse_typ_def() {
         let doc = "[TYP] id: string";
@@ -808,6 +1280,44 @@ mod tests {
         assert!(matches!(&lines[0].kind, LineKind::Step { noun, verb, .. } if noun == "id" && verb == "create"));
     }
 
+    #[test]
+    fn test_apostrophe_before_inline_comment() {
+        let lines = parse_document("[TYP] id: string\n    the user's id // TODO");
+        assert!(matches!(&lines[1].kind, LineKind::TypDesc { text, .. } if text == "the user's id"));
+    }
+
+    #[test]
+    fn test_crlf_line_endings() {
+        let lines = parse_document("[DTO] FooDto: a\r\n    desc\r\n");
+        assert!(matches!(&lines[0].kind, LineKind::DtoDef { name, properties } if name == "FooDto" && properties == &vec!["a".to_string()]));
+        assert!(matches!(&lines[1].kind, LineKind::DtoDesc { text, .. } if text == "desc"));
+        assert_eq!(lines[0].spans.name, Some(Span { start: 6, end: 12 }));
+
+        // A final `\r` without `\n`
+        let lines = parse_document("[TYP] id: string\r\n    the id\r");
+        assert_eq!(source_lines("a\r\nb\r").collect::<Vec<_>>(), vec!["a", "b"]);
+        assert!(matches!(&lines[1].kind, LineKind::TypDesc { text, .. } if text == "the id"));
+    }
+
+    #[test]
+    fn test_url_in_typ_is_not_a_comment() {
+        let doc = "[TYP] endpoint: \"https://api.example.com\" // the default host\n[TYP] mirror: https://mirror.example.com";
+        let lines = parse_document(doc);
+        assert!(matches!(&lines[0].kind, LineKind::TypDef { name, type_name, .. }
+            if name == "endpoint" && type_name == "\"https://api.example.com\""));
+        assert!(matches!(&lines[1].kind, LineKind::TypDef { type_name, .. } if type_name == "https://mirror.example.com"));
+    }
+
+    #[test]
+    fn test_comment_start_skips_quotes() {
+        assert_eq!(comment_start("a // b"), Some(2));
+        assert_eq!(comment_start("// b"), Some(0));
+        assert_eq!(comment_start("x: \"a // b\" // c"), Some(12));
+        assert_eq!(comment_start("x: https://host"), None);
+        assert_eq!(comment_start("x: 'a // b'|'c' // d"), Some(16));
+        assert_eq!(comment_start("    the user's id // TODO"), Some(18));
+    }
+
     #[test]
     fn test_parse_dto_array_property() {
         let doc = "[DTO] SearchDto: url(s)";
@@ -816,6 +1326,66 @@ mod tests {
             if name == "SearchDto" && properties == &vec!["url(s)".to_string()]));
     }
 
+    #[test]
+    fn test_parse_dto_optional_property() {
+        let doc = "[DTO] MetadataDto: id, metadata?";
+        let lines = parse_document(doc);
+        let LineKind::DtoDef { properties, .. } = &lines[0].kind else { panic!("expected DtoDef") };
+        assert_eq!(split_optional(&properties[0]), ("id", false));
+        assert_eq!(split_optional(&properties[1]), ("metadata", true));
+    }
+
+    #[test]
+    fn test_parse_dto_optional_array_property() {
+        let doc = "[DTO] SearchDto: url(s)?";
+        let lines = parse_document(doc);
+        let LineKind::DtoDef { properties, .. } = &lines[0].kind else { panic!("expected DtoDef") };
+        let (prop, optional) = split_optional(&properties[0]);
+        assert!(optional);
+        assert_eq!(parse_array_property(prop), Some(("url".to_string(), "s".to_string())));
+    }
+
+    #[test]
+    fn test_step_spans_match_source_columns() {
+        let doc = "[REQ] user.get(IdDto): UserDto\n    db:user.load(user): user // fetch\n    id::create(IdDto): id\n\n[DTO] UserDto: id\n    a user";
+        let lines = parse_document(doc);
+        let source: Vec<&str> = doc.lines().collect();
+        let text = |line: usize, span: Option<Span>| {
+            let span = span.expect("span");
+            source[line].chars().skip(span.start).take(span.end - span.start).collect::<String>()
+        };
+
+        assert_eq!(lines[1].spans.verb, Some(Span { start: 12, end: 16 }));
+        assert_eq!(text(1, lines[1].spans.noun), "user");
+        assert_eq!(lines[1].spans.noun, Some(Span { start: 7, end: 11 }));
+        // The output is the `user` after `):`, not the param
+        assert_eq!(lines[1].spans.output, Some(Span { start: 24, end: 28 }));
+        assert_eq!(text(2, lines[2].spans.verb), "create");
+        assert_eq!(text(0, lines[0].spans.verb), "get");
+        assert_eq!(text(0, lines[0].spans.output), "UserDto");
+        assert_eq!(text(4, lines[4].spans.name), "UserDto");
+        assert_eq!(lines[5].spans, Spans::default());
+    }
+
+    #[test]
+    fn test_parse_dto_nested_properties() {
+        let doc = "[DTO] FooDto:\n    a foo\n    name: string\n    tags: tag(s)\n    url(s): url\n    note?: string";
+        let lines = parse_document(doc);
+        assert!(matches!(&lines[0].kind, LineKind::DtoDef { name, properties } if name == "FooDto" && properties.is_empty()));
+        assert!(matches!(&lines[1].kind, LineKind::DtoDesc { text, .. } if text == "a foo"));
+        assert!(matches!(&lines[2].kind, LineKind::DtoProperty { name, type_name }
+            if name == "name" && type_name == "string"));
+        assert!(matches!(&lines[3].kind, LineKind::DtoArrayProperty { property_name, base_type, suffix }
+            if property_name == "tags" && base_type == "tag" && suffix == "s"));
+        assert!(matches!(&lines[4].kind, LineKind::DtoArrayProperty { property_name, base_type, suffix }
+            if property_name == "urls" && base_type == "url" && suffix == "s"));
+        assert!(matches!(&lines[5].kind, LineKind::DtoProperty { name, .. } if name == "note?"));
+
+        // A colon inside prose is still a description
+        let lines = parse_document("[DTO] FooDto: id\n    note: ids are opaque");
+        assert!(matches!(&lines[1].kind, LineKind::DtoDesc { .. }));
+    }
+
     #[test]
     fn test_parse_dto_multiple_props() {
         let doc = "[DTO] GetRecordingDto: providerName, externalId";
@@ -853,6 +1423,27 @@ mod tests {
         assert!(matches!(&lines[1].kind, LineKind::NonDesc { text, .. } if text == "a storage system"));
     }
 
+    #[test]
+    fn test_parse_doc_block() {
+        let doc = "[DTO] UserDto: name
+[DOC] Why users are cached
+    db:user.load(IdDto) is slow, see https://example.com
+
+    [REQ] in prose stays prose
+        not-a-fault
+[TYP] name: string
+    a name";
+        let lines = parse_document(doc);
+        assert!(matches!(&lines[1].kind, LineKind::DocDef { title } if title == "Why users are cached"));
+        assert!(matches!(&lines[2].kind, LineKind::DocLine { text, indent: 4 }
+            if text == "db:user.load(IdDto) is slow, see https://example.com"));
+        assert!(matches!(lines[3].kind, LineKind::Empty));
+        assert!(matches!(&lines[4].kind, LineKind::DocLine { text, .. } if text == "[REQ] in prose stays prose"));
+        assert!(matches!(&lines[5].kind, LineKind::DocLine { text, indent: 8 } if text == "not-a-fault"));
+        assert!(matches!(&lines[6].kind, LineKind::TypDef { name, .. } if name == "name"));
+        assert!(matches!(&lines[7].kind, LineKind::TypDesc { text, .. } if text == "a name"));
+    }
+
     #[test]
     fn test_parse_mod() {
         let doc = "[MOD] checkout";
@@ -929,6 +1520,23 @@ mod tests {
             if modifier == &Some("core".to_string())));
     }
 
+    #[test]
+    fn test_parse_tab_indented_step() {
+        let doc = "\tid::create(name): id";
+        let lines = parse_document(doc);
+        assert_eq!(lines[0].raw_indent, 1);
+        assert_eq!(lines[0].indent, 4);
+        assert!(matches!(&lines[0].kind, LineKind::Step { noun, indent: 4, .. } if noun == "id"));
+    }
+
+    #[test]
+    fn test_parse_custom_tab_width() {
+        let doc = "[REQ] a.run(InDto): OutDto\n\t\tdb:store.get(id): id\n\t\t\tnot-found";
+        let lines = parse_document_with_tab_width(doc, 2);
+        assert_eq!(lines[1].indent, 4);
+        assert!(matches!(&lines[2].kind, LineKind::Fault { indent: 6, .. }));
+    }
+
     #[test]
     fn test_parse_ctr_synonym() {
         let doc = "    [CTR] storage";
@@ -936,4 +1544,30 @@ mod tests {
         assert!(matches!(&lines[0].kind, LineKind::New { class_name, indent: 4 }
             if class_name == "storage"));
     }
+
+    #[test]
+    fn test_parse_constructor_missing_class_names_the_tag() {
+        let lines = parse_document("    [NEW]\n    [CTR]");
+        assert!(matches!(&lines[0].kind, LineKind::Unknown(msg) if msg == "[NEW] missing class name"));
+        assert!(matches!(&lines[1].kind, LineKind::Unknown(msg) if msg == "[CTR] missing class name"));
+    }
+
+    #[test]
+    fn document_views_and_positions() {
+        let doc = Document::parse("[REQ] user.get(IdDto): UserDto\n    db:user.load(IdDto): UserDto\n");
+        let req = doc.reqs().next().unwrap();
+        assert_eq!((req.noun, req.verb, req.input, req.output), ("user", "get", "IdDto", "UserDto"));
+        assert!(matches!(doc.line_at(1).unwrap().kind, LineKind::BoundaryStep { .. }));
+        assert!(doc.line_at(5).is_none());
+        assert_eq!(doc.symbol_at(1, 9), Some("user"));
+        assert_eq!(doc.symbol_at(1, 6), None); // on the ':'
+        assert_eq!(doc.symbol_at(0, 99), None);
+    }
+
+    #[test]
+    fn test_parse_step_inline_comment() {
+        let lines = parse_document("    id::create(providerName): id // creates an id\n    db:user.load(id): UserDto");
+        assert!(matches!(&lines[0].kind, LineKind::Step { comment: Some(c), .. } if c == "creates an id"));
+        assert!(matches!(&lines[1].kind, LineKind::BoundaryStep { comment: None, .. }));
+    }
 }
@@ -0,0 +1,50 @@
+//! Definitions collected across one or more parsed .rune files
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{LineKind, ParsedLine};
+
+/// The `[DTO]` names and `[TYP]` aliases a set of files defines
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable {
+    pub dtos: HashSet<String>,
+    /// `[TYP]` name -> declared type
+    pub types: HashMap<String, String>,
+}
+
+impl SymbolTable {
+    pub fn from_lines(lines: &[ParsedLine]) -> Self {
+        let mut table = Self::default();
+        table.add_lines(lines);
+        table
+    }
+
+    /// Add every definition in `lines`; the first definition of a name wins
+    pub fn add_lines(&mut self, lines: &[ParsedLine]) {
+        for line in lines {
+            match &line.kind {
+                LineKind::DtoDef { name, .. } => {
+                    self.dtos.insert(name.clone());
+                }
+                LineKind::TypDef { name, type_name, .. } => {
+                    self.types.entry(name.clone()).or_insert_with(|| type_name.clone());
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_document;
+
+    #[test]
+    fn collects_across_files() {
+        let mut table = SymbolTable::from_lines(&parse_document("[DTO] UserDto: name\n    a user"));
+        table.add_lines(&parse_document("[TYP] name: string\n    a name"));
+        assert!(table.dtos.contains("UserDto"));
+        assert_eq!(table.types.get("name").map(String::as_str), Some("string"));
+    }
+}
@@ -0,0 +1,1813 @@
+//! Client-agnostic validation of .rune documents, shared by the LSP and
+//! `rune-syntax validate` so both report the same rules.
+//!
+//! The rules mirror what `rune sync`/`manifest` (the TS parser) enforces:
+//! structure plus the documented shape rules. Usage rules the generator does
+//! not perform are opt-in through [`Config`] flags.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::casing::to_pascal_case;
+use crate::codes::DiagnosticCode;
+use crate::config::Config;
+use crate::symbols::SymbolTable;
+use crate::{comment_start, parse_array_property, source_lines, split_optional, LineKind, ParsedLine, Span};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// One problem found in a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// 0-based line
+    pub line: usize,
+    /// Character columns on `line`; `None` when the whole line is at fault
+    pub span: Option<Span>,
+    pub severity: Severity,
+    pub code: DiagnosticCode,
+    pub message: String,
+}
+
+/// Faults every project may use without declaring them under a boundary
+pub const KNOWN_FAULTS: &[&str] = &[
+    "not-found",
+    "timeout",
+    "timed-out",
+    "conflict",
+    "already-exists",
+    "invalid-input",
+    "unauthorized",
+    "forbidden",
+    "network-error",
+];
+
+/// What a document is validated against
+#[derive(Debug, Clone, Copy)]
+pub struct ValidateOptions<'a> {
+    pub config: &'a Config,
+    /// DTOs/TYPs defined in other .rune files (the document's directory or
+    /// workspace); references to them resolve as if local
+    pub siblings: &'a SymbolTable,
+}
+
+/// Every diagnostic for a .rune document, in the order the rules find them
+pub fn validate_document(text: &str, opts: ValidateOptions) -> Vec<Diagnostic> {
+    validate_parsed(text, &opts.config.parse(text), opts)
+}
+
+/// `validate_document` for callers that keep `text` parsed with
+/// `opts.config` already, such as the LSP's per-document cache
+pub fn validate_parsed(text: &str, lines: &[ParsedLine], opts: ValidateOptions) -> Vec<Diagnostic> {
+    let ValidateOptions { config, siblings } = opts;
+    let boundaries = config.boundaries();
+    let mut diagnostics = Vec::new();
+    let source: Vec<&str> = source_lines(text).collect();
+
+    // Column limit (80 unless rune.toml says otherwise). [DOC] prose is
+    // exempt from this like from every other rule.
+    let max = config.max_line_length;
+    let doc_lines: HashSet<usize> = lines
+        .iter()
+        .filter(|l| matches!(l.kind, LineKind::DocLine { .. }))
+        .map(|l| l.line_num)
+        .collect();
+    for (line_num, line) in source.iter().enumerate() {
+        if doc_lines.contains(&line_num) {
+            continue;
+        }
+        let len = line.chars().count();
+        if len > max {
+            diagnostics.push(diag_err_at(
+                DiagnosticCode::LineTooLong,
+                line_num,
+                Some(Span { start: max, end: len }),
+                format!("Line exceeds {} columns ({} chars)", max, len),
+            ));
+        }
+
+        // Tabs in the indent, one diagnostic per run of them
+        let indent: Vec<char> = line.chars().take_while(|c| c.is_whitespace()).collect();
+        let mut col = 0;
+        while col < indent.len() {
+            if indent[col] != '\t' {
+                col += 1;
+                continue;
+            }
+            let start = col;
+            while col < indent.len() && indent[col] == '\t' {
+                col += 1;
+            }
+            diagnostics.push(diag_err_at(
+                DiagnosticCode::Indentation,
+                line_num,
+                Some(Span { start, end: col }),
+                "Use spaces for indentation, tabs are not allowed".to_string(),
+            ));
+        }
+    }
+
+    // Definitions collected in the first pass (shape checks only — no usage).
+    let mut seen_reqs: HashSet<String> = HashSet::new();
+    let mut defined_dtos: HashSet<String> = HashSet::new();
+    let mut defined_dtos_lines: HashMap<String, usize> = HashMap::new();
+    let mut defined_types: HashMap<String, String> = HashMap::new();
+    let mut defined_types_lines: HashMap<String, usize> = HashMap::new();
+    defined_dtos.extend(siblings.dtos.iter().cloned());
+    defined_types.extend(siblings.types.iter().map(|(k, v)| (k.clone(), v.clone())));
+    let mut defined_nouns_lines: HashMap<String, usize> = HashMap::new();
+    let mut dto_has_desc: HashSet<String> = HashSet::new();
+    let mut typ_has_desc: HashSet<String> = HashSet::new();
+    let mut non_has_desc: HashSet<String> = HashSet::new();
+    let mut last_typ_name: Option<&str> = None;
+    let mut last_non_name: Option<&str> = None;
+    // DTO -> (line, property name, type it resolves through)
+    let mut dto_properties: HashMap<String, Vec<(usize, String, String)>> = HashMap::new();
+    let mut last_dto_name: Option<String> = None;
+    let mut first_pass_dto: Option<String> = None;
+
+    // First pass: collect DTO/TYP/NON definitions, DTO properties, descriptions.
+    for parsed_line in lines {
+        let line_num = parsed_line.line_num;
+        match &parsed_line.kind {
+            LineKind::DtoDef { name, properties } => {
+                if let Some(&first) = defined_dtos_lines.get(name) {
+                    diagnostics.push(diag_err_at(DiagnosticCode::DuplicateDefinition, line_num, parsed_line.spans.name, format!(
+                        "Duplicate DTO definition '{}' (first defined on line {})",
+                        name, first + 1)));
+                } else {
+                    defined_dtos.insert(name.clone());
+                    defined_dtos_lines.insert(name.clone(), line_num);
+                }
+                for prop in properties {
+                    let (base, _) = split_optional(prop);
+                    let pname = match base.find('(') {
+                        Some(p) => base[..p].to_string(),
+                        None => base.to_string(),
+                    };
+                    dto_properties.entry(name.clone()).or_default().push((line_num, pname.clone(), pname));
+                }
+                first_pass_dto = Some(name.clone());
+                last_dto_name = Some(name.clone());
+            }
+            LineKind::DtoProperty { name, type_name } => {
+                if let Some(d) = &first_pass_dto {
+                    let (pname, _) = split_optional(name);
+                    let ty = normalize_type(type_name).0.to_string();
+                    dto_properties.entry(d.clone()).or_default().push((line_num, pname.to_string(), ty));
+                }
+            }
+            LineKind::DtoArrayProperty { property_name, base_type, .. } => {
+                if let Some(d) = &first_pass_dto {
+                    let (pname, _) = split_optional(property_name);
+                    dto_properties.entry(d.clone()).or_default().push((line_num, pname.to_string(), base_type.clone()));
+                }
+            }
+            // Any non-empty line counts; a description may run over several
+            LineKind::DtoDesc { text, .. } if !text.trim().is_empty() => {
+                if let Some(d) = &last_dto_name {
+                    dto_has_desc.insert(d.clone());
+                }
+            }
+            LineKind::TypDesc { text, .. } if !text.trim().is_empty() => {
+                if let Some(t) = last_typ_name {
+                    typ_has_desc.insert(t.to_string());
+                }
+            }
+            LineKind::NonDesc { text, .. } if !text.trim().is_empty() => {
+                if let Some(n) = last_non_name {
+                    non_has_desc.insert(n.to_string());
+                }
+            }
+            LineKind::Empty => {
+                first_pass_dto = None;
+            }
+            LineKind::TypDef { name, type_name, .. } => {
+                last_typ_name = Some(name);
+                if let Some(&first) = defined_types_lines.get(name) {
+                    diagnostics.push(diag_err_at(DiagnosticCode::DuplicateDefinition, line_num, parsed_line.spans.name, format!(
+                        "Duplicate type definition '{}' (first defined on line {})",
+                        name, first + 1)));
+                } else {
+                    defined_types.insert(name.clone(), type_name.clone());
+                    defined_types_lines.insert(name.clone(), line_num);
+                }
+            }
+            LineKind::NonDef { name } => {
+                last_non_name = Some(name);
+                if let Some(&first) = defined_nouns_lines.get(name) {
+                    diagnostics.push(diag_err_at(DiagnosticCode::DuplicateDefinition, line_num, parsed_line.spans.name, format!(
+                        "Duplicate noun definition '{}' (first defined on line {})",
+                        name, first + 1)));
+                } else {
+                    defined_nouns_lines.insert(name.clone(), line_num);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Every property used in a [DTO] must resolve to a declared type — a
+    // [TYP], a nested [DTO] (direct name or the <Name>Dto convention). Mirrors
+    // the TS parser's check so the LSP flags the same missing-TYP errors.
+    for (dto_name, props) in &dto_properties {
+        for (prop_line, _, ty) in props {
+            let resolved = is_primitive(ty)
+                || defined_types.contains_key(ty)
+                || defined_dtos.contains(ty)
+                || defined_dtos.contains(&format!("{}Dto", to_pascal_case(ty)));
+            if !resolved {
+                diagnostics.push(diag_err(DiagnosticCode::UndefinedReference, *prop_line, format!(
+                    "[DTO] {}: property \"{}\" has no [TYP] or [DTO] — declare \"[TYP] {}: <type>\"",
+                    dto_name, ty, ty)));
+            }
+        }
+    }
+
+    // DTOs that (transitively) contain themselves can never be built. A
+    // property names a nested DTO directly or via the <Name>Dto convention;
+    // a [TYP] of the same name wins, as in the check above. `IdDto: id` is the
+    // wrapper idiom, not a self-reference, so the convention never points a
+    // DTO back at itself.
+    let mut dto_edges: HashMap<&str, Vec<(usize, String)>> = HashMap::new();
+    for (dto_name, props) in &dto_properties {
+        for (prop_line, _, ty) in props {
+            let target = if defined_dtos.contains(ty) {
+                ty.clone()
+            } else if !defined_types.contains_key(ty)
+                && defined_dtos.contains(&format!("{}Dto", to_pascal_case(ty)))
+                && format!("{}Dto", to_pascal_case(ty)) != *dto_name
+            {
+                format!("{}Dto", to_pascal_case(ty))
+            } else {
+                continue;
+            };
+            dto_edges.entry(dto_name.as_str()).or_default().push((*prop_line, target));
+        }
+    }
+    let mut dto_order: Vec<(&String, &usize)> = defined_dtos_lines.iter().collect();
+    dto_order.sort_by_key(|(_, line)| **line);
+    let mut finished: HashSet<&str> = HashSet::new();
+    for (root, _) in dto_order {
+        find_dto_cycles(root, &dto_edges, &mut Vec::new(), &mut finished, &mut diagnostics);
+    }
+
+    let output_span = |line: usize| lines.iter().find(|l| l.line_num == line).and_then(|l| l.spans.output);
+
+    // Second-pass state.
+    let mut method_signatures: HashMap<String, (usize, Vec<String>, String)> = HashMap::new();
+    let mut poly_stack: Vec<usize> = Vec::new(); // indents of open [PLY] scopes
+    let mut poly_blocks: Vec<PolyBlock> = Vec::new();
+    let mut in_req = false;
+    let mut last_step_indent: Option<usize> = None;
+    let mut current_req_output: Option<String> = None;
+    let mut last_step_output: Option<String> = None;
+    let mut last_step_line: Option<usize> = None;
+    let mut current_req_line: Option<usize> = None;
+    let mut req_output_produced = false; // has any step of the REQ yielded its output?
+    let mut last_was_req = false;
+    let mut consecutive_empty: usize = 0;
+    let mut step_faults: (Option<usize>, HashSet<String>) = (None, HashSet::new()); // (step line, faults)
+    let mut returned_depth: Option<usize> = None; // [PLY] depth of the last [RET]
+
+    // Second pass: structure + shape validation.
+    for parsed_line in lines {
+        let line_num = parsed_line.line_num;
+
+        // Close [PLY] scopes whose body has ended (indentation dropped to/below
+        // the [PLY] line). Faults and tags handle their own scope, so only
+        // step-like lines participate here.
+        if let Some(li) = step_like_indent(&parsed_line.kind) {
+            while let Some(&p) = poly_stack.last() {
+                if li <= p {
+                    poly_stack.pop();
+                } else {
+                    break;
+                }
+            }
+        }
+        let depth = poly_stack.len();
+        let step_expected = if depth == 0 { 4 } else { poly_stack.last().unwrap() + 4 };
+
+        // Anything after a [RET] in the same scope never runs. A [RET] in a
+        // [CSE] only ends that branch: the next case, or leaving the [PLY],
+        // makes steps reachable again.
+        if let Some(d) = returned_depth {
+            let branch_closed = depth < d
+                || (depth == d && matches!(parsed_line.kind, LineKind::Cse { .. }));
+            if branch_closed {
+                returned_depth = None;
+            } else if step_like_indent(&parsed_line.kind).is_some() {
+                diagnostics.push(diag_warn(DiagnosticCode::UnreachableStep, line_num, "Unreachable step after [RET]".to_string()));
+            }
+        }
+
+        match &parsed_line.kind {
+            LineKind::Mod { .. } => {
+                in_req = false;
+                poly_stack.clear();
+                last_was_req = false;
+                consecutive_empty = 0;
+            }
+
+            LineKind::Ent { input, output, indent, .. } => {
+                if *indent != 0 {
+                    diagnostics.push(diag_err(DiagnosticCode::Indentation, line_num, "[ENT] must start at column 0".to_string()));
+                }
+                if !input.is_empty() && !input.ends_with("Dto") && !input.starts_with('{') {
+                    diagnostics.push(diag_err(DiagnosticCode::RequirementShape, line_num, format!("[ENT] input must be a DTO, got '{}'", input)));
+                }
+                if !output.ends_with("Dto") {
+                    diagnostics.push(diag_err(DiagnosticCode::RequirementShape, line_num, format!("[ENT] output must be a DTO, got '{}'", output)));
+                }
+                in_req = false;
+                poly_stack.clear();
+                last_was_req = false;
+                consecutive_empty = 0;
+            }
+
+            LineKind::Req { noun, verb, input, output, indent, modifier, .. } => {
+                // The previous REQ's last step must have returned its output DTO.
+                if let (Some(ro), Some(so), Some(sl)) = (&current_req_output, &last_step_output, last_step_line)
+                    && ro != so
+                {
+                    diagnostics.push(diag_err_at(DiagnosticCode::ReturnMismatch, sl, output_span(sl), format!("Last step must return '{}' (REQ output), got '{}'", ro, so)));
+                }
+                if let (Some(ro), Some(rl), Some(_)) = (&current_req_output, current_req_line, last_step_line)
+                    && !req_output_produced
+                {
+                    diagnostics.push(diag_err_at(DiagnosticCode::ReturnMismatch, rl, output_span(rl), format!("No step produces the required output '{}'", ro)));
+                }
+                if let Some(m) = modifier {
+                    // Parity with the TS parser: the core modifier keeps its
+                    // specific message; any other modifier gets the generic one.
+                    if m == "core" {
+                        diagnostics.push(diag_err(DiagnosticCode::RequirementShape, line_num, "[REQ:core] is invalid — coordinators are module-level".to_string()));
+                    } else {
+                        diagnostics.push(diag_err(DiagnosticCode::RequirementShape, line_num, "[REQ] does not take a modifier".to_string()));
+                    }
+                }
+                if *indent != 0 {
+                    diagnostics.push(diag_err(DiagnosticCode::Indentation, line_num, "[REQ] must start at column 0".to_string()));
+                }
+                let key = format!("{}.{}", noun, verb);
+                if seen_reqs.contains(&key) {
+                    diagnostics.push(diag_err(DiagnosticCode::DuplicateRequirement, line_num, format!("Duplicate REQ: {}", key)));
+                }
+                seen_reqs.insert(key);
+                if !input.is_empty() && !input.ends_with("Dto") && !input.starts_with('{') {
+                    diagnostics.push(diag_err(DiagnosticCode::RequirementShape, line_num, format!("REQ input must be a DTO, got '{}'", input)));
+                }
+                if !output.ends_with("Dto") && output != "void" {
+                    diagnostics.push(diag_err(DiagnosticCode::RequirementShape, line_num, format!("REQ output must be a DTO or void, got '{}'", output)));
+                }
+                if last_was_req && consecutive_empty < 2 {
+                    diagnostics.push(diag_warn(DiagnosticCode::RequirementSpacing, line_num, "Expected double blank line between requirements".to_string()));
+                }
+                in_req = true;
+                poly_stack.clear();
+                returned_depth = None;
+                // A fire-and-forget `void` REQ has no output for its steps to produce
+                current_req_output = (output != "void").then(|| output.clone());
+                current_req_line = Some(line_num);
+                req_output_produced = false;
+                last_step_output = None;
+                last_step_line = None;
+                last_step_indent = None;
+                last_was_req = true;
+                consecutive_empty = 0;
+            }
+
+            LineKind::Step { noun, verb, indent, params, output, is_static, .. } => {
+                if !in_req {
+                    diagnostics.push(diag_err(DiagnosticCode::OutOfScope, line_num, "Step outside [REQ]".to_string()));
+                    continue;
+                }
+                if *indent != step_expected {
+                    diagnostics.push(diag_err(DiagnosticCode::Indentation, line_num, format!("Step should be indented {} spaces, got {}", step_expected, indent)));
+                }
+                check_sig(&mut diagnostics, &mut method_signatures, line_num, parsed_line.spans.verb, noun, verb, *is_static, params, output);
+                if config.strict_dto_params {
+                    check_dto_params(&mut diagnostics, lines, line_num, params, &defined_dtos);
+                }
+                if output.is_empty() {
+                    diagnostics.push(diag_err(DiagnosticCode::MissingReturnType, line_num, "Step missing return type".to_string()));
+                }
+                last_step_output = Some(output.clone());
+                last_step_line = Some(line_num);
+                last_step_indent = Some(*indent);
+                last_was_req = false;
+                consecutive_empty = 0;
+            }
+
+            LineKind::BoundaryStep { prefix, noun, verb, indent, params, output, is_static, .. } => {
+                if !in_req {
+                    diagnostics.push(diag_err(DiagnosticCode::OutOfScope, line_num, "Boundary step outside [REQ]".to_string()));
+                    continue;
+                }
+                if *indent != step_expected {
+                    diagnostics.push(diag_err(DiagnosticCode::Indentation, line_num, format!("Boundary step should be indented {} spaces, got {}", step_expected, indent)));
+                }
+                check_sig(&mut diagnostics, &mut method_signatures, line_num, parsed_line.spans.verb, noun, verb, *is_static, params, output);
+                if config.strict_dto_params {
+                    check_dto_params(&mut diagnostics, lines, line_num, params, &defined_dtos);
+                }
+                if !boundaries.iter().any(|(bp, _)| bp == prefix) {
+                    diagnostics.push(diag_err(DiagnosticCode::InvalidBoundary, line_num, format!("Invalid boundary prefix: {}", prefix)));
+                }
+                for param in params {
+                    if !is_dto_or_primitive(param, &defined_types) {
+                        diagnostics.push(diag_err(DiagnosticCode::InvalidBoundary, line_num, format!("{} boundary parameter must be a DTO or primitive, got '{}'", prefix, param)));
+                    }
+                }
+                if !is_dto_or_primitive(output, &defined_types) {
+                    diagnostics.push(diag_err_at(DiagnosticCode::InvalidBoundary, line_num, parsed_line.spans.output, format!("{} boundary must return a DTO or primitive, got '{}'", prefix, output)));
+                }
+                if config.warn_fs_path_params
+                    && prefix == "fs:"
+                    && let Some(path) = params.first()
+                    && !path.ends_with("Dto")
+                    && path != "string"
+                    && defined_types.get(path).map(String::as_str) != Some("string")
+                {
+                    diagnostics.push(diag_warn(DiagnosticCode::FsPathParam, line_num, "fs: path parameter should be a string".to_string()));
+                }
+                last_step_output = Some(output.clone());
+                last_step_line = Some(line_num);
+                last_step_indent = Some(*indent);
+                last_was_req = false;
+                consecutive_empty = 0;
+            }
+
+            LineKind::Fault { names, indent } => {
+                if let Some(step_indent) = last_step_indent {
+                    let expected = step_indent + 2;
+                    if *indent != expected {
+                        diagnostics.push(diag_err(DiagnosticCode::Indentation, line_num, format!("Fault should be indented {} spaces (2 more than step), got {}", expected, indent)));
+                    }
+                    if step_faults.0 != last_step_line {
+                        step_faults = (last_step_line, HashSet::new());
+                    }
+                    for name in names {
+                        if !step_faults.1.insert(name.clone()) {
+                            diagnostics.push(diag_warn(DiagnosticCode::DuplicateFault, line_num, format!("Duplicate fault '{}' under the same step", name)));
+                        }
+                    }
+                } else {
+                    diagnostics.push(diag_err(DiagnosticCode::OutOfScope, line_num, "Orphan fault: not under a step".to_string()));
+                }
+                last_was_req = false;
+                consecutive_empty = 0;
+            }
+
+            LineKind::Ply { noun, verb, params, output, indent, is_static, .. } => {
+                if !in_req {
+                    diagnostics.push(diag_err(DiagnosticCode::OutOfScope, line_num, "[PLY] outside [REQ]".to_string()));
+                    continue;
+                }
+                if *indent != step_expected {
+                    diagnostics.push(diag_err(DiagnosticCode::Indentation, line_num, format!("[PLY] should be indented {} spaces, got {}", step_expected, indent)));
+                }
+                check_sig(&mut diagnostics, &mut method_signatures, line_num, parsed_line.spans.verb, noun, verb, *is_static, params, output);
+                poly_stack.push(*indent);
+                poly_blocks.push(PolyBlock { noun, output, line: line_num, indent: *indent, cases: Vec::new() });
+                last_step_output = Some(output.clone());
+                last_step_line = Some(line_num);
+                last_step_indent = Some(*indent);
+                last_was_req = false;
+                consecutive_empty = 0;
+            }
+
+            LineKind::Cse { name, indent } => {
+                if poly_stack.is_empty() {
+                    diagnostics.push(diag_err(DiagnosticCode::OutOfScope, line_num, format!("[CSE] {} must be inside a [PLY] block", name)));
+                } else {
+                    let expected = poly_stack.last().unwrap() + 4;
+                    if *indent != expected {
+                        diagnostics.push(diag_err(DiagnosticCode::Indentation, line_num, format!("[CSE] should be indented {} spaces, got {}", expected, indent)));
+                    }
+                    let ply_indent = *poly_stack.last().unwrap();
+                    if let Some(block) = poly_blocks.iter_mut().rev().find(|b| b.indent == ply_indent) {
+                        block.cases.push((name, line_num));
+                    }
+                }
+                last_step_indent = None;
+                last_was_req = false;
+                consecutive_empty = 0;
+            }
+
+            LineKind::DtoDef { name, .. } => {
+                if !name.ends_with("Dto") {
+                    diagnostics.push(diag_err_at(DiagnosticCode::DtoName, line_num, parsed_line.spans.name, format!("DTO name '{}' must end in 'Dto'", name)));
+                }
+                if let Some(col) = source.get(line_num).and_then(|l| trailing_comma(l)) {
+                    diagnostics.push(diag_warn_at(DiagnosticCode::TrailingComma, line_num, Some(Span { start: col, end: col + 1 }), format!(
+                        "Trailing comma in the property list of '{}'", name)));
+                }
+                in_req = false;
+                poly_stack.clear();
+                last_step_indent = None;
+                last_was_req = false;
+                consecutive_empty = 0;
+            }
+
+            LineKind::TypDef { name, type_name, modifier } => {
+                let (base, is_array) = normalize_type(type_name);
+                if is_array {
+                    // Lists of primitives or [TYP]s are fine, either spelling
+                    if base.ends_with("Dto") {
+                        diagnostics.push(diag_err(DiagnosticCode::NonPrimitiveType, line_num, format!("Type '{}' cannot reference DTO '{}' - types must be primitives", name, base)));
+                    }
+                } else if !is_valid_primitive_type(type_name) {
+                    if type_name.ends_with("Dto") {
+                        diagnostics.push(diag_err(DiagnosticCode::NonPrimitiveType, line_num, format!("Type '{}' cannot reference DTO '{}' - types must be primitives", name, type_name)));
+                    } else if defined_types.contains_key(type_name) {
+                        diagnostics.push(diag_err(DiagnosticCode::NonPrimitiveType, line_num, format!("Type '{}' cannot reference type '{}' - types must be primitives", name, type_name)));
+                    }
+                }
+                for member in tuple_members(type_name).unwrap_or_default() {
+                    let (member_type, _) = normalize_type(member);
+                    if !is_primitive(member_type) && !defined_types.contains_key(member_type) {
+                        diagnostics.push(diag_err(DiagnosticCode::NonPrimitiveType, line_num, format!("Tuple member '{}' must be a primitive", member)));
+                    }
+                }
+                if let Some(m) = modifier {
+                    for msg in validate_typ_modifiers(m, name, type_name) {
+                        diagnostics.push(diag_err(DiagnosticCode::InvalidTypeModifier, line_num, msg));
+                    }
+                }
+                in_req = false;
+                poly_stack.clear();
+                last_was_req = false;
+                consecutive_empty = 0;
+            }
+
+            LineKind::NonDef { .. } | LineKind::DocDef { .. } => {
+                in_req = false;
+                poly_stack.clear();
+                last_was_req = false;
+                consecutive_empty = 0;
+            }
+
+            LineKind::Ret { value, indent } => {
+                if !in_req {
+                    diagnostics.push(diag_err(DiagnosticCode::OutOfScope, line_num, "[RET] outside [REQ]".to_string()));
+                    continue;
+                }
+                if *indent != step_expected {
+                    diagnostics.push(diag_err(DiagnosticCode::Indentation, line_num, format!("[RET] should be indented {} spaces, got {}", step_expected, indent)));
+                }
+                last_step_output = Some(value.clone());
+                last_step_line = Some(line_num);
+                last_step_indent = Some(*indent);
+                last_was_req = false;
+                consecutive_empty = 0;
+                returned_depth.get_or_insert(depth);
+            }
+
+            LineKind::New { indent, .. } => {
+                if !in_req {
+                    diagnostics.push(diag_err(DiagnosticCode::OutOfScope, line_num, "[NEW] outside [REQ]".to_string()));
+                    continue;
+                }
+                if *indent != step_expected {
+                    diagnostics.push(diag_err(DiagnosticCode::Indentation, line_num, format!("[NEW] should be indented {} spaces, got {}", step_expected, indent)));
+                }
+                last_step_indent = Some(*indent);
+                last_was_req = false;
+                consecutive_empty = 0;
+            }
+
+            LineKind::MultilineContinuation { expected_indent, actual_indent } => {
+                if expected_indent != actual_indent {
+                    diagnostics.push(diag_err(DiagnosticCode::Indentation, line_num, format!(
+                        "Inconsistent indentation: expected {} spaces, got {}",
+                        expected_indent, actual_indent)));
+                }
+                consecutive_empty = 0;
+            }
+
+            LineKind::Unknown(text) => {
+                let faults = if parsed_line.indent >= 6 { canonical_faults(text) } else { None };
+                if let Some(faults) = faults {
+                    diagnostics.push(diag_err(DiagnosticCode::FaultName, line_num, format!(
+                        "Fault names are lowercase and hyphenated, did you mean {}?", faults)));
+                } else {
+                    let msg = if text.contains('.') && !text.contains('(') {
+                        "missing parameters, expected 'noun.verb(args): type'".to_string()
+                    } else if text.contains('(') && !text.contains(':') {
+                        "missing return type after ':'".to_string()
+                    } else if text.starts_with('[') {
+                        text.clone()
+                    } else {
+                        format!("unexpected '{}', expected a tag, step, fault, or definition", text)
+                    };
+                    diagnostics.push(diag_err(DiagnosticCode::ParseError, line_num, format!("Parse error: {}", msg)));
+                }
+                consecutive_empty = 0;
+            }
+
+            LineKind::Empty => {
+                consecutive_empty += 1;
+                returned_depth = None;
+            }
+
+            // Definitions handled in the first pass; descriptions / refs are
+            // prose with no second-pass checks.
+            LineKind::DtoDesc { .. }
+            | LineKind::TypDesc { .. }
+            | LineKind::NonDesc { .. }
+            | LineKind::DtoProperty { .. }
+            | LineKind::DtoArrayProperty { .. }
+            | LineKind::DtoRef(_) => {
+                consecutive_empty = 0;
+            }
+
+            // [DOC] prose is never validated
+            LineKind::DocLine { .. } => {}
+
+            LineKind::Comment { .. } => {}
+        }
+
+        if in_req && last_step_line == Some(line_num) && last_step_output == current_req_output {
+            req_output_produced = true;
+        }
+    }
+
+    // Final REQ's last step must return its output DTO.
+    if let (Some(ro), Some(so), Some(sl)) = (&current_req_output, &last_step_output, last_step_line)
+        && ro != so
+    {
+        diagnostics.push(diag_err_at(DiagnosticCode::ReturnMismatch, sl, output_span(sl), format!("Last step must return '{}' (REQ output), got '{}'", ro, so)));
+    }
+    if let (Some(ro), Some(rl), Some(_)) = (&current_req_output, current_req_line, last_step_line)
+        && !req_output_produced
+    {
+        diagnostics.push(diag_err_at(DiagnosticCode::ReturnMismatch, rl, output_span(rl), format!("No step produces the required output '{}'", ro)));
+    }
+
+    // Every [CSE] runs at least one step, and a [PLY] over a noun that has
+    // a string-union [TYP] must branch on exactly the union's members.
+    // Each case implements the [PLY] method, so like a [REQ] its last step
+    // (or [RET]) must produce the declared output.
+    for PolyBlock { noun, output, line: ply_line, cases, .. } in &poly_blocks {
+        for (case, case_line) in cases {
+            if !case_has_steps(lines, *case_line) {
+                diagnostics.push(diag_warn(DiagnosticCode::EmptyCase, *case_line, format!("Case '{}' has no steps", case)));
+            } else if let Some((last_line, last_output)) = case_last_step(lines, *case_line)
+                && last_output != *output
+            {
+                diagnostics.push(diag_err_at(DiagnosticCode::ReturnMismatch, last_line, output_span(last_line), format!(
+                    "Last step of case '{}' must return '{}' ([PLY] output), got '{}'",
+                    case, output, last_output)));
+            }
+        }
+        let Some(members) = defined_types.get(*noun).and_then(|t| union_members(t)) else { continue };
+        for (case, case_line) in cases {
+            if !members.contains(case) {
+                diagnostics.push(diag_err(DiagnosticCode::CaseNotInUnion, *case_line, format!(
+                    "[CSE] {} is not a member of [TYP] {} ({})",
+                    case, noun, members.join(" | "))));
+            }
+        }
+        for member in &members {
+            if !cases.iter().any(|(c, _)| c == member) {
+                diagnostics.push(diag_warn(DiagnosticCode::MissingUnionCase, *ply_line, format!(
+                    "[PLY] {} has no [CSE] for union member '{}'",
+                    noun, member)));
+            }
+        }
+    }
+
+    // Duplicate DTO properties within the same DTO.
+    for (dto_name, props) in &dto_properties {
+        let mut seen: HashMap<&String, usize> = HashMap::new();
+        for (line_num, prop_name, _) in props {
+            if let Some(&first) = seen.get(prop_name) {
+                diagnostics.push(diag_err(DiagnosticCode::DuplicateProperty, *line_num, format!(
+                    "Duplicate property '{}' in {} (first defined on line {})",
+                    prop_name, dto_name, first + 1)));
+            } else {
+                seen.insert(prop_name, *line_num);
+            }
+        }
+    }
+
+    // A noun with both boundary and pure methods lands wholly in impure/;
+    // usually a modeling mistake, so flag every step that touches it.
+    if config.warn_mixed_purity {
+        let mut pure_steps: HashMap<&str, Vec<usize>> = HashMap::new();
+        let mut boundary_steps: HashMap<&str, Vec<usize>> = HashMap::new();
+        for parsed_line in lines {
+            match &parsed_line.kind {
+                LineKind::Step { noun, .. } => pure_steps.entry(noun).or_default().push(parsed_line.line_num),
+                LineKind::BoundaryStep { noun, .. } => {
+                    boundary_steps.entry(noun).or_default().push(parsed_line.line_num)
+                }
+                _ => {}
+            }
+        }
+        for (noun, boundary_lines) in &boundary_steps {
+            let Some(pure_lines) = pure_steps.get(noun) else { continue };
+            for line_num in boundary_lines.iter().chain(pure_lines) {
+                diagnostics.push(diag_warn(DiagnosticCode::MixedPurity, *line_num, format!(
+                    "Noun '{}' mixes boundary and pure methods; it will be generated as impure",
+                    noun)));
+            }
+        }
+    }
+
+    // A [REQ] noun seen once next to a near-identical one that several
+    // requirements share is most likely a typo of it.
+    if config.warn_noun_typos {
+        let mut req_nouns: Vec<(&str, usize)> = Vec::new();
+        let mut noun_counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for parsed_line in lines {
+            if let LineKind::Req { noun, .. } = &parsed_line.kind {
+                req_nouns.push((noun, parsed_line.line_num));
+                *noun_counts.entry(noun).or_default() += 1;
+            }
+        }
+        for (noun, line_num) in req_nouns {
+            if noun_counts[noun] != 1 {
+                continue;
+            }
+            let intended = noun_counts.iter().find(|(other, count)| **count > 1 && within_one_edit(noun, other));
+            if let Some((intended, _)) = intended {
+                diagnostics.push(diag_warn(DiagnosticCode::NounTypo, line_num, format!(
+                    "Noun '{}' is used once; did you mean '{}'?", noun, intended)));
+            }
+        }
+    }
+
+    // A noun only ever called as an instance, with nothing that returns,
+    // constructs or statically produces it, has no factory. Boundary nouns
+    // are injected adapters, so any boundary use counts as providing it.
+    if config.warn_unproduced_nouns {
+        let mut receivers: BTreeMap<&str, (usize, Option<Span>)> = BTreeMap::new();
+        let mut produced: HashSet<&str> = HashSet::new();
+        for parsed_line in lines {
+            match &parsed_line.kind {
+                LineKind::Step { noun, output, is_static, .. } | LineKind::Ply { noun, output, is_static, .. } => {
+                    if *is_static {
+                        produced.insert(noun);
+                    } else {
+                        receivers.entry(noun).or_insert((parsed_line.line_num, parsed_line.spans.noun));
+                    }
+                    produced.insert(normalize_type(output).0);
+                }
+                LineKind::BoundaryStep { noun, output, .. } => {
+                    produced.insert(noun);
+                    produced.insert(normalize_type(output).0);
+                }
+                LineKind::New { class_name, .. } => {
+                    produced.insert(class_name);
+                }
+                _ => {}
+            }
+        }
+        for (noun, (line_num, span)) in receivers {
+            if !produced.contains(noun) {
+                diagnostics.push(diag_warn_at(DiagnosticCode::UnproducedNoun, line_num, span, format!(
+                    "Noun '{}' is only used as an instance; no step returns it and nothing constructs it",
+                    noun)));
+            }
+        }
+    }
+
+    // Opt-in: a `[TYP] x: Class` exists to be instantiated with [NEW]/[CTR];
+    // one that is never constructed (nor called statically) is likely stale.
+    if config.warn_unconstructed_classes {
+        let mut class_types: Vec<(&str, usize)> = Vec::new();
+        let mut constructed: HashSet<&str> = HashSet::new();
+        for parsed_line in lines {
+            match &parsed_line.kind {
+                LineKind::TypDef { name, type_name, .. } if type_name == "Class" => {
+                    class_types.push((name, parsed_line.line_num));
+                }
+                LineKind::New { class_name, .. } => {
+                    constructed.insert(class_name);
+                }
+                LineKind::Step { noun, is_static: true, .. }
+                | LineKind::BoundaryStep { noun, is_static: true, .. }
+                | LineKind::Ply { noun, is_static: true, .. } => {
+                    constructed.insert(noun);
+                }
+                _ => {}
+            }
+        }
+        for (name, line_num) in class_types {
+            if !constructed.contains(name) {
+                diagnostics.push(diag_warn(DiagnosticCode::UnconstructedClass, line_num, format!(
+                    "Class type '{}' is never constructed (add '[CTR] {}' where it is first used)",
+                    name, name)));
+            }
+        }
+    }
+
+    // Opt-in: a fault that only ever appears under pure steps — no boundary
+    // raises it anywhere in the file — must come from the fault vocabulary,
+    // otherwise it is most likely a typo.
+    if let Some(extra) = &config.faults {
+        let mut boundary_faults: HashSet<&str> = HashSet::new();
+        let mut pure_faults: Vec<(usize, &str)> = Vec::new();
+        let mut under_boundary = false;
+        for parsed_line in lines {
+            match &parsed_line.kind {
+                LineKind::BoundaryStep { .. } => under_boundary = true,
+                LineKind::Step { .. } | LineKind::Ply { .. } => under_boundary = false,
+                LineKind::Fault { names, .. } => {
+                    for name in names {
+                        if under_boundary {
+                            boundary_faults.insert(name);
+                        } else {
+                            pure_faults.push((parsed_line.line_num, name));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        for (line_num, name) in pure_faults {
+            if !boundary_faults.contains(name) && !KNOWN_FAULTS.contains(&name) && !extra.iter().any(|f| f == name) {
+                diagnostics.push(diag_warn(DiagnosticCode::UnknownFault, line_num, format!(
+                    "Unknown fault '{}': not raised by any boundary and not in the fault vocabulary", name)));
+            }
+        }
+    }
+
+    // Every DTO needs a description.
+    for (dto_name, line_num) in &defined_dtos_lines {
+        if !dto_has_desc.contains(dto_name) {
+            diagnostics.push(diag_err(DiagnosticCode::DtoMissingDescription, *line_num, format!(
+                "DTO '{}' is missing a description (add a 4-space indented description on the next line)",
+                dto_name)));
+        }
+    }
+
+    // Opt-in: [TYP]s and [NON]s documented like DTOs.
+    if config.require_type_descriptions {
+        for (kind, defined, described) in [("Type", &defined_types_lines, &typ_has_desc), ("Noun", &defined_nouns_lines, &non_has_desc)] {
+            for (name, line_num) in defined {
+                if !described.contains(name) {
+                    diagnostics.push(diag_warn(DiagnosticCode::TypeMissingDescription, *line_num, format!(
+                        "{} '{}' is missing a description (add a 4-space indented description on the next line)",
+                        kind, name)));
+                }
+            }
+        }
+    }
+
+    diagnostics
+}
+
+/// Depth-first walk over DTO property edges; every back-edge is a cycle,
+/// reported on the property line that closes it.
+fn find_dto_cycles<'a>(
+    dto: &'a str,
+    edges: &'a HashMap<&str, Vec<(usize, String)>>,
+    stack: &mut Vec<&'a str>,
+    finished: &mut HashSet<&'a str>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if finished.contains(dto) {
+        return;
+    }
+    stack.push(dto);
+    for (prop_line, target) in edges.get(dto).into_iter().flatten() {
+        if let Some(pos) = stack.iter().position(|d| *d == target) {
+            let mut path: Vec<&str> = stack[pos..].to_vec();
+            path.push(target);
+            diagnostics.push(diag_err(DiagnosticCode::CyclicDto, *prop_line, format!("Cyclic DTO reference: {}", path.join(" -> "))));
+        } else {
+            find_dto_cycles(target, edges, stack, finished, diagnostics);
+        }
+    }
+    stack.pop();
+    finished.insert(dto);
+}
+
+/// The hyphenated spelling of a line of would-be faults (`not_found`,
+/// `notFound`), or None when the line is not one or is already canonical.
+fn canonical_faults(text: &str) -> Option<String> {
+    let mut changed = false;
+    let mut fixed = Vec::new();
+    for word in text.split_whitespace() {
+        if !word.starts_with(|c: char| c.is_ascii_alphabetic())
+            || !word.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return None;
+        }
+        let mut name = String::new();
+        let mut prev_lower = false;
+        for c in word.chars() {
+            if c == '_' || c == '-' {
+                if !name.is_empty() && !name.ends_with('-') {
+                    name.push('-');
+                }
+            } else {
+                // `notFound` splits at the case change, `HTTPerror` stays whole
+                if c.is_ascii_uppercase() && prev_lower {
+                    name.push('-');
+                }
+                name.push(c.to_ascii_lowercase());
+            }
+            prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        }
+        let name = name.trim_end_matches('-').to_string();
+        changed |= name != word;
+        fixed.push(name);
+    }
+    (changed && !fixed.is_empty()).then(|| fixed.join(" "))
+}
+
+fn diag_err(code: DiagnosticCode, line: usize, message: String) -> Diagnostic {
+    diag_err_at(code, line, None, message)
+}
+
+/// `diag_err` narrowed to a token, when the parser located it
+fn diag_err_at(code: DiagnosticCode, line: usize, span: Option<Span>, message: String) -> Diagnostic {
+    Diagnostic { line, span, severity: Severity::Error, code, message }
+}
+
+fn diag_warn(code: DiagnosticCode, line: usize, message: String) -> Diagnostic {
+    diag_warn_at(code, line, None, message)
+}
+
+fn diag_warn_at(code: DiagnosticCode, line: usize, span: Option<Span>, message: String) -> Diagnostic {
+    Diagnostic { line, span, severity: Severity::Warning, code, message }
+}
+
+/// Whether `a` and `b` differ by at most one inserted, removed or replaced
+/// character (Levenshtein distance <= 1)
+fn within_one_edit(a: &str, b: &str) -> bool {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if long.len() - short.len() > 1 {
+        return false;
+    }
+    let prefix = short.iter().zip(&long).take_while(|(x, y)| x == y).count();
+    let skip = if short.len() == long.len() { 1 } else { 0 };
+    short.get(prefix + skip..).unwrap_or_default() == long.get(prefix + 1..).unwrap_or_default()
+}
+
+/// Character column of a comma ending the line's code, before any comment
+fn trailing_comma(line: &str) -> Option<usize> {
+    let code = line[..comment_start(line).unwrap_or(line.len())].trim_end();
+    code.ends_with(',').then(|| code.chars().count() - 1)
+}
+
+/// Indent of the lines that participate in [PLY] scope nesting.
+fn step_like_indent(kind: &LineKind) -> Option<usize> {
+    match kind {
+        LineKind::Step { indent, .. }
+        | LineKind::BoundaryStep { indent, .. }
+        | LineKind::Ply { indent, .. }
+        | LineKind::Cse { indent, .. }
+        | LineKind::Ret { indent, .. }
+        | LineKind::New { indent, .. } => Some(*indent),
+        _ => None,
+    }
+}
+
+/// Whether `noun` is available as an instance on `line`: constructed earlier
+/// in the enclosing [REQ], or in its scope (see `scope_at`). None when `line`
+/// is not inside a [REQ].
+pub fn noun_in_scope(parsed: &[ParsedLine], line: usize, noun: &str) -> Option<bool> {
+    let line = line.min(parsed.len());
+    let req_idx = parsed[..line].iter().rposition(|l| {
+        matches!(l.kind, LineKind::Req { .. } | LineKind::Empty | LineKind::DtoDef { .. } | LineKind::TypDef { .. } | LineKind::NonDef { .. })
+    })?;
+    if !matches!(parsed[req_idx].kind, LineKind::Req { .. }) {
+        return None;
+    }
+    let constructed = parsed[req_idx..line]
+        .iter()
+        .any(|l| matches!(&l.kind, LineKind::New { class_name, .. } if class_name == noun));
+    Some(constructed || scope_at(parsed, line).iter().any(|name| name == noun))
+}
+
+/// Names a step on `line` can use: the enclosing REQ's input DTO and its
+/// properties, then every earlier step output. Outputs from a finished [CSE]
+/// branch (or a closed [PLY] body) drop out of scope by indentation.
+pub fn scope_at(parsed: &[ParsedLine], line: usize) -> Vec<String> {
+    let Some(req_idx) = parsed[..line.min(parsed.len())].iter().rposition(|l| {
+        matches!(l.kind, LineKind::Req { .. } | LineKind::Empty | LineKind::DtoDef { .. } | LineKind::TypDef { .. } | LineKind::NonDef { .. })
+    }) else {
+        return Vec::new();
+    };
+    let LineKind::Req { input, .. } = &parsed[req_idx].kind else {
+        return Vec::new();
+    };
+
+    let mut scope: Vec<(String, usize)> = Vec::new();
+    if !input.is_empty() {
+        scope.push((input.clone(), 0));
+    }
+    let props = parsed.iter().find_map(|l| match &l.kind {
+        LineKind::DtoDef { name, properties } if name == input => Some(properties),
+        _ => None,
+    });
+    for prop in props.into_iter().flatten() {
+        let (prop, _) = split_optional(prop);
+        let name = match parse_array_property(prop) {
+            Some((base, suffix)) => format!("{}{}", base, suffix),
+            None => prop.to_string(),
+        };
+        scope.push((name, 0));
+    }
+
+    for parsed_line in &parsed[req_idx + 1..line.min(parsed.len())] {
+        let (output, indent) = match &parsed_line.kind {
+            LineKind::Step { output, indent, .. }
+            | LineKind::BoundaryStep { output, indent, .. }
+            | LineKind::Ply { output, indent, .. } => (output, *indent),
+            LineKind::Cse { indent, .. } => {
+                scope.retain(|(_, i)| *i < *indent);
+                continue;
+            }
+            _ => continue,
+        };
+        scope.retain(|(_, i)| *i <= indent);
+        let (output, _) = normalize_type(output);
+        if !output.is_empty() && output != "void" {
+            scope.push((output.to_string(), indent));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    scope.into_iter().map(|(name, _)| name).filter(|name| seen.insert(name.clone())).collect()
+}
+
+/// A param naming a DTO must be one the step can actually see (the REQ input
+/// or an earlier output), not merely one that is defined somewhere.
+fn check_dto_params(
+    diagnostics: &mut Vec<Diagnostic>,
+    lines: &[ParsedLine],
+    line_num: usize,
+    params: &[String],
+    defined_dtos: &HashSet<String>,
+) {
+    let dto_params: Vec<&str> = params.iter().map(|p| normalize_type(p).0).filter(|p| defined_dtos.contains(*p)).collect();
+    if dto_params.is_empty() {
+        return;
+    }
+    let Some(idx) = lines.iter().position(|l| l.line_num == line_num) else { return };
+    let scope = scope_at(lines, idx);
+    for param in dto_params {
+        if !scope.iter().any(|name| name == param) {
+            diagnostics.push(diag_err(DiagnosticCode::DtoParamNotInScope, line_num, format!(
+                "'{}' is not in scope: no earlier step returns it and it is not the REQ input",
+                param)));
+        }
+    }
+}
+
+/// A `noun.verb` (or `Noun::verb`) must keep one signature throughout a document.
+#[allow(clippy::too_many_arguments)]
+fn check_sig(
+    diagnostics: &mut Vec<Diagnostic>,
+    sigs: &mut HashMap<String, (usize, Vec<String>, String)>,
+    line_num: usize,
+    verb_span: Option<Span>,
+    noun: &str,
+    verb: &str,
+    is_static: bool,
+    params: &[String],
+    output: &str,
+) {
+    let key = signature_key(noun, verb, is_static);
+    if let Some((first_line, first_params, first_output)) = sigs.get(&key) {
+        if first_params != params || first_output != output {
+            diagnostics.push(diag_err_at(DiagnosticCode::InconsistentSignature, line_num, verb_span, format!(
+                "Inconsistent signature for '{}': expected ({}) -> {} (from line {}), got ({}) -> {}",
+                key,
+                first_params.join(", "),
+                first_output,
+                first_line + 1,
+                params.join(", "),
+                output)));
+        }
+    } else {
+        sigs.insert(key, (line_num, params.to_vec(), output.to_string()));
+    }
+}
+
+/// Whether the `[CSE]` on `case_line` is followed by a step before the next
+/// case, a dedent out of the [PLY] block, a blank line or the end of the file.
+fn case_has_steps(lines: &[ParsedLine], case_line: usize) -> bool {
+    let Some(i) = lines.iter().position(|l| l.line_num == case_line) else { return false };
+    let LineKind::Cse { indent: case_indent, .. } = lines[i].kind else { return false };
+    for l in &lines[i + 1..] {
+        match &l.kind {
+            LineKind::Comment { .. } => continue,
+            LineKind::Step { indent, .. }
+            | LineKind::BoundaryStep { indent, .. }
+            | LineKind::Ply { indent, .. }
+            | LineKind::New { indent, .. }
+            | LineKind::Ret { indent, .. } => return *indent >= case_indent,
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// Line and output of the last step (or [RET]) directly in the `[CSE]` on
+/// `case_line`; a nested [PLY] counts as one step. The case ends at the next
+/// case, a dedent out of it, a blank line or a definition.
+fn case_last_step(lines: &[ParsedLine], case_line: usize) -> Option<(usize, &str)> {
+    let i = lines.iter().position(|l| l.line_num == case_line)?;
+    let LineKind::Cse { indent: case_indent, .. } = lines[i].kind else { return None };
+    let mut last = None;
+    for l in &lines[i + 1..] {
+        match &l.kind {
+            LineKind::Step { indent, output, .. }
+            | LineKind::BoundaryStep { indent, output, .. }
+            | LineKind::Ply { indent, output, .. }
+            | LineKind::Ret { indent, value: output } => {
+                if *indent < case_indent {
+                    break;
+                }
+                if *indent == case_indent {
+                    last = Some((l.line_num, output.as_str()));
+                }
+            }
+            LineKind::New { indent, .. } if *indent < case_indent => break,
+            LineKind::Cse { indent, .. } if *indent <= case_indent => break,
+            LineKind::New { .. }
+            | LineKind::Cse { .. }
+            | LineKind::Fault { .. }
+            | LineKind::Comment { .. }
+            | LineKind::MultilineContinuation { .. } => {}
+            _ => break,
+        }
+    }
+    last
+}
+
+/// Members of a string-union type (`"genie" | "fiveNine"`), or None when
+/// `type_name` is anything else.
+pub fn union_members(type_name: &str) -> Option<Vec<&str>> {
+    if !type_name.contains('|') {
+        return None;
+    }
+    type_name
+        .split('|')
+        .map(|m| m.trim().strip_prefix('"').and_then(|m| m.strip_suffix('"')))
+        .collect()
+}
+
+pub fn signature_key(noun: &str, verb: &str, is_static: bool) -> String {
+    let sep = if is_static { "::" } else { "." };
+    format!("{}{}{}", noun, sep, verb)
+}
+
+/// First recorded signature of every `noun.verb`/`Noun::verb` in `lines`,
+/// keyed the same way `check_sig` keys them.
+pub fn method_signatures<'a>(lines: impl IntoIterator<Item = &'a ParsedLine>) -> HashMap<String, (usize, Vec<String>, String)> {
+    let mut sigs = HashMap::new();
+    for parsed_line in lines {
+        match &parsed_line.kind {
+            LineKind::Step { noun, verb, params, output, is_static, .. }
+            | LineKind::BoundaryStep { noun, verb, params, output, is_static, .. }
+            | LineKind::Ply { noun, verb, params, output, is_static, .. } => {
+                sigs.entry(signature_key(noun, verb, *is_static))
+                    .or_insert_with(|| (parsed_line.line_num, params.clone(), output.clone()));
+            }
+            _ => {}
+        }
+    }
+    sigs
+}
+
+/// Check if a type is a raw primitive (string, number, boolean, etc.)
+fn is_primitive(s: &str) -> bool {
+    matches!(
+        s,
+        "string" | "number" | "boolean" | "void" | "Uint8Array" | "Primitive"
+    )
+}
+
+/// Base type and whether it is a list: `FooDto[]` and `Array<FooDto>` both
+/// give `("FooDto", true)`, `FooDto` gives `("FooDto", false)`.
+pub fn normalize_type(s: &str) -> (&str, bool) {
+    let s = s.trim();
+    if let Some(inner) = s.strip_suffix("[]") {
+        return (normalize_type(inner).0, true);
+    }
+    if let Some(inner) = s.strip_prefix("Array<").and_then(|r| r.strip_suffix('>')) {
+        return (normalize_type(inner).0, true);
+    }
+    (s, false)
+}
+
+/// Check if a value is valid for boundary crossing:
+/// - DTOs (ends in "Dto")
+/// - Raw primitives (string, number, boolean, void, Uint8Array)
+/// - Type names that resolve to primitives (e.g., `url: string`)
+fn is_dto_or_primitive(s: &str, defined_types: &HashMap<String, String>) -> bool {
+    // A list crosses a boundary when its element does
+    let (s, _) = normalize_type(s);
+
+    // DTOs are always valid at boundaries
+    if s.ends_with("Dto") {
+        return true;
+    }
+
+    // Raw primitives are valid
+    if is_primitive(s) {
+        return true;
+    }
+
+    // Check if it's a type name that resolves to a primitive
+    if let Some(underlying_type) = defined_types.get(s) {
+        return is_primitive(underlying_type);
+    }
+
+    false
+}
+
+/// Check if a type expression is valid for [TYP] definitions
+/// Valid: primitives, generics (Array<T>, Record<K,V>), tuples ([a, b]), string enums
+fn is_valid_primitive_type(s: &str) -> bool {
+    let s = s.trim();
+
+    // Raw primitives
+    if is_primitive(s) {
+        return true;
+    }
+
+    // String enum types like "genie" | "fiveNine"
+    if s.contains('"') && s.contains('|') {
+        return true;
+    }
+
+    // Generic types like Array<url>, Record<string, Primitive>
+    if s.contains('<') && s.ends_with('>') {
+        let base = s.split('<').next().unwrap_or("");
+        // Allow any generic - the inner types will be validated separately if needed
+        return matches!(base, "Array" | "Record" | "Map" | "Set" | "Promise" | "Partial" | "Required" | "Pick" | "Omit" | "ReturnType");
+    }
+
+    // Tuple types like [id, name]; members are checked by `tuple_members`
+    if s.starts_with('[') && s.ends_with(']') {
+        return true;
+    }
+
+    false
+}
+
+/// Members of a tuple type: `[id, name]` -> `["id", "name"]`
+fn tuple_members(s: &str) -> Option<Vec<&str>> {
+    let inner = s.trim().strip_prefix('[')?.strip_suffix(']')?;
+    Some(inner.split(',').map(str::trim).filter(|m| !m.is_empty()).collect())
+}
+
+/// Validate a `[TYP:...]` constraint-modifier list (e.g. `ext,uuid` or
+/// `min=0,max=100`) against the design contract §5. Returns one message per
+/// problem, byte-identical to the TS engine + studio so all three emit the
+/// same diagnostics. `name` is the type name, `declared_type` the primitive it
+/// aliases (e.g. "string", "number").
+/// Mirrors the TS engine's `^-?\d+(\.\d+)?$` numeric-value check exactly:
+/// plain decimals only — no exponents, no leading `+`, no bare `.5` / `5.`.
+fn is_plain_decimal(v: &str) -> bool {
+    let s = v.strip_prefix('-').unwrap_or(v);
+    let mut parts = s.splitn(2, '.');
+    let all_digits = |p: &str| !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit());
+    let int = parts.next().unwrap_or("");
+    let frac = parts.next();
+    all_digits(int) && frac.is_none_or(all_digits)
+}
+
+fn validate_typ_modifiers(raw: &str, name: &str, declared_type: &str) -> Vec<String> {
+    let mut errors = Vec::new();
+    for item in raw.split(',') {
+        let item = item.trim();
+        if item.is_empty() {
+            continue;
+        }
+        // `min=0` splits into id + value; bare modifiers have no value.
+        // NO trim around '=' — the TS engine slices at indexOf('=') verbatim,
+        // so `min = 5` yields the unknown modifier `min ` there; mirror that.
+        let (id, value) = match item.split_once('=') {
+            Some((i, v)) => (i, Some(v)),
+            None => (item, None),
+        };
+        // Required base type per modifier; None = ext/core/example (no base requirement).
+        let base: Option<&str> = match id {
+            "ext" | "core" | "example" => None,
+            "uuid" | "email" | "url" | "nonempty" => Some("string"),
+            "int" | "min" | "max" | "positive" => Some("number"),
+            _ => {
+                errors.push(format!(
+                    "[TYP] unknown modifier \"{}\" (allowed: ext, core, uuid, email, url, nonempty, int, min=<n>, max=<n>, positive, example=<value>)",
+                    id
+                ));
+                continue;
+            }
+        };
+        let takes_value = id == "min" || id == "max";
+        let takes_text = id == "example";
+        if takes_value {
+            let numeric = value.map(is_plain_decimal).unwrap_or(false);
+            if !numeric {
+                errors.push(format!(
+                    "[TYP] modifier \"{}\" requires a numeric value (e.g. min=0)",
+                    id
+                ));
+                continue;
+            }
+        } else if takes_text {
+            // Free-text value, mirrors the TS engine: required and non-empty.
+            if value.is_none_or(|v| v.is_empty()) {
+                errors.push(format!(
+                    "[TYP] modifier \"{}\" requires a value (e.g. example=orders)",
+                    id
+                ));
+                continue;
+            }
+        } else if value.is_some() {
+            errors.push(format!("[TYP] modifier \"{}\" does not take a value", id));
+            continue;
+        }
+        if let Some(b) = base
+            && declared_type != b
+        {
+            errors.push(format!(
+                "[TYP] modifier \"{}\" requires a {} type, but \"{}\" is {}",
+                id, b, name, declared_type
+            ));
+        }
+    }
+    errors
+}
+
+/// A `[PLY]` block and the `[CSE]` names branching under it
+struct PolyBlock<'a> {
+    noun: &'a str,
+    output: &'a str,
+    line: usize,
+    indent: usize,
+    cases: Vec<(&'a str, usize)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &str = "[REQ] user.get(IdDto): UserDto
+    db:user.load(IdDto): UserDto
+      not-found
+
+[DTO] IdDto: id
+    the IdDto lookup key
+[DTO] UserDto: id, name
+    a user
+[TYP] id: string
+    an id
+[TYP] name: string
+    a name
+";
+
+    fn validate(text: &str) -> Vec<Diagnostic> {
+        validate_document(text, ValidateOptions { config: &Config::default(), siblings: &SymbolTable::default() })
+    }
+
+    fn codes(text: &str) -> Vec<DiagnosticCode> {
+        validate(text).into_iter().map(|d| d.code).collect()
+    }
+
+    #[test]
+    fn valid_document_is_clean() {
+        assert!(validate(VALID).is_empty(), "{:?}", validate(VALID));
+        assert!(validate("").is_empty());
+    }
+
+    #[test]
+    fn req_must_take_and_return_dtos() {
+        let text = VALID.replace("[REQ] user.get(IdDto): UserDto", "[REQ] user.get(id): UserDto");
+        let diags = validate(&text);
+        assert!(diags.iter().any(|d| d.code == DiagnosticCode::RequirementShape
+            && d.message == "REQ input must be a DTO, got 'id'"));
+        assert!(diags.iter().all(|d| d.severity == Severity::Error));
+    }
+
+    #[test]
+    fn last_step_must_produce_the_output() {
+        let text = VALID.replace("db:user.load(IdDto): UserDto", "db:user.load(IdDto): IdDto");
+        let diags = validate(&text);
+        let mismatch = diags.iter().find(|d| d.code == DiagnosticCode::ReturnMismatch).unwrap();
+        assert_eq!(mismatch.line, 1);
+        assert_eq!(mismatch.message, "Last step must return 'UserDto' (REQ output), got 'IdDto'");
+        assert!(mismatch.span.is_some(), "points at the step's output type");
+    }
+
+    #[test]
+    fn step_outside_req_is_out_of_scope() {
+        let text = "[DTO] IdDto: id\n    an id\n[TYP] id: string\n    an id\n    db:user.load(IdDto): IdDto\n";
+        assert!(codes(text).contains(&DiagnosticCode::OutOfScope));
+    }
+
+    #[test]
+    fn duplicate_definitions_and_dto_names() {
+        let text = format!("{}[DTO] IdDto: id\n    again\n[DTO] User: name\n    no suffix\n", VALID);
+        let codes = codes(&text);
+        assert!(codes.contains(&DiagnosticCode::DuplicateDefinition));
+        assert!(codes.contains(&DiagnosticCode::DtoName));
+    }
+
+    #[test]
+    fn dto_without_description_is_flagged() {
+        let text = VALID.replace("    the IdDto lookup key\n", "");
+        assert_eq!(codes(&text), vec![DiagnosticCode::DtoMissingDescription]);
+    }
+
+    #[test]
+    fn undefined_property_type_unless_a_sibling_defines_it() {
+        let text = VALID.replace("[TYP] name: string\n    a name\n", "");
+        let diags = validate(&text);
+        assert_eq!(diags.len(), 1, "{:?}", diags);
+        assert_eq!(diags[0].code, DiagnosticCode::UndefinedReference);
+        assert_eq!(diags[0].line, 6);
+
+        let mut siblings = SymbolTable::default();
+        siblings.add_lines(&crate::parse_document("[TYP] name: string\n    a name\n"));
+        let opts = ValidateOptions { config: &Config::default(), siblings: &siblings };
+        assert!(validate_document(&text, opts).is_empty());
+    }
+
+    #[test]
+    fn cyclic_dtos_are_reported() {
+        let text = "[DTO] OrderDto: customer\n    an order\n[DTO] CustomerDto: order\n    a customer\n";
+        let cycles: Vec<String> = validate(text)
+            .into_iter()
+            .filter(|d| d.code == DiagnosticCode::CyclicDto)
+            .map(|d| d.message)
+            .collect();
+        assert_eq!(cycles, vec!["Cyclic DTO reference: OrderDto -> CustomerDto -> OrderDto"]);
+    }
+
+    #[test]
+    fn poly_cases_must_match_the_union() {
+        let text = "[REQ] call.get(CallDto): DataDto
+    [PLY] provider.get(CallDto): DataDto
+        [CSE] genie
+        ex:genie.fetch(CallDto): DataDto
+        [CSE] acme
+        ex:acme.fetch(CallDto): DataDto
+
+[DTO] CallDto: id
+    a call
+[DTO] DataDto: id
+    the data
+[TYP] id: string
+    an id
+[TYP] provider: \"genie\" | \"fiveNine\"
+    the provider
+";
+        let codes = codes(text);
+        assert!(codes.contains(&DiagnosticCode::CaseNotInUnion));
+        assert!(codes.contains(&DiagnosticCode::MissingUnionCase));
+    }
+
+    #[test]
+    fn doc_blocks_are_never_validated() {
+        let text = format!("{}
+[DOC] Rationale
+    user.load(): whatever // not a step
+	indented with a tab, and {}
+
+    [REQ] bad.shape(id): id
+      orphan-fault
+", VALID, "x".repeat(90));
+        assert!(validate(&text).is_empty(), "{:?}", validate(&text));
+    }
+
+    #[test]
+    fn poly_case_must_return_the_poly_output() {
+        let text = "[REQ] call.get(CallDto): DataDto
+    [PLY] provider.get(CallDto): DataDto
+        [CSE] genie
+        ex:genie.fetch(CallDto): DataDto
+          timeout
+        [CSE] acme
+        ex:acme.fetch(CallDto): CallDto
+        // the data is still missing
+        [CSE] local
+        [RET] DataDto
+    [RET] DataDto
+
+[DTO] CallDto: id
+    a call
+[DTO] DataDto: id
+    the data
+[TYP] id: string
+    an id
+";
+        let diags = validate(text);
+        assert_eq!(diags.len(), 1, "{:?}", diags);
+        assert_eq!((diags[0].code, diags[0].line), (DiagnosticCode::ReturnMismatch, 6));
+        assert_eq!(diags[0].message, "Last step of case 'acme' must return 'DataDto' ([PLY] output), got 'CallDto'");
+        assert_eq!(diags[0].span, Some(Span { start: 32, end: 39 }));
+    }
+
+    #[test]
+    fn long_lines_and_tabs_carry_spans() {
+        let long = format!("// {}\n", "x".repeat(90));
+        let diags = validate(&long);
+        assert_eq!(diags[0].code, DiagnosticCode::LineTooLong);
+        assert_eq!(diags[0].span, Some(Span { start: 80, end: 93 }));
+
+        let tabbed = VALID.replace("    db:user", "\tdb:user");
+        let tab = validate(&tabbed).into_iter().find(|d| d.code == DiagnosticCode::Indentation).unwrap();
+        assert_eq!((tab.line, tab.span), (1, Some(Span { start: 0, end: 1 })));
+    }
+
+    #[test]
+    fn unparseable_lines_are_parse_errors() {
+        let text = VALID.replace("      not-found\n", "      not-found\n    user.load\n");
+        let parse = validate(&text).into_iter().find(|d| d.code == DiagnosticCode::ParseError).unwrap();
+        assert_eq!(parse.line, 3);
+        assert_eq!(parse.message, "Parse error: missing parameters, expected 'noun.verb(args): type'");
+    }
+
+    #[test]
+    fn trailing_comma_in_dto_properties_warns() {
+        let text = "[DTO] FooDto: id, name,  // half done\n    a foo\n[TYP] id: string\n    an id\n[TYP] name: string\n    a name\n";
+        let diags = validate(text);
+        assert_eq!(diags.len(), 1, "{:?}", diags);
+        assert_eq!(diags[0].code, DiagnosticCode::TrailingComma);
+        assert_eq!(diags[0].severity, Severity::Warning);
+        assert_eq!((diags[0].line, diags[0].span), (0, Some(Span { start: 22, end: 23 })));
+
+        let lines = crate::parse_document(text);
+        assert!(matches!(&lines[0].kind, LineKind::DtoDef { properties, .. } if properties == &["id", "name"]));
+    }
+
+    #[test]
+    fn near_duplicate_req_noun_is_a_typo() {
+        let text = "[REQ] recording.register(IdDto): IdDto
+    db:recording.save(IdDto): IdDto
+
+
+[REQ] recording.delete(IdDto): IdDto
+    db:recording.delete(IdDto): IdDto
+
+
+[REQ] recordng.get(IdDto): IdDto
+    db:recording.load(IdDto): IdDto
+
+[DTO] IdDto: id
+    an id
+[TYP] id: string
+    the id
+";
+        assert!(validate(text).is_empty());
+        let config = Config { warn_noun_typos: true, ..Config::default() };
+        let diags = validate_document(text, ValidateOptions { config: &config, siblings: &SymbolTable::default() });
+        assert_eq!(diags.len(), 1, "{:?}", diags);
+        assert_eq!((diags[0].code, diags[0].line), (DiagnosticCode::NounTypo, 8));
+        assert_eq!(diags[0].message, "Noun 'recordng' is used once; did you mean 'recording'?");
+    }
+
+    #[test]
+    fn noun_that_is_never_produced() {
+        let text = "[REQ] toy.spin(IdDto): IdDto
+    widget.foo(IdDto): IdDto
+    gear::create(IdDto): gear
+    gear.turn(IdDto): IdDto
+    db:motor.load(IdDto): IdDto
+    motor.run(IdDto): IdDto
+    widget.bar(IdDto): IdDto
+
+[DTO] IdDto: id
+    an id
+[TYP] id: string
+    the id
+";
+        assert!(validate(text).is_empty());
+        let config = Config { warn_unproduced_nouns: true, ..Config::default() };
+        let diags = validate_document(text, ValidateOptions { config: &config, siblings: &SymbolTable::default() });
+        assert_eq!(diags.len(), 1, "{:?}", diags);
+        assert_eq!((diags[0].code, diags[0].line), (DiagnosticCode::UnproducedNoun, 1));
+        assert_eq!(diags[0].message, "Noun 'widget' is only used as an instance; no step returns it and nothing constructs it");
+    }
+
+    #[test]
+    fn fault_vocabulary_flags_unknown_pure_faults() {
+        let text = "[REQ] order.place(OrderDto): OrderDto
+    db:order.save(OrderDto): OrderDto
+      out-of-stock
+    cart.total(OrderDto): OrderDto
+      out-of-stock not-found emty-cart empty-cart
+
+[DTO] OrderDto: id
+    an order
+[TYP] id: string
+    the id
+";
+        assert!(validate(text).is_empty());
+        let config = Config { faults: Some(vec!["empty-cart".to_string()]), ..Config::default() };
+        let diags = validate_document(text, ValidateOptions { config: &config, siblings: &SymbolTable::default() });
+        assert_eq!(diags.len(), 1, "{:?}", diags);
+        assert_eq!((diags[0].code, diags[0].line), (DiagnosticCode::UnknownFault, 4));
+        assert_eq!(diags[0].message, "Unknown fault 'emty-cart': not raised by any boundary and not in the fault vocabulary");
+    }
+
+    #[test]
+    fn type_descriptions_are_opt_in() {
+        let text = "[TYP] id: string
+[TYP] name: string
+    a name
+[NON] storage
+[NON] cache
+    a cache
+";
+        assert!(validate(text).is_empty());
+        let config = Config { require_type_descriptions: true, ..Config::default() };
+        let diags = validate_document(text, ValidateOptions { config: &config, siblings: &SymbolTable::default() });
+        let mut found: Vec<(usize, &str)> = diags.iter().map(|d| (d.line, d.message.as_str())).collect();
+        found.sort();
+        assert_eq!(found, vec![
+            (0, "Type 'id' is missing a description (add a 4-space indented description on the next line)"),
+            (3, "Noun 'storage' is missing a description (add a 4-space indented description on the next line)"),
+        ]);
+        assert!(diags.iter().all(|d| d.code == DiagnosticCode::TypeMissingDescription && d.severity == Severity::Warning));
+    }
+
+    #[test]
+    fn miscased_faults_suggest_the_hyphenated_form() {
+        let text = "[REQ] user.get(UserIdDto): UserDto
+    db:user.find(UserIdDto): UserDto
+      not_found
+      notFound timed-out
+    [RET] UserDto
+";
+        let diags = validate(text);
+        let found: Vec<(usize, &str)> = diags.iter().map(|d| (d.line, d.message.as_str())).collect();
+        assert_eq!(found, vec![
+            (2, "Fault names are lowercase and hyphenated, did you mean not-found?"),
+            (3, "Fault names are lowercase and hyphenated, did you mean not-found timed-out?"),
+        ]);
+        assert!(diags.iter().all(|d| d.code == DiagnosticCode::FaultName));
+
+        assert_eq!(canonical_faults("timeout"), None);
+        assert_eq!(canonical_faults("HTTP_error"), Some("http-error".to_string()));
+        assert_eq!(canonical_faults("user.find"), None);
+    }
+
+    #[test]
+    fn edit_distance_of_one() {
+        assert!(within_one_edit("recording", "recordng"));
+        assert!(within_one_edit("user", "users"));
+        assert!(within_one_edit("user", "usar"));
+        assert!(within_one_edit("user", "user"));
+        assert!(!within_one_edit("user", "uzar"));
+        assert!(!within_one_edit("user", "order"));
+        assert!(!within_one_edit("ab", "ba"));
+    }
+
+    #[test]
+    fn tuple_members_must_be_primitives() {
+        let text = "[DTO] FooDto: id\n    a foo\n[TYP] id: string\n    an id\n[TYP] pair: [id, FooDto]\n    a pair\n";
+        let diags = validate(text);
+        assert_eq!(diags.len(), 1, "{:?}", diags);
+        assert_eq!(diags[0].code, DiagnosticCode::NonPrimitiveType);
+        assert_eq!(diags[0].line, 4);
+        assert_eq!(diags[0].message, "Tuple member 'FooDto' must be a primitive");
+
+        assert!(validate("[TYP] point: [number, number]\n    x and y\n").is_empty());
+    }
+
+    #[test]
+    fn both_array_spellings_reject_dtos_in_types() {
+        for spelling in ["UrlDto[]", "Array<UrlDto>"] {
+            let diags = validate(&format!("[TYP] urls: {}\n    some urls\n", spelling));
+            assert_eq!(diags.len(), 1, "{}: {:?}", spelling, diags);
+            assert_eq!(diags[0].code, DiagnosticCode::NonPrimitiveType);
+            assert_eq!(diags[0].message, "Type 'urls' cannot reference DTO 'UrlDto' - types must be primitives");
+        }
+        let text = "[TYP] url: string\n    a url\n[TYP] urls: url[]\n    some urls\n[TYP] links: Array<url>\n    some links\n";
+        assert!(validate(text).is_empty());
+    }
+
+    #[test]
+    fn opt_in_rules_follow_the_config() {
+        let text = "[REQ] user.update(UpdateDto): UserDto
+    db:user.save(UserDto): UserDto
+
+[DTO] UpdateDto: name
+    the update
+[DTO] UserDto: name
+    a user
+[TYP] name: string
+    a name
+";
+        assert!(validate(text).is_empty());
+        let config = Config { strict_dto_params: true, ..Config::default() };
+        let strict = validate_document(text, ValidateOptions { config: &config, siblings: &SymbolTable::default() });
+        assert_eq!(strict.iter().map(|d| d.code).collect::<Vec<_>>(), vec![DiagnosticCode::DtoParamNotInScope]);
+    }
+
+    // --- [TYP] constraint-modifier validator (design §5) -------------------
+
+    #[test]
+    fn typ_modifier_ok_compose() {
+        assert!(validate_typ_modifiers("ext,uuid", "externalId", "string").is_empty());
+        assert!(validate_typ_modifiers("min=0,max=100", "qty", "number").is_empty());
+        assert!(validate_typ_modifiers("nonempty", "name", "string").is_empty());
+        assert!(validate_typ_modifiers("core", "id", "string").is_empty());
+        assert!(validate_typ_modifiers("int", "count", "number").is_empty());
+        assert!(validate_typ_modifiers("positive", "amount", "number").is_empty());
+        assert!(validate_typ_modifiers("example=orders", "tableName", "string").is_empty());
+        assert!(validate_typ_modifiers("ext,example=42", "qty", "number").is_empty());
+    }
+
+    #[test]
+    fn typ_modifier_unknown() {
+        assert_eq!(
+            validate_typ_modifiers("bogus", "id", "string"),
+            vec!["[TYP] unknown modifier \"bogus\" (allowed: ext, core, uuid, email, url, nonempty, int, min=<n>, max=<n>, positive, example=<value>)".to_string()]
+        );
+    }
+
+    #[test]
+    fn typ_modifier_example_needs_value() {
+        assert_eq!(
+            validate_typ_modifiers("example", "tableName", "string"),
+            vec!["[TYP] modifier \"example\" requires a value (e.g. example=orders)".to_string()]
+        );
+        assert_eq!(
+            validate_typ_modifiers("example=", "tableName", "string"),
+            vec!["[TYP] modifier \"example\" requires a value (e.g. example=orders)".to_string()]
+        );
+    }
+
+    #[test]
+    fn typ_modifier_wrong_base() {
+        assert_eq!(
+            validate_typ_modifiers("uuid", "count", "number"),
+            vec!["[TYP] modifier \"uuid\" requires a string type, but \"count\" is number".to_string()]
+        );
+        assert_eq!(
+            validate_typ_modifiers("int", "name", "string"),
+            vec!["[TYP] modifier \"int\" requires a number type, but \"name\" is string".to_string()]
+        );
+    }
+
+    #[test]
+    fn typ_modifier_bad_value() {
+        assert_eq!(
+            validate_typ_modifiers("min", "qty", "number"),
+            vec!["[TYP] modifier \"min\" requires a numeric value (e.g. min=0)".to_string()]
+        );
+        assert_eq!(
+            validate_typ_modifiers("max=abc", "qty", "number"),
+            vec!["[TYP] modifier \"max\" requires a numeric value (e.g. min=0)".to_string()]
+        );
+    }
+
+    #[test]
+    fn typ_modifier_unexpected_value() {
+        assert_eq!(
+            validate_typ_modifiers("uuid=5", "id", "string"),
+            vec!["[TYP] modifier \"uuid\" does not take a value".to_string()]
+        );
+    }
+
+    // Parity with the TS engine's `^-?\d+(\.\d+)?$` value check: the f64
+    // grammar (exponents, leading +, bare dots) must be REJECTED, and
+    // whitespace around `=` is NOT trimmed (`min = 5` → unknown "min ").
+    #[test]
+    fn typ_modifier_value_grammar_matches_engine() {
+        let bad = |raw: &str| {
+            assert_eq!(
+                validate_typ_modifiers(raw, "qty", "number"),
+                vec!["[TYP] modifier \"min\" requires a numeric value (e.g. min=0)".to_string()],
+                "expected bad-value for {raw}"
+            );
+        };
+        bad("min=1e3");
+        bad("min=+5");
+        bad("min=.5");
+        bad("min=5.");
+        bad("min=");
+        assert!(validate_typ_modifiers("min=-3", "qty", "number").is_empty());
+        assert!(validate_typ_modifiers("min=1.25", "qty", "number").is_empty());
+        assert_eq!(
+            validate_typ_modifiers("min = 5", "qty", "number"),
+            vec!["[TYP] unknown modifier \"min \" (allowed: ext, core, uuid, email, url, nonempty, int, min=<n>, max=<n>, positive, example=<value>)".to_string()]
+        );
+    }
+
+    #[test]
+    fn normalize_type_strips_both_array_spellings() {
+        assert_eq!(normalize_type("FooDto[]"), ("FooDto", true));
+        assert_eq!(normalize_type("Array<FooDto>"), ("FooDto", true));
+        assert_eq!(normalize_type("FooDto[][]"), ("FooDto", true));
+        assert_eq!(normalize_type("Array<FooDto[]>"), ("FooDto", true));
+        assert_eq!(normalize_type("FooDto"), ("FooDto", false));
+    }
+}
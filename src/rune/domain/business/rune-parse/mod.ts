@@ -598,7 +598,7 @@ const BUILTIN_TAGS: ParseTagSpec[] = [
   { id: "non", tag: "[NON]" },
   { id: "ply", tag: "[PLY]" },
   { id: "cse", tag: "[CSE]" },
-  { id: "new", tag: "[NEW]", synonyms: ["[CTR]"] },
+  { id: "new", tag: "[CTR]", synonyms: ["[NEW]"] },
   { id: "ret", tag: "[RET]" },
 ];
 